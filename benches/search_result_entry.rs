@@ -0,0 +1,41 @@
+//! Benchmark of `SearchResultEntry` parsing
+//!
+//! Run with `cargo bench --bench search_result_entry`. The number of iterations can be set
+//! using the first argument.
+
+use ldap_parser::ldap::SearchResultEntry;
+use ldap_parser::FromBer;
+use std::hint::black_box;
+use std::time::Instant;
+
+static SEARCH_RESULT_ENTRY: &[u8] = include_bytes!("../assets/search_result_entry.bin");
+
+fn bench<F: FnMut()>(name: &str, iterations: u32, mut f: F) {
+    // warm up
+    for _ in 0..iterations / 10 {
+        f();
+    }
+    let start = Instant::now();
+    for _ in 0..iterations {
+        f();
+    }
+    let elapsed = start.elapsed();
+    println!(
+        "{:<32} {:>10.1} ns/iter ({} iterations)",
+        name,
+        elapsed.as_nanos() as f64 / f64::from(iterations),
+        iterations
+    );
+}
+
+fn main() {
+    let iterations = std::env::args()
+        .skip(1)
+        .find(|arg| !arg.starts_with('-'))
+        .and_then(|arg| arg.parse().ok())
+        .unwrap_or(1_000_000);
+    bench("search_result_entry", iterations, || {
+        let res = SearchResultEntry::from_ber(black_box(SEARCH_RESULT_ENTRY));
+        black_box(res.expect("parsing failed"));
+    });
+}
@@ -0,0 +1,252 @@
+//! Builders for LDAP requests
+//!
+//! The builders create owned (`'static`) objects, which is useful to write test fixtures or fuzz
+//! seeds without filling every field manually.
+//!
+//! ```rust
+//! use ldap_parser::builder::SearchRequestBuilder;
+//! use ldap_parser::ldap::SearchScope;
+//! use ldap_parser::parse_filter_str;
+//!
+//! let filter = parse_filter_str("(uid=jsmith)").expect("invalid filter");
+//! let req = SearchRequestBuilder::new()
+//!     .base("dc=example,dc=com")
+//!     .scope(SearchScope::SingleLevel)
+//!     .filter(&filter)
+//!     .attributes(["cn", "mail"])
+//!     .build();
+//! assert_eq!(req.attributes.len(), 2);
+//! ```
+
+use crate::filter::Filter;
+use crate::ldap::*;
+use asn1_rs::ToStatic;
+use std::borrow::Cow;
+
+/// Builder for [`SearchRequest`]
+///
+/// Defaults are an empty base object, scope `wholeSubtree`, `neverDerefAliases`, no size and
+/// time limits, `typesOnly` false, filter `(objectClass=*)`, and no attributes (all user
+/// attributes are returned).
+#[derive(Clone, Debug)]
+pub struct SearchRequestBuilder {
+    req: SearchRequest<'static>,
+}
+
+impl SearchRequestBuilder {
+    /// Create a new builder, with default values
+    pub fn new() -> Self {
+        let req = SearchRequest {
+            base_object: LdapDN(Cow::Borrowed("")),
+            scope: SearchScope::WholeSubtree,
+            deref_aliases: DerefAliases::NeverDerefAliases,
+            size_limit: 0,
+            time_limit: 0,
+            types_only: false,
+            filter: Filter::Present(LdapString(Cow::Borrowed("objectClass"))),
+            attributes: Vec::new(),
+        };
+        SearchRequestBuilder { req }
+    }
+
+    /// Set the base object
+    pub fn base(mut self, dn: &str) -> Self {
+        self.req.base_object = LdapDN(Cow::Owned(dn.to_owned()));
+        self
+    }
+
+    /// Set the search scope
+    pub fn scope(mut self, scope: SearchScope) -> Self {
+        self.req.scope = scope;
+        self
+    }
+
+    /// Set how aliases are dereferenced
+    pub fn deref(mut self, deref_aliases: DerefAliases) -> Self {
+        self.req.deref_aliases = deref_aliases;
+        self
+    }
+
+    /// Set the size limit (0 means no limit)
+    pub fn size_limit(mut self, size_limit: u32) -> Self {
+        self.req.size_limit = size_limit;
+        self
+    }
+
+    /// Set the time limit, in seconds (0 means no limit)
+    pub fn time_limit(mut self, time_limit: u32) -> Self {
+        self.req.time_limit = time_limit;
+        self
+    }
+
+    /// Request only attribute types, without values
+    pub fn types_only(mut self, types_only: bool) -> Self {
+        self.req.types_only = types_only;
+        self
+    }
+
+    /// Set the filter (the filter is copied)
+    pub fn filter(mut self, filter: &Filter) -> Self {
+        self.req.filter = filter.to_static();
+        self
+    }
+
+    /// Set the list of requested attributes
+    pub fn attributes<I, S>(mut self, attributes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.req.attributes = attributes
+            .into_iter()
+            .map(|s| LdapString(Cow::Owned(s.as_ref().to_owned())))
+            .collect();
+        self
+    }
+
+    /// Build the search request
+    pub fn build(self) -> SearchRequest<'static> {
+        self.req
+    }
+}
+
+impl Default for SearchRequestBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Builder for [`BindRequest`]
+///
+/// Defaults are version 3, and anonymous simple authentication (empty name and password).
+#[derive(Clone, Debug)]
+pub struct BindRequestBuilder {
+    req: BindRequest<'static>,
+}
+
+impl BindRequestBuilder {
+    /// Create a new builder, with default values
+    pub fn new() -> Self {
+        let req = BindRequest {
+            version: 3,
+            name: LdapDN(Cow::Borrowed("")),
+            authentication: AuthenticationChoice::Simple(Cow::Borrowed(b"")),
+        };
+        BindRequestBuilder { req }
+    }
+
+    /// Set the protocol version
+    pub fn version(mut self, version: u8) -> Self {
+        self.req.version = version;
+        self
+    }
+
+    /// Set the name of the directory object to bind as
+    pub fn name(mut self, dn: &str) -> Self {
+        self.req.name = LdapDN(Cow::Owned(dn.to_owned()));
+        self
+    }
+
+    /// Use simple authentication, with the given password
+    pub fn simple(mut self, password: &[u8]) -> Self {
+        self.req.authentication = AuthenticationChoice::Simple(Cow::Owned(password.to_vec()));
+        self
+    }
+
+    /// Use SASL authentication, with the given mechanism and credentials
+    pub fn sasl(mut self, mechanism: &str, credentials: Option<&[u8]>) -> Self {
+        let sasl = SaslCredentials {
+            mechanism: LdapString(Cow::Owned(mechanism.to_owned())),
+            credentials: credentials.map(|c| Cow::Owned(c.to_vec())),
+        };
+        self.req.authentication = AuthenticationChoice::Sasl(sasl);
+        self
+    }
+
+    /// Build the bind request
+    pub fn build(self) -> BindRequest<'static> {
+        self.req
+    }
+}
+
+impl Default for BindRequestBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Same defaults as [`BindRequestBuilder`]
+impl Default for BindRequest<'_> {
+    fn default() -> Self {
+        BindRequestBuilder::new().build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_request_builder() {
+        let req = SearchRequestBuilder::new().build();
+        assert_eq!(req.scope, SearchScope::WholeSubtree);
+        assert_eq!(req.deref_aliases, DerefAliases::NeverDerefAliases);
+        assert_eq!(req.size_limit, 0);
+        assert!(!req.types_only);
+        assert!(req.attributes.is_empty());
+
+        let req = SearchRequestBuilder::new()
+            .base("ou=users,dc=example,dc=com")
+            .scope(SearchScope::BaseObject)
+            .deref(DerefAliases::DerefAlways)
+            .size_limit(10)
+            .time_limit(30)
+            .types_only(true)
+            .attributes(vec![String::from("cn")])
+            .build();
+        assert_eq!(req.base_object.0, "ou=users,dc=example,dc=com");
+        assert_eq!(req.deref_aliases, DerefAliases::DerefAlways);
+        assert_eq!((req.size_limit, req.time_limit), (10, 30));
+        assert_eq!(req.attributes, vec![LdapString(Cow::Borrowed("cn"))]);
+    }
+
+    #[test]
+    fn test_bind_request_builder() {
+        let req = BindRequestBuilder::new().build();
+        assert_eq!(req.version, 3);
+        assert_eq!(
+            req.authentication,
+            AuthenticationChoice::Simple(Cow::Borrowed(b""))
+        );
+
+        let req = BindRequestBuilder::new()
+            .name("cn=admin,dc=example,dc=com")
+            .sasl("EXTERNAL", None)
+            .build();
+        assert_eq!(req.name.0, "cn=admin,dc=example,dc=com");
+        assert!(matches!(
+            req.authentication,
+            AuthenticationChoice::Sasl(ref sasl) if sasl.mechanism.0 == "EXTERNAL"
+        ));
+    }
+
+    #[test]
+    fn test_request_defaults() {
+        let req = SearchRequest::default();
+        assert_eq!(req.base_object.0, "");
+        assert_eq!(req.scope, SearchScope::BaseObject);
+        assert_eq!(req.deref_aliases, DerefAliases::NeverDerefAliases);
+        assert_eq!(req.size_limit, 0);
+        assert_eq!(req.time_limit, 0);
+        assert!(!req.types_only);
+        assert_eq!(
+            req.filter,
+            Filter::Present(LdapString(Cow::Borrowed("objectClass")))
+        );
+        assert!(req.attributes.is_empty());
+        assert_eq!(BindRequest::default(), BindRequestBuilder::new().build());
+        let req = ModifyRequest::default();
+        assert_eq!(req.object.0, "");
+        assert!(req.changes.is_empty());
+    }
+}
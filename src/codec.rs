@@ -0,0 +1,216 @@
+//! Framing of LDAP messages for asynchronous I/O (requires the `codec` feature)
+//!
+//! [`LdapCodec`] implements `tokio_util::codec::Decoder`, and can be used with `FramedRead` to
+//! read a stream of LDAP messages:
+//!
+//! ```rust,ignore
+//! use futures::StreamExt;
+//! use ldap_parser::codec::LdapCodec;
+//! use tokio_util::codec::FramedRead;
+//!
+//! let mut reader = FramedRead::new(socket, LdapCodec::new());
+//! while let Some(msg) = reader.next().await {
+//!     let msg = msg?;
+//!     // ...
+//! }
+//! ```
+//!
+//! Only decoding is supported, since this crate does not serialize messages.
+
+use crate::error::LdapError;
+use crate::ldap::LdapMessage;
+use asn1_rs::nom::Err;
+use asn1_rs::{FromBer, Header, Length, ToStatic};
+use bytes::{Buf, BytesMut};
+use std::io;
+use tokio_util::codec::Decoder;
+
+/// Errors returned by [`LdapCodec`]
+#[derive(Debug, thiserror::Error)]
+pub enum LdapCodecError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("LDAP error: {0}")]
+    Ldap(#[from] LdapError),
+    #[error("Message too large ({0} bytes)")]
+    MessageTooLarge(usize),
+}
+
+/// Default maximum size of a message for [`LdapCodec::new`] (16 MiB)
+pub const DEFAULT_MAX_LENGTH: usize = 16 * 1024 * 1024;
+
+/// A decoder for LDAP messages
+///
+/// The length of the outer `SEQUENCE` is used to know if a complete message has been received.
+/// Decoded messages own their data (see [`ToStatic`]).
+///
+/// Messages larger than the maximum length are rejected with
+/// [`LdapCodecError::MessageTooLarge`], before buffering them. For messages encoded with an
+/// indefinite length, the size is known only after parsing, so the limit applies to the
+/// buffered data.
+#[derive(Clone, Debug)]
+pub struct LdapCodec {
+    max_length: usize,
+}
+
+impl Default for LdapCodec {
+    fn default() -> Self {
+        LdapCodec::with_max_length(DEFAULT_MAX_LENGTH)
+    }
+}
+
+impl LdapCodec {
+    /// Create a new decoder, rejecting messages larger than [`DEFAULT_MAX_LENGTH`] bytes
+    pub fn new() -> Self {
+        LdapCodec::default()
+    }
+
+    /// Create a new decoder, rejecting messages larger than `max_length` bytes
+    pub fn with_max_length(max_length: usize) -> Self {
+        LdapCodec { max_length }
+    }
+}
+
+/// Size of the message at the start of the buffer
+enum FrameLength {
+    /// Header is incomplete
+    Unknown,
+    Definite(usize),
+    /// The end of the message is known only after parsing it
+    Indefinite,
+}
+
+fn frame_length(data: &[u8]) -> Result<FrameLength, LdapCodecError> {
+    match Header::from_ber(data) {
+        Ok((rem, header)) => match header.length() {
+            Length::Definite(len) => Ok(FrameLength::Definite(
+                (data.len() - rem.len()).saturating_add(len),
+            )),
+            Length::Indefinite => Ok(FrameLength::Indefinite),
+        },
+        Err(Err::Incomplete(_)) => Ok(FrameLength::Unknown),
+        Err(e) => Err(LdapError::from(Err::convert(e)).into()),
+    }
+}
+
+impl Decoder for LdapCodec {
+    type Item = LdapMessage<'static>;
+    type Error = LdapCodecError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        if src.is_empty() {
+            return Ok(None);
+        }
+        let (input, definite) = match frame_length(src)? {
+            FrameLength::Unknown => return Ok(None),
+            FrameLength::Definite(len) => {
+                if len > self.max_length {
+                    return Err(LdapCodecError::MessageTooLarge(len));
+                }
+                if src.len() < len {
+                    src.reserve(len - src.len());
+                    return Ok(None);
+                }
+                (&src[..len], true)
+            }
+            FrameLength::Indefinite => (&src[..], false),
+        };
+        let (msg, consumed) = match LdapMessage::from_ber(input) {
+            Ok((rem, msg)) => (msg.to_static(), input.len() - rem.len()),
+            // the frame is complete, so the message has an invalid length inside
+            Err(Err::Incomplete(n)) if definite => {
+                return Err(LdapError::Ber(asn1_rs::Error::Incomplete(n)).into())
+            }
+            Err(Err::Incomplete(_)) => {
+                // indefinite length: stop buffering (and re-parsing) once the limit is reached
+                if src.len() > self.max_length {
+                    return Err(LdapCodecError::MessageTooLarge(src.len()));
+                }
+                return Ok(None);
+            }
+            Err(e) => return Err(LdapError::from(e).into()),
+        };
+        if consumed > self.max_length {
+            return Err(LdapCodecError::MessageTooLarge(consumed));
+        }
+        src.advance(consumed);
+        Ok(Some(msg))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ldap::{MessageID, ProtocolOp};
+    use hex_literal::hex;
+
+    #[test]
+    fn test_codec_decode() {
+        const DATA: &[u8] = &hex!(
+            "30 06 02 01 06 50 01 05
+             30 05 02 01 07 42 00"
+        );
+        let mut codec = LdapCodec::new();
+        let mut buf = BytesMut::new();
+        // partial header and partial message
+        buf.extend_from_slice(&DATA[..1]);
+        assert!(codec.decode(&mut buf).expect("decoding failed").is_none());
+        buf.extend_from_slice(&DATA[1..5]);
+        assert!(codec.decode(&mut buf).expect("decoding failed").is_none());
+        buf.extend_from_slice(&DATA[5..]);
+        let msg = codec
+            .decode(&mut buf)
+            .expect("decoding failed")
+            .expect("no message");
+        assert_eq!(msg.message_id, MessageID(6));
+        let msg = codec
+            .decode(&mut buf)
+            .expect("decoding failed")
+            .expect("no message");
+        assert_eq!(msg.protocol_op, ProtocolOp::UnbindRequest);
+        assert!(buf.is_empty());
+        assert!(codec.decode(&mut buf).expect("decoding failed").is_none());
+    }
+
+    #[test]
+    fn test_codec_errors() {
+        let mut codec = LdapCodec::with_max_length(16);
+        let mut buf = BytesMut::from(&hex!("30 82 10 00")[..]);
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(LdapCodecError::MessageTooLarge(4100))
+        ));
+        // announced length above the default limit
+        let mut codec = LdapCodec::new();
+        let mut buf = BytesMut::from(&hex!("30 84 ff ff ff ff")[..]);
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(LdapCodecError::MessageTooLarge(_))
+        ));
+        assert!(buf.capacity() < 1024);
+        // indefinite length: the limit applies to the buffered data
+        const INDEFINITE: &[u8] = &hex!("30 80 02 01 07 42 00");
+        let mut codec = LdapCodec::with_max_length(8);
+        let mut buf = BytesMut::from(INDEFINITE);
+        assert!(codec.decode(&mut buf).expect("decoding failed").is_none());
+        let mut codec = LdapCodec::with_max_length(4);
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(LdapCodecError::MessageTooLarge(7))
+        ));
+        let mut codec = LdapCodec::new();
+        let mut buf = BytesMut::from(&hex!("30 03 04 01 00")[..]);
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(LdapCodecError::Ldap(_))
+        ));
+        // complete frame, with a truncated BindRequest inside
+        let mut buf = BytesMut::from(&hex!("30 05 02 01 07 60 07")[..]);
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(LdapCodecError::Ldap(LdapError::Ber(
+                asn1_rs::Error::Incomplete(_)
+            )))
+        ));
+    }
+}
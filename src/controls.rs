@@ -0,0 +1,2235 @@
+//! Definitions and parsers for typed LDAP controls
+//!
+//! Controls are parsed as opaque values in [`Control`]. The types in this module can be used to
+//! decode the value of well-known controls, using the `from_control` constructors. These
+//! constructors return `None` if the control type does not match.
+
+use crate::error::*;
+use crate::extended::{parse_opt_bool, parse_opt_sync_cookie, parse_sync_uuid};
+use crate::filter::{AssertionValue, Filter};
+use crate::ldap::*;
+use crate::oids;
+use crate::parser::*;
+use asn1_rs::nom;
+use asn1_rs::{
+    Class, Enumerated, FromBer, OptTaggedImplicit, OptTaggedParser, ParseResult, Sequence, Tag,
+    TaggedImplicit, TaggedParser,
+};
+use nom::combinator::{complete, map, opt};
+use nom::error::ErrorKind;
+use nom::Err;
+use rusticata_macros::newtype_enum;
+use std::borrow::Cow;
+
+/// Simple Paged Results control ([RFC2696])
+///
+/// [RFC2696]: https://tools.ietf.org/html/rfc2696
+pub const OID_PAGED_RESULTS: &str = oids::PAGED_RESULTS;
+/// Server Side Sort request control ([RFC2891])
+///
+/// [RFC2891]: https://tools.ietf.org/html/rfc2891
+pub const OID_SERVER_SORT_REQUEST: &str = oids::SERVER_SORT_REQUEST;
+/// Server Side Sort response control ([RFC2891])
+///
+/// [RFC2891]: https://tools.ietf.org/html/rfc2891
+pub const OID_SERVER_SORT_RESPONSE: &str = oids::SERVER_SORT_RESPONSE;
+/// Virtual List View request control ([draft-ietf-ldapext-ldapv3-vlv])
+///
+/// [draft-ietf-ldapext-ldapv3-vlv]: https://tools.ietf.org/html/draft-ietf-ldapext-ldapv3-vlv-09
+pub const OID_VLV_REQUEST: &str = oids::VLV_REQUEST;
+/// Virtual List View response control ([draft-ietf-ldapext-ldapv3-vlv])
+///
+/// [draft-ietf-ldapext-ldapv3-vlv]: https://tools.ietf.org/html/draft-ietf-ldapext-ldapv3-vlv-09
+pub const OID_VLV_RESPONSE: &str = oids::VLV_RESPONSE;
+
+/// Sync Request control ([RFC4533])
+///
+/// [RFC4533]: https://tools.ietf.org/html/rfc4533
+pub const OID_SYNC_REQUEST: &str = oids::SYNC_REQUEST;
+/// Sync State control ([RFC4533])
+///
+/// [RFC4533]: https://tools.ietf.org/html/rfc4533
+pub const OID_SYNC_STATE: &str = oids::SYNC_STATE;
+/// Sync Done control ([RFC4533])
+///
+/// [RFC4533]: https://tools.ietf.org/html/rfc4533
+pub const OID_SYNC_DONE: &str = oids::SYNC_DONE;
+
+/// Password Policy control ([draft-behera-ldap-password-policy])
+///
+/// [draft-behera-ldap-password-policy]: https://tools.ietf.org/html/draft-behera-ldap-password-policy-11
+pub const OID_PASSWORD_POLICY: &str = oids::PASSWORD_POLICY;
+
+/// ManageDsaIT control ([RFC3296])
+///
+/// [RFC3296]: https://tools.ietf.org/html/rfc3296
+pub const OID_MANAGE_DSA_IT: &str = oids::MANAGE_DSA_IT;
+/// Assertion control ([RFC4528])
+///
+/// [RFC4528]: https://tools.ietf.org/html/rfc4528
+pub const OID_ASSERTION: &str = oids::ASSERTION;
+/// Pre-Read control ([RFC4527])
+///
+/// [RFC4527]: https://tools.ietf.org/html/rfc4527
+pub const OID_PRE_READ: &str = oids::PRE_READ;
+/// Post-Read control ([RFC4527])
+///
+/// [RFC4527]: https://tools.ietf.org/html/rfc4527
+pub const OID_POST_READ: &str = oids::POST_READ;
+/// Proxied Authorization control ([RFC4370])
+///
+/// [RFC4370]: https://tools.ietf.org/html/rfc4370
+pub const OID_PROXY_AUTHZ: &str = oids::PROXY_AUTHZ;
+/// Active Directory DirSync control ([MS-ADTS])
+///
+/// [MS-ADTS]: https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-adts/2213a7f2-0a36-483c-b2a4-8574d53aa1e3
+pub const OID_DIRSYNC: &str = oids::DIRSYNC;
+/// Persistent Search control ([draft-ietf-ldapext-psearch])
+///
+/// [draft-ietf-ldapext-psearch]: https://tools.ietf.org/html/draft-ietf-ldapext-psearch-03
+pub const OID_PERSISTENT_SEARCH: &str = oids::PERSISTENT_SEARCH;
+/// Entry Change Notification control ([draft-ietf-ldapext-psearch])
+///
+/// [draft-ietf-ldapext-psearch]: https://tools.ietf.org/html/draft-ietf-ldapext-psearch-03
+pub const OID_ENTRY_CHANGE_NOTIFICATION: &str = oids::ENTRY_CHANGE_NOTIFICATION;
+/// Matched Values control ([RFC3876])
+///
+/// [RFC3876]: https://tools.ietf.org/html/rfc3876
+pub const OID_MATCHED_VALUES: &str = oids::MATCHED_VALUES;
+/// Authorization Identity request control ([RFC3829])
+///
+/// [RFC3829]: https://tools.ietf.org/html/rfc3829
+pub const OID_AUTHZ_ID_REQUEST: &str = oids::AUTHZ_ID_REQUEST;
+/// Authorization Identity response control ([RFC3829])
+///
+/// [RFC3829]: https://tools.ietf.org/html/rfc3829
+pub const OID_AUTHZ_ID_RESPONSE: &str = oids::AUTHZ_ID_RESPONSE;
+/// Subentries control ([RFC3672])
+///
+/// [RFC3672]: https://tools.ietf.org/html/rfc3672
+pub const OID_SUBENTRIES: &str = oids::SUBENTRIES;
+/// Don't Use Copy control ([RFC6171])
+///
+/// [RFC6171]: https://tools.ietf.org/html/rfc6171
+pub const OID_DONT_USE_COPY: &str = oids::DONT_USE_COPY;
+/// No-Op control ([draft-zeilenga-ldap-noop])
+///
+/// [draft-zeilenga-ldap-noop]: https://tools.ietf.org/html/draft-zeilenga-ldap-noop-01
+pub const OID_NO_OP: &str = oids::NO_OP;
+/// Transaction Specification control ([RFC5805])
+///
+/// [RFC5805]: https://tools.ietf.org/html/rfc5805
+pub const OID_TRANSACTION_SPECIFICATION: &str = oids::TRANSACTION_SPECIFICATION;
+/// Active Directory Permissive Modify control ([MS-ADTS])
+///
+/// [MS-ADTS]: https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-adts/
+pub const OID_PERMISSIVE_MODIFY: &str = oids::PERMISSIVE_MODIFY;
+/// Active Directory Show Deleted control ([MS-ADTS])
+///
+/// [MS-ADTS]: https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-adts/
+pub const OID_SHOW_DELETED: &str = oids::SHOW_DELETED;
+/// Account Usable control (Sun Directory Server)
+pub const OID_ACCOUNT_USABLE: &str = oids::ACCOUNT_USABLE;
+
+/// Decode the value of `control` using parser `f`, if the control type is `oid`
+fn parse_control_value<'a, T, F>(
+    control: &'a Control,
+    oid: &str,
+    f: F,
+) -> Option<std::result::Result<T, LdapError>>
+where
+    F: FnOnce(&'a [u8]) -> Result<'a, T>,
+{
+    if control.control_type.0 != oid {
+        return None;
+    }
+    Some(decode_control_value(control, f))
+}
+
+/// Decode the value of `control` using parser `f`
+///
+/// The value must be decoded entirely: trailing bytes are rejected with
+/// [`LdapError::TrailingData`].
+fn decode_control_value<'a, T, F>(control: &'a Control, f: F) -> std::result::Result<T, LdapError>
+where
+    F: FnOnce(&'a [u8]) -> Result<'a, T>,
+{
+    match &control.control_value {
+        Some(value) => match f(value) {
+            Ok((rem, _)) if !rem.is_empty() => Err(LdapError::TrailingData),
+            Ok((_, v)) => Ok(v),
+            Err(e) => Err(LdapError::from(e)),
+        },
+        None => Err(LdapError::MissingControlValue),
+    }
+}
+
+/// Check that `control` has no value
+fn check_no_control_value(control: &Control) -> std::result::Result<(), LdapError> {
+    match control.control_value {
+        Some(_) => Err(LdapError::UnexpectedControlValue),
+        None => Ok(()),
+    }
+}
+
+/// Check that `control` has no value, if it is critical (the value is ignored otherwise)
+fn check_no_critical_control_value(control: &Control) -> std::result::Result<(), LdapError> {
+    if control.criticality {
+        check_no_control_value(control)
+    } else {
+        Ok(())
+    }
+}
+
+/// A control decoded according to its type
+///
+/// See [`Control::parse_known`].
+#[derive(Debug, PartialEq)]
+pub enum KnownControl<'a> {
+    PagedResults(PagedResultsControl<'a>),
+    ServerSortRequest(ServerSortRequest<'a>),
+    ServerSortResponse(ServerSortResponse<'a>),
+    VlvRequest(VlvRequest<'a>),
+    VlvResponse(VlvResponse<'a>),
+    SyncRequest(SyncRequestControl<'a>),
+    SyncState(SyncStateControl<'a>),
+    SyncDone(SyncDoneControl<'a>),
+    PasswordPolicyRequest(PasswordPolicyRequestControl),
+    PasswordPolicy(PasswordPolicyResponse),
+    ManageDsaIT(ManageDsaITControl),
+    Assertion(AssertionControl<'a>),
+    PreRead(ReadEntryControl<'a>),
+    PostRead(ReadEntryControl<'a>),
+    ProxyAuthz(ProxyAuthzControl<'a>),
+    DirSync(DirSyncControl<'a>),
+    PersistentSearch(PersistentSearchControl),
+    EntryChangeNotification(EntryChangeNotificationControl<'a>),
+    MatchedValues(MatchedValuesControl<'a>),
+    AuthzIdRequest(AuthzIdRequestControl),
+    AuthzIdResponse(AuthzIdResponseControl<'a>),
+    Subentries(SubentriesControl),
+    DontUseCopy(DontUseCopyControl),
+    NoOp(NoOpControl),
+    TransactionSpecification(TransactionSpecificationControl<'a>),
+    PermissiveModify(PermissiveModifyControl),
+    ShowDeleted(ShowDeletedControl),
+    AccountUsableRequest(AccountUsableRequestControl),
+    AccountUsableResponse(AccountUsableResponse),
+    /// A known control, with a value that could not be decoded
+    Invalid {
+        control: &'a Control<'a>,
+        error: LdapError,
+    },
+    /// A control not known by this crate
+    Unknown(&'a Control<'a>),
+}
+
+impl Control<'_> {
+    /// Return `true` if this is a Password Policy request control
+    ///
+    /// The request and response controls have the same type, but the request control has no
+    /// value.
+    pub fn is_password_policy_request(&self) -> bool {
+        self.control_type.0 == OID_PASSWORD_POLICY && self.control_value.is_none()
+    }
+
+    /// Decode the value of the control as a `T`
+    ///
+    /// This can be used to decode the value of controls not known by this crate, by implementing
+    /// [`FromBer`] for the value type. Returns `None` if the control has no value. The control
+    /// type is not checked.
+    ///
+    /// ```rust
+    /// use ldap_parser::controls::PagedResultsControl;
+    /// use ldap_parser::ldap::{Control, LdapOID};
+    /// use std::borrow::Cow;
+    ///
+    /// let control = Control {
+    ///     control_type: LdapOID(Cow::Borrowed("1.2.840.113556.1.4.319")),
+    ///     criticality: false,
+    ///     control_value: Some(Cow::Borrowed(&[0x30, 0x05, 0x02, 0x01, 0x0a, 0x04, 0x00])),
+    /// };
+    /// let value = control
+    ///     .parse_value_as::<PagedResultsControl<'_>>()
+    ///     .expect("no value")
+    ///     .expect("parsing failed");
+    /// assert_eq!(value.size, 10);
+    /// ```
+    pub fn parse_value_as<'a, T>(&'a self) -> Option<std::result::Result<T, LdapError>>
+    where
+        T: FromBer<'a, LdapError>,
+    {
+        self.control_value.as_ref()?;
+        Some(decode_control_value(self, T::from_ber))
+    }
+
+    /// Decode the value of the control, according to its type
+    ///
+    /// Controls not known by this crate are returned as [`KnownControl::Unknown`]. If the type is
+    /// known but the value cannot be decoded, [`KnownControl::Invalid`] is returned.
+    ///
+    /// Pre-Read and Post-Read controls are decoded as response controls. Password Policy and
+    /// Account Usable controls are decoded as request controls if they have no value, and as
+    /// response controls otherwise.
+    pub fn parse_known(&self) -> KnownControl<'_> {
+        let res =
+            match self.control_type.0.as_ref() {
+                OID_PAGED_RESULTS => decode_control_value(self, PagedResultsControl::from_ber)
+                    .map(KnownControl::PagedResults),
+                OID_SERVER_SORT_REQUEST => decode_control_value(self, ServerSortRequest::from_ber)
+                    .map(KnownControl::ServerSortRequest),
+                OID_SERVER_SORT_RESPONSE => {
+                    decode_control_value(self, ServerSortResponse::from_ber)
+                        .map(KnownControl::ServerSortResponse)
+                }
+                OID_VLV_REQUEST => {
+                    decode_control_value(self, VlvRequest::from_ber).map(KnownControl::VlvRequest)
+                }
+                OID_VLV_RESPONSE => {
+                    decode_control_value(self, VlvResponse::from_ber).map(KnownControl::VlvResponse)
+                }
+                OID_SYNC_REQUEST => decode_control_value(self, SyncRequestControl::from_ber)
+                    .map(KnownControl::SyncRequest),
+                OID_SYNC_STATE => decode_control_value(self, SyncStateControl::from_ber)
+                    .map(KnownControl::SyncState),
+                OID_SYNC_DONE => decode_control_value(self, SyncDoneControl::from_ber)
+                    .map(KnownControl::SyncDone),
+                OID_PASSWORD_POLICY if self.control_value.is_none() => Ok(
+                    KnownControl::PasswordPolicyRequest(PasswordPolicyRequestControl),
+                ),
+                OID_PASSWORD_POLICY => decode_control_value(self, PasswordPolicyResponse::from_ber)
+                    .map(KnownControl::PasswordPolicy),
+                OID_MANAGE_DSA_IT => check_no_control_value(self)
+                    .map(|_| KnownControl::ManageDsaIT(ManageDsaITControl)),
+                OID_ASSERTION => decode_control_value(self, AssertionControl::from_ber)
+                    .map(KnownControl::Assertion),
+                OID_PRE_READ => decode_control_value(self, ReadEntryControl::from_ber)
+                    .map(KnownControl::PreRead),
+                OID_POST_READ => decode_control_value(self, ReadEntryControl::from_ber)
+                    .map(KnownControl::PostRead),
+                OID_PROXY_AUTHZ => ProxyAuthzControl::decode(self).map(KnownControl::ProxyAuthz),
+                OID_DIRSYNC => {
+                    decode_control_value(self, DirSyncControl::from_ber).map(KnownControl::DirSync)
+                }
+                OID_PERSISTENT_SEARCH => {
+                    decode_control_value(self, PersistentSearchControl::from_ber)
+                        .map(KnownControl::PersistentSearch)
+                }
+                OID_ENTRY_CHANGE_NOTIFICATION => {
+                    decode_control_value(self, EntryChangeNotificationControl::from_ber)
+                        .map(KnownControl::EntryChangeNotification)
+                }
+                OID_MATCHED_VALUES => decode_control_value(self, MatchedValuesControl::from_ber)
+                    .map(KnownControl::MatchedValues),
+                OID_AUTHZ_ID_REQUEST => check_no_control_value(self)
+                    .map(|_| KnownControl::AuthzIdRequest(AuthzIdRequestControl)),
+                OID_AUTHZ_ID_RESPONSE => decode_control_value(self, parse_authz_id)
+                    .map(|authz_id| AuthzIdResponseControl { authz_id })
+                    .map(KnownControl::AuthzIdResponse),
+                OID_SUBENTRIES => decode_control_value(self, SubentriesControl::from_ber)
+                    .map(KnownControl::Subentries),
+                OID_DONT_USE_COPY => check_no_critical_control_value(self)
+                    .map(|_| KnownControl::DontUseCopy(DontUseCopyControl)),
+                OID_NO_OP => {
+                    check_no_critical_control_value(self).map(|_| KnownControl::NoOp(NoOpControl))
+                }
+                OID_TRANSACTION_SPECIFICATION => {
+                    decode_control_value(self, parse_transaction_identifier)
+                        .map(KnownControl::TransactionSpecification)
+                }
+                OID_PERMISSIVE_MODIFY => check_no_control_value(self)
+                    .map(|_| KnownControl::PermissiveModify(PermissiveModifyControl)),
+                OID_SHOW_DELETED => check_no_control_value(self)
+                    .map(|_| KnownControl::ShowDeleted(ShowDeletedControl)),
+                OID_ACCOUNT_USABLE if self.control_value.is_none() => Ok(
+                    KnownControl::AccountUsableRequest(AccountUsableRequestControl),
+                ),
+                OID_ACCOUNT_USABLE => decode_control_value(self, AccountUsableResponse::from_ber)
+                    .map(KnownControl::AccountUsableResponse),
+                _ => return KnownControl::Unknown(self),
+            };
+        res.unwrap_or_else(|error| KnownControl::Invalid {
+            control: self,
+            error,
+        })
+    }
+}
+
+/// Controls carrying a cookie, used to resume paged or synchronized searches
+///
+/// This allows writing code independent of the mechanism used by the server (Simple Paged
+/// Results, DirSync or Content Synchronization). Cookies are opaque, and must be sent back
+/// unmodified in the next request.
+///
+/// ```rust
+/// use ldap_parser::controls::{HasCookie, PagedResultsControl};
+/// use ldap_parser::ldap::SyncCookie;
+/// use std::borrow::Cow;
+///
+/// fn save_cookie<C: HasCookie>(control: &C, state: &mut Vec<u8>) {
+///     if let Some(cookie) = control.cookie() {
+///         state.clear();
+///         state.extend_from_slice(cookie);
+///     }
+/// }
+///
+/// let control = PagedResultsControl { size: 0, cookie: SyncCookie(Cow::Borrowed(b"abcd")) };
+/// let mut state = Vec::new();
+/// save_cookie(&control, &mut state);
+/// assert_eq!(state, b"abcd");
+/// ```
+pub trait HasCookie {
+    /// Get the raw bytes of the cookie, if present
+    ///
+    /// For the Simple Paged Results and DirSync controls, the cookie is always present (an empty
+    /// paged results cookie means that there are no more pages).
+    fn cookie(&self) -> Option<&[u8]>;
+}
+
+impl HasCookie for PagedResultsControl<'_> {
+    fn cookie(&self) -> Option<&[u8]> {
+        Some(self.cookie.as_bytes())
+    }
+}
+
+impl HasCookie for DirSyncControl<'_> {
+    fn cookie(&self) -> Option<&[u8]> {
+        Some(self.cookie.as_bytes())
+    }
+}
+
+impl HasCookie for SyncRequestControl<'_> {
+    fn cookie(&self) -> Option<&[u8]> {
+        self.cookie.as_ref().map(SyncCookie::as_bytes)
+    }
+}
+
+impl HasCookie for SyncStateControl<'_> {
+    fn cookie(&self) -> Option<&[u8]> {
+        self.cookie.as_ref().map(SyncCookie::as_bytes)
+    }
+}
+
+impl HasCookie for SyncDoneControl<'_> {
+    fn cookie(&self) -> Option<&[u8]> {
+        self.cookie.as_ref().map(SyncCookie::as_bytes)
+    }
+}
+
+/// Simple Paged Results control value ([RFC2696])
+///
+/// The same value is used for both the request (`size` is the requested page size) and the
+/// response (`size` is the estimated result set size). The `cookie` is opaque and must be sent
+/// back unmodified to get the next page.
+///
+/// [RFC2696]: https://tools.ietf.org/html/rfc2696
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PagedResultsControl<'a> {
+    pub size: u32,
+    pub cookie: SyncCookie<'a>,
+}
+
+impl<'a> PagedResultsControl<'a> {
+    /// Decode the value of a Simple Paged Results control
+    ///
+    /// Returns `None` if the control type is not [`OID_PAGED_RESULTS`].
+    pub fn from_control(control: &'a Control) -> Option<std::result::Result<Self, LdapError>> {
+        parse_control_value(control, OID_PAGED_RESULTS, Self::from_ber)
+    }
+}
+
+// realSearchControlValue ::= SEQUENCE {
+//         size            INTEGER (0..maxInt),
+//                                 -- requested page size from client
+//                                 -- result set size estimate from server
+//         cookie          OCTET STRING
+// }
+impl<'a> FromBer<'a, LdapError> for PagedResultsControl<'a> {
+    fn from_ber(bytes: &'a [u8]) -> ParseResult<'a, Self, LdapError> {
+        Sequence::from_ber_and_then(bytes, |i| {
+            let (i, size) = parse_ldap_int_constrained(i)?;
+            let (i, cookie) = parse_ldap_octet_string_as_slice(i)?;
+            let value = PagedResultsControl {
+                size,
+                cookie: SyncCookie(Cow::Borrowed(cookie)),
+            };
+            Ok((i, value))
+        })
+    }
+}
+
+/// A sort key of the Server Side Sort request control ([RFC2891])
+///
+/// [RFC2891]: https://tools.ietf.org/html/rfc2891
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SortKey<'a> {
+    pub attribute_type: LdapString<'a>,
+    pub ordering_rule: Option<LdapString<'a>>,
+    pub reverse: bool,
+}
+
+/// Server Side Sort request control value ([RFC2891])
+///
+/// [RFC2891]: https://tools.ietf.org/html/rfc2891
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ServerSortRequest<'a> {
+    pub sort_keys: Vec<SortKey<'a>>,
+}
+
+impl<'a> ServerSortRequest<'a> {
+    /// Decode the value of a Server Side Sort request control
+    ///
+    /// Returns `None` if the control type is not [`OID_SERVER_SORT_REQUEST`].
+    pub fn from_control(control: &'a Control) -> Option<std::result::Result<Self, LdapError>> {
+        parse_control_value(control, OID_SERVER_SORT_REQUEST, Self::from_ber)
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub struct SortResult(pub u32);
+
+newtype_enum! {
+impl debug SortResult {
+    Success = 0,
+    OperationsError = 1,
+    TimeLimitExceeded = 3,
+    StrongAuthRequired = 8,
+    AdminLimitExceeded = 11,
+    NoSuchAttribute = 16,
+    InappropriateMatching = 18,
+    InsufficientAccessRights = 50,
+    Busy = 51,
+    UnwillingToPerform = 53,
+    Other = 80,
+}
+}
+
+/// Server Side Sort response control value ([RFC2891])
+///
+/// [RFC2891]: https://tools.ietf.org/html/rfc2891
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ServerSortResponse<'a> {
+    pub sort_result: SortResult,
+    pub attribute_type: Option<LdapString<'a>>,
+}
+
+impl<'a> ServerSortResponse<'a> {
+    /// Decode the value of a Server Side Sort response control
+    ///
+    /// Returns `None` if the control type is not [`OID_SERVER_SORT_RESPONSE`].
+    pub fn from_control(control: &'a Control) -> Option<std::result::Result<Self, LdapError>> {
+        parse_control_value(control, OID_SERVER_SORT_RESPONSE, Self::from_ber)
+    }
+}
+
+/// Target of the Virtual List View request control
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum VlvTarget<'a> {
+    /// Position of the target entry, relative to the estimated content count
+    ByOffset { offset: u32, content_count: u32 },
+    /// The target is the first entry whose sort key is greater than or equal to the value
+    GreaterThanOrEqual(AssertionValue<'a>),
+}
+
+/// Virtual List View request control value ([draft-ietf-ldapext-ldapv3-vlv])
+///
+/// [draft-ietf-ldapext-ldapv3-vlv]: https://tools.ietf.org/html/draft-ietf-ldapext-ldapv3-vlv-09
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VlvRequest<'a> {
+    pub before_count: u32,
+    pub after_count: u32,
+    pub target: VlvTarget<'a>,
+    pub context_id: Option<Cow<'a, [u8]>>,
+}
+
+impl<'a> VlvRequest<'a> {
+    /// Decode the value of a Virtual List View request control
+    ///
+    /// Returns `None` if the control type is not [`OID_VLV_REQUEST`].
+    pub fn from_control(control: &'a Control) -> Option<std::result::Result<Self, LdapError>> {
+        parse_control_value(control, OID_VLV_REQUEST, Self::from_ber)
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub struct VirtualListViewResult(pub u32);
+
+newtype_enum! {
+impl debug VirtualListViewResult {
+    Success = 0,
+    OperationsError = 1,
+    ProtocolError = 2,
+    TimeLimitExceeded = 3,
+    AdminLimitExceeded = 11,
+    InappropriateMatching = 18,
+    InsufficientAccessRights = 50,
+    Busy = 51,
+    UnwillingToPerform = 53,
+    SortControlMissing = 60,
+    OffsetRangeError = 61,
+    Other = 80,
+}
+}
+
+/// Virtual List View response control value ([draft-ietf-ldapext-ldapv3-vlv])
+///
+/// [draft-ietf-ldapext-ldapv3-vlv]: https://tools.ietf.org/html/draft-ietf-ldapext-ldapv3-vlv-09
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VlvResponse<'a> {
+    pub target_position: u32,
+    pub content_count: u32,
+    pub result: VirtualListViewResult,
+    pub context_id: Option<Cow<'a, [u8]>>,
+}
+
+impl<'a> VlvResponse<'a> {
+    /// Decode the value of a Virtual List View response control
+    ///
+    /// Returns `None` if the control type is not [`OID_VLV_RESPONSE`].
+    pub fn from_control(control: &'a Control) -> Option<std::result::Result<Self, LdapError>> {
+        parse_control_value(control, OID_VLV_RESPONSE, Self::from_ber)
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub struct SyncRequestMode(pub u32);
+
+newtype_enum! {
+impl debug SyncRequestMode {
+    RefreshOnly = 1,
+    RefreshAndPersist = 3,
+}
+}
+
+/// Sync Request control value ([RFC4533])
+///
+/// [RFC4533]: https://tools.ietf.org/html/rfc4533
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SyncRequestControl<'a> {
+    pub mode: SyncRequestMode,
+    pub cookie: Option<SyncCookie<'a>>,
+    pub reload_hint: bool,
+}
+
+impl<'a> SyncRequestControl<'a> {
+    /// Decode the value of a Sync Request control
+    ///
+    /// Returns `None` if the control type is not [`OID_SYNC_REQUEST`].
+    pub fn from_control(control: &'a Control) -> Option<std::result::Result<Self, LdapError>> {
+        parse_control_value(control, OID_SYNC_REQUEST, Self::from_ber)
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub struct SyncStateValue(pub u32);
+
+newtype_enum! {
+impl debug SyncStateValue {
+    Present = 0,
+    Add = 1,
+    Modify = 2,
+    Delete = 3,
+}
+}
+
+/// Sync State control value ([RFC4533])
+///
+/// [RFC4533]: https://tools.ietf.org/html/rfc4533
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SyncStateControl<'a> {
+    pub state: SyncStateValue,
+    pub entry_uuid: [u8; 16],
+    pub cookie: Option<SyncCookie<'a>>,
+}
+
+impl<'a> SyncStateControl<'a> {
+    /// Decode the value of a Sync State control
+    ///
+    /// Returns `None` if the control type is not [`OID_SYNC_STATE`].
+    pub fn from_control(control: &'a Control) -> Option<std::result::Result<Self, LdapError>> {
+        parse_control_value(control, OID_SYNC_STATE, Self::from_ber)
+    }
+}
+
+/// Sync Done control value ([RFC4533])
+///
+/// [RFC4533]: https://tools.ietf.org/html/rfc4533
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SyncDoneControl<'a> {
+    pub cookie: Option<SyncCookie<'a>>,
+    pub refresh_deletes: bool,
+}
+
+impl<'a> SyncDoneControl<'a> {
+    /// Decode the value of a Sync Done control
+    ///
+    /// Returns `None` if the control type is not [`OID_SYNC_DONE`].
+    pub fn from_control(control: &'a Control) -> Option<std::result::Result<Self, LdapError>> {
+        parse_control_value(control, OID_SYNC_DONE, Self::from_ber)
+    }
+}
+
+/// Warning of the Password Policy response control
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PasswordPolicyWarning {
+    /// Number of seconds before the password expires
+    TimeBeforeExpiration(u32),
+    /// Number of remaining grace authentications
+    GraceAuthNsRemaining(u32),
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub struct PasswordPolicyError(pub u32);
+
+newtype_enum! {
+impl debug PasswordPolicyError {
+    PasswordExpired = 0,
+    AccountLocked = 1,
+    ChangeAfterReset = 2,
+    PasswordModNotAllowed = 3,
+    MustSupplyOldPassword = 4,
+    InsufficientPasswordQuality = 5,
+    PasswordTooShort = 6,
+    PasswordTooYoung = 7,
+    PasswordInHistory = 8,
+}
+}
+
+/// Password Policy request control ([draft-behera-ldap-password-policy])
+///
+/// This control has no value, and is sent to request a Password Policy response control.
+///
+/// [draft-behera-ldap-password-policy]: https://tools.ietf.org/html/draft-behera-ldap-password-policy-11
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PasswordPolicyRequestControl;
+
+impl PasswordPolicyRequestControl {
+    /// Recognize a Password Policy request control
+    ///
+    /// Returns `None` if the control type is not [`OID_PASSWORD_POLICY`], and an error if the
+    /// control has a value (see [`PasswordPolicyResponse::from_control`]).
+    pub fn from_control(control: &Control) -> Option<std::result::Result<Self, LdapError>> {
+        if control.control_type.0 != OID_PASSWORD_POLICY {
+            return None;
+        }
+        Some(check_no_control_value(control).map(|_| PasswordPolicyRequestControl))
+    }
+}
+
+/// Password Policy response control value ([draft-behera-ldap-password-policy])
+///
+/// [draft-behera-ldap-password-policy]: https://tools.ietf.org/html/draft-behera-ldap-password-policy-11
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PasswordPolicyResponse {
+    pub warning: Option<PasswordPolicyWarning>,
+    pub error: Option<PasswordPolicyError>,
+}
+
+impl PasswordPolicyResponse {
+    /// Decode the value of a Password Policy response control
+    ///
+    /// Returns `None` if the control type is not [`OID_PASSWORD_POLICY`].
+    pub fn from_control(control: &Control) -> Option<std::result::Result<Self, LdapError>> {
+        parse_control_value(control, OID_PASSWORD_POLICY, Self::from_ber)
+    }
+}
+
+/// ManageDsaIT control ([RFC3296])
+///
+/// This control has no value.
+///
+/// [RFC3296]: https://tools.ietf.org/html/rfc3296
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ManageDsaITControl;
+
+impl ManageDsaITControl {
+    /// Recognize a ManageDsaIT control
+    ///
+    /// Returns `None` if the control type is not [`OID_MANAGE_DSA_IT`], and an error if the
+    /// control has a value.
+    pub fn from_control(control: &Control) -> Option<std::result::Result<Self, LdapError>> {
+        if control.control_type.0 != OID_MANAGE_DSA_IT {
+            return None;
+        }
+        Some(check_no_control_value(control).map(|_| ManageDsaITControl))
+    }
+}
+
+/// Assertion control value ([RFC4528])
+///
+/// [RFC4528]: https://tools.ietf.org/html/rfc4528
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AssertionControl<'a> {
+    pub filter: Filter<'a>,
+}
+
+impl<'a> AssertionControl<'a> {
+    /// Decode the value of an Assertion control
+    ///
+    /// Returns `None` if the control type is not [`OID_ASSERTION`].
+    pub fn from_control(control: &'a Control) -> Option<std::result::Result<Self, LdapError>> {
+        parse_control_value(control, OID_ASSERTION, Self::from_ber)
+    }
+}
+
+/// Pre-Read or Post-Read response control value ([RFC4527])
+///
+/// [RFC4527]: https://tools.ietf.org/html/rfc4527
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReadEntryControl<'a> {
+    pub entry: SearchResultEntry<'a>,
+}
+
+impl<'a> ReadEntryControl<'a> {
+    /// Decode the value of a Pre-Read or Post-Read response control
+    ///
+    /// Returns `None` if the control type is not [`OID_PRE_READ`] or [`OID_POST_READ`].
+    pub fn from_control(control: &'a Control) -> Option<std::result::Result<Self, LdapError>> {
+        parse_control_value(control, OID_PRE_READ, Self::from_ber)
+            .or_else(|| parse_control_value(control, OID_POST_READ, Self::from_ber))
+    }
+}
+
+/// Proxied Authorization control ([RFC4370])
+///
+/// [RFC4370]: https://tools.ietf.org/html/rfc4370
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProxyAuthzControl<'a> {
+    /// Authorization identity (for ex. `dn:cn=admin,dc=example,dc=com` or `u:bob`)
+    ///
+    /// An empty value is the anonymous identity.
+    pub authz_id: LdapString<'a>,
+    /// Criticality of the control (should always be `true`)
+    pub criticality: bool,
+}
+
+impl<'a> ProxyAuthzControl<'a> {
+    /// Decode the value of a Proxied Authorization control
+    ///
+    /// Returns `None` if the control type is not [`OID_PROXY_AUTHZ`].
+    pub fn from_control(control: &'a Control) -> Option<std::result::Result<Self, LdapError>> {
+        if control.control_type.0 != OID_PROXY_AUTHZ {
+            return None;
+        }
+        Some(Self::decode(control))
+    }
+
+    /// Test if the authorization identity is the anonymous identity
+    pub fn is_anonymous(&self) -> bool {
+        self.authz_id.0.is_empty()
+    }
+
+    fn decode(control: &'a Control) -> std::result::Result<Self, LdapError> {
+        let authz_id = decode_control_value(control, parse_authz_id)?;
+        Ok(ProxyAuthzControl {
+            authz_id,
+            criticality: control.criticality,
+        })
+    }
+}
+
+/// Flags of the DirSync request control
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct DirSyncFlags(pub u32);
+
+impl DirSyncFlags {
+    pub const OBJECT_SECURITY: DirSyncFlags = DirSyncFlags(0x0000_0001);
+    pub const ANCESTORS_FIRST_ORDER: DirSyncFlags = DirSyncFlags(0x0000_0800);
+    pub const PUBLIC_DATA_ONLY: DirSyncFlags = DirSyncFlags(0x0000_2000);
+    pub const INCREMENTAL_VALUES: DirSyncFlags = DirSyncFlags(0x8000_0000);
+
+    /// Test if all the bits of `flags` are set
+    pub fn contains(self, flags: DirSyncFlags) -> bool {
+        self.0 & flags.0 == flags.0
+    }
+}
+
+/// Active Directory DirSync control value ([MS-ADTS])
+///
+/// The same structure is used for both the request and the response. In the response, `flags`
+/// is non-zero if more data is available (`moreResults`), and `max_bytes` is unused. The
+/// `cookie` is opaque and must be sent back unmodified in the next request.
+///
+/// [MS-ADTS]: https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-adts/2213a7f2-0a36-483c-b2a4-8574d53aa1e3
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DirSyncControl<'a> {
+    pub flags: DirSyncFlags,
+    pub max_bytes: u32,
+    pub cookie: SyncCookie<'a>,
+}
+
+impl<'a> DirSyncControl<'a> {
+    /// Decode the value of a DirSync control
+    ///
+    /// Returns `None` if the control type is not [`OID_DIRSYNC`].
+    pub fn from_control(control: &'a Control) -> Option<std::result::Result<Self, LdapError>> {
+        parse_control_value(control, OID_DIRSYNC, Self::from_ber)
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub struct ChangeType(pub u32);
+
+newtype_enum! {
+impl debug ChangeType {
+    Add = 1,
+    Delete = 2,
+    Modify = 4,
+    ModDN = 8,
+}
+}
+
+/// Persistent Search request control value ([draft-ietf-ldapext-psearch])
+///
+/// [draft-ietf-ldapext-psearch]: https://tools.ietf.org/html/draft-ietf-ldapext-psearch-03
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PersistentSearchControl {
+    /// Bitmask of the requested [`ChangeType`] values
+    pub change_types: u32,
+    pub changes_only: bool,
+    /// Request Entry Change Notification controls in returned entries
+    pub return_ecs: bool,
+}
+
+impl PersistentSearchControl {
+    /// Decode the value of a Persistent Search control
+    ///
+    /// Returns `None` if the control type is not [`OID_PERSISTENT_SEARCH`].
+    pub fn from_control(control: &Control) -> Option<std::result::Result<Self, LdapError>> {
+        parse_control_value(control, OID_PERSISTENT_SEARCH, Self::from_ber)
+    }
+
+    /// Test if changes of type `change_type` are requested
+    pub fn has_change_type(&self, change_type: ChangeType) -> bool {
+        self.change_types & change_type.0 != 0
+    }
+}
+
+/// Entry Change Notification control value ([draft-ietf-ldapext-psearch])
+///
+/// [draft-ietf-ldapext-psearch]: https://tools.ietf.org/html/draft-ietf-ldapext-psearch-03
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EntryChangeNotificationControl<'a> {
+    pub change_type: ChangeType,
+    /// DN of the entry before the change (only sent for `modDN` changes)
+    pub previous_dn: Option<LdapDN<'a>>,
+    pub change_number: Option<u32>,
+}
+
+impl<'a> EntryChangeNotificationControl<'a> {
+    /// Decode the value of an Entry Change Notification control
+    ///
+    /// Returns `None` if the control type is not [`OID_ENTRY_CHANGE_NOTIFICATION`].
+    pub fn from_control(control: &'a Control) -> Option<std::result::Result<Self, LdapError>> {
+        parse_control_value(control, OID_ENTRY_CHANGE_NOTIFICATION, Self::from_ber)
+    }
+}
+
+/// Matched Values control value ([RFC3876])
+///
+/// Filters are simple filter items: `and`, `or` and `not` filters are rejected when parsing.
+///
+/// [RFC3876]: https://tools.ietf.org/html/rfc3876
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MatchedValuesControl<'a> {
+    pub filters: Vec<Filter<'a>>,
+}
+
+impl<'a> MatchedValuesControl<'a> {
+    /// Decode the value of a Matched Values control
+    ///
+    /// Returns `None` if the control type is not [`OID_MATCHED_VALUES`].
+    pub fn from_control(control: &'a Control) -> Option<std::result::Result<Self, LdapError>> {
+        parse_control_value(control, OID_MATCHED_VALUES, Self::from_ber)
+    }
+}
+
+/// Authorization Identity request control ([RFC3829])
+///
+/// This control has no value.
+///
+/// [RFC3829]: https://tools.ietf.org/html/rfc3829
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct AuthzIdRequestControl;
+
+impl AuthzIdRequestControl {
+    /// Recognize an Authorization Identity request control
+    ///
+    /// Returns `None` if the control type is not [`OID_AUTHZ_ID_REQUEST`], and an error if the
+    /// control has a value.
+    pub fn from_control(control: &Control) -> Option<std::result::Result<Self, LdapError>> {
+        if control.control_type.0 != OID_AUTHZ_ID_REQUEST {
+            return None;
+        }
+        Some(check_no_control_value(control).map(|_| AuthzIdRequestControl))
+    }
+}
+
+/// Authorization Identity response control ([RFC3829])
+///
+/// [RFC3829]: https://tools.ietf.org/html/rfc3829
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AuthzIdResponseControl<'a> {
+    /// Authorization identity established by the bind (empty for the anonymous identity)
+    pub authz_id: LdapString<'a>,
+}
+
+impl<'a> AuthzIdResponseControl<'a> {
+    /// Decode the value of an Authorization Identity response control
+    ///
+    /// Returns `None` if the control type is not [`OID_AUTHZ_ID_RESPONSE`].
+    pub fn from_control(control: &'a Control) -> Option<std::result::Result<Self, LdapError>> {
+        parse_control_value(control, OID_AUTHZ_ID_RESPONSE, parse_authz_id)
+            .map(|res| res.map(|authz_id| AuthzIdResponseControl { authz_id }))
+    }
+}
+
+/// Subentries control value ([RFC3672])
+///
+/// [RFC3672]: https://tools.ietf.org/html/rfc3672
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SubentriesControl {
+    /// If `true`, only subentries are visible. Otherwise, only normal entries are visible.
+    pub visibility: bool,
+}
+
+impl SubentriesControl {
+    /// Decode the value of a Subentries control
+    ///
+    /// Returns `None` if the control type is not [`OID_SUBENTRIES`].
+    pub fn from_control(control: &Control) -> Option<std::result::Result<Self, LdapError>> {
+        parse_control_value(control, OID_SUBENTRIES, Self::from_ber)
+    }
+}
+
+/// Don't Use Copy control ([RFC6171])
+///
+/// This control has no value.
+///
+/// [RFC6171]: https://tools.ietf.org/html/rfc6171
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DontUseCopyControl;
+
+impl DontUseCopyControl {
+    /// Recognize a Don't Use Copy control
+    ///
+    /// Returns `None` if the control type is not [`OID_DONT_USE_COPY`], and an error if the
+    /// control is critical and has a value.
+    pub fn from_control(control: &Control) -> Option<std::result::Result<Self, LdapError>> {
+        if control.control_type.0 != OID_DONT_USE_COPY {
+            return None;
+        }
+        Some(check_no_critical_control_value(control).map(|_| DontUseCopyControl))
+    }
+}
+
+/// No-Op control ([draft-zeilenga-ldap-noop])
+///
+/// This control has no value.
+///
+/// [draft-zeilenga-ldap-noop]: https://tools.ietf.org/html/draft-zeilenga-ldap-noop-01
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct NoOpControl;
+
+impl NoOpControl {
+    /// Recognize a No-Op control
+    ///
+    /// Returns `None` if the control type is not [`OID_NO_OP`], and an error if the control is
+    /// critical and has a value.
+    pub fn from_control(control: &Control) -> Option<std::result::Result<Self, LdapError>> {
+        if control.control_type.0 != OID_NO_OP {
+            return None;
+        }
+        Some(check_no_critical_control_value(control).map(|_| NoOpControl))
+    }
+}
+
+/// Transaction Specification control ([RFC5805])
+///
+/// [RFC5805]: https://tools.ietf.org/html/rfc5805
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TransactionSpecificationControl<'a> {
+    /// Transaction identifier, as returned by the Start Transaction response
+    pub identifier: Cow<'a, [u8]>,
+}
+
+impl<'a> TransactionSpecificationControl<'a> {
+    /// Decode the value of a Transaction Specification control
+    ///
+    /// Returns `None` if the control type is not [`OID_TRANSACTION_SPECIFICATION`].
+    pub fn from_control(control: &'a Control) -> Option<std::result::Result<Self, LdapError>> {
+        parse_control_value(
+            control,
+            OID_TRANSACTION_SPECIFICATION,
+            parse_transaction_identifier,
+        )
+    }
+}
+
+/// Active Directory Permissive Modify control ([MS-ADTS])
+///
+/// This control has no value. Adding a value that already exists or deleting a value that does
+/// not exist does not fail when this control is used.
+///
+/// [MS-ADTS]: https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-adts/
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PermissiveModifyControl;
+
+impl PermissiveModifyControl {
+    /// Recognize a Permissive Modify control
+    ///
+    /// Returns `None` if the control type is not [`OID_PERMISSIVE_MODIFY`], and an error if the
+    /// control has a value.
+    pub fn from_control(control: &Control) -> Option<std::result::Result<Self, LdapError>> {
+        if control.control_type.0 != OID_PERMISSIVE_MODIFY {
+            return None;
+        }
+        Some(check_no_control_value(control).map(|_| PermissiveModifyControl))
+    }
+}
+
+/// Active Directory Show Deleted control ([MS-ADTS])
+///
+/// This control has no value. Deleted objects are returned by searches when this control is
+/// used.
+///
+/// [MS-ADTS]: https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-adts/
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ShowDeletedControl;
+
+impl ShowDeletedControl {
+    /// Recognize a Show Deleted control
+    ///
+    /// Returns `None` if the control type is not [`OID_SHOW_DELETED`], and an error if the
+    /// control has a value.
+    pub fn from_control(control: &Control) -> Option<std::result::Result<Self, LdapError>> {
+        if control.control_type.0 != OID_SHOW_DELETED {
+            return None;
+        }
+        Some(check_no_control_value(control).map(|_| ShowDeletedControl))
+    }
+}
+
+/// Account Usable request control (Sun Directory Server)
+///
+/// This control has no value, and is sent with a search request to get an Account Usable
+/// response control with each returned entry.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct AccountUsableRequestControl;
+
+impl AccountUsableRequestControl {
+    /// Recognize an Account Usable request control
+    ///
+    /// Returns `None` if the control type is not [`OID_ACCOUNT_USABLE`], and an error if the
+    /// control has a value (see [`AccountUsableResponse::from_control`]).
+    pub fn from_control(control: &Control) -> Option<std::result::Result<Self, LdapError>> {
+        if control.control_type.0 != OID_ACCOUNT_USABLE {
+            return None;
+        }
+        Some(check_no_control_value(control).map(|_| AccountUsableRequestControl))
+    }
+}
+
+/// Account Usable response control value (Sun Directory Server)
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AccountUsableResponse {
+    /// The account is usable, with the number of seconds before the password expires (-1 if
+    /// the password does not expire)
+    Available(i32),
+    /// The account is not usable
+    NotAvailable(AccountNotAvailable),
+}
+
+/// Reasons why an account is not usable
+///
+/// See [`AccountUsableResponse`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct AccountNotAvailable {
+    pub inactive: bool,
+    pub reset: bool,
+    pub expired: bool,
+    pub remaining_grace: Option<i32>,
+    pub seconds_before_unlock: Option<i32>,
+}
+
+impl AccountUsableResponse {
+    /// Decode the value of an Account Usable response control
+    ///
+    /// Returns `None` if the control type is not [`OID_ACCOUNT_USABLE`].
+    pub fn from_control(control: &Control) -> Option<std::result::Result<Self, LdapError>> {
+        parse_control_value(control, OID_ACCOUNT_USABLE, Self::from_ber)
+    }
+}
+
+// The value of the Transaction Specification control is the transaction identifier, and is not
+// BER-encoded
+fn parse_transaction_identifier(i: &[u8]) -> Result<'_, TransactionSpecificationControl<'_>> {
+    let value = TransactionSpecificationControl {
+        identifier: Cow::Borrowed(i),
+    };
+    Ok((&b""[..], value))
+}
+
+// The value of the Proxied Authorization and Authorization Identity response controls is the
+// authzId, and is not BER-encoded
+fn parse_authz_id(i: &[u8]) -> Result<'_, LdapString<'_>> {
+    let s = parse_utf8(i, "authzId")?;
+    Ok((&b""[..], LdapString(Cow::Borrowed(s))))
+}
+
+// The assertion control value is a Filter
+impl<'a> FromBer<'a, LdapError> for AssertionControl<'a> {
+    fn from_ber(bytes: &'a [u8]) -> ParseResult<'a, Self, LdapError> {
+        map(Filter::from_ber, |filter| AssertionControl { filter })(bytes)
+    }
+}
+
+// The Pre-Read and Post-Read response controls values are a SearchResultEntry
+impl<'a> FromBer<'a, LdapError> for ReadEntryControl<'a> {
+    fn from_ber(bytes: &'a [u8]) -> ParseResult<'a, Self, LdapError> {
+        map(SearchResultEntry::from_ber, |entry| ReadEntryControl {
+            entry,
+        })(bytes)
+    }
+}
+
+// SortKeyList ::= SEQUENCE OF SEQUENCE {
+//            attributeType   AttributeDescription,
+//            orderingRule    [0] MatchingRuleId OPTIONAL,
+//            reverseOrder    [1] BOOLEAN DEFAULT FALSE }
+impl<'a> FromBer<'a, LdapError> for SortKey<'a> {
+    fn from_ber(bytes: &'a [u8]) -> ParseResult<'a, Self, LdapError> {
+        Sequence::from_ber_and_then(bytes, |i| {
+            let (i, attribute_type) = LdapString::from_ber(i)?;
+            let (i, ordering_rule) = OptTaggedParser::new(Class::ContextSpecific, Tag(0))
+                .parse_ber(i, |_, content| {
+                    let s = parse_utf8(content, "MatchingRuleId")?;
+                    Ok((&b""[..], LdapString(Cow::Borrowed(s))))
+                })?;
+            let (i, reverse) =
+                OptTaggedImplicit::<bool, asn1_rs::Error, 1>::from_ber(i).map_err(Err::convert)?;
+            let reverse = reverse.map(|t| t.into_inner()).unwrap_or(false);
+            let key = SortKey {
+                attribute_type,
+                ordering_rule,
+                reverse,
+            };
+            Ok((i, key))
+        })
+    }
+}
+
+impl<'a> FromBer<'a, LdapError> for ServerSortRequest<'a> {
+    fn from_ber(bytes: &'a [u8]) -> ParseResult<'a, Self, LdapError> {
+        Sequence::from_ber_and_then(bytes, |mut i| {
+            if i.is_empty() {
+                return Err(Err::Error(LdapError::NomError(ErrorKind::Many1)));
+            }
+            // errors of sort keys are returned unchanged
+            let mut sort_keys = Vec::new();
+            while !i.is_empty() {
+                let (rem, key) = complete(SortKey::from_ber)(i)?;
+                sort_keys.push(key);
+                i = rem;
+            }
+            Ok((i, ServerSortRequest { sort_keys }))
+        })
+    }
+}
+
+// SortResult ::= SEQUENCE {
+//    sortResult  ENUMERATED {
+//        success                   (0), -- results are sorted
+//        operationsError           (1), -- server internal failure
+//        timeLimitExceeded         (3), -- timelimit reached before
+//                                       -- sorting was completed
+//        strongAuthRequired        (8), -- refused to return sorted
+//                                       -- results via insecure
+//                                       -- protocol
+//        adminLimitExceeded       (11), -- too many matching entries
+//                                       -- for the server to sort
+//        noSuchAttribute          (16), -- unrecognized attribute
+//                                       -- type in sort key
+//        inappropriateMatching    (18), -- unrecognized or
+//                                       -- inappropriate matching
+//                                       -- rule in sort key
+//        insufficientAccessRights (50), -- refused to return sorted
+//                                       -- results to this client
+//        busy                     (51), -- too busy to process
+//        unwillingToPerform       (53), -- unable to sort
+//        other                    (80)
+//        },
+//  attributeType [0] AttributeDescription OPTIONAL }
+impl<'a> FromBer<'a, LdapError> for ServerSortResponse<'a> {
+    fn from_ber(bytes: &'a [u8]) -> ParseResult<'a, Self, LdapError> {
+        Sequence::from_ber_and_then(bytes, |i| {
+            let (i, sort_result) =
+                map(Enumerated::from_ber, |e| SortResult(e.0))(i).map_err(Err::convert)?;
+            let (i, attribute_type) = OptTaggedParser::new(Class::ContextSpecific, Tag(0))
+                .parse_ber(i, |_, content| {
+                    let s = parse_utf8(content, "AttributeDescription")?;
+                    Ok((&b""[..], LdapString(Cow::Borrowed(s))))
+                })?;
+            let value = ServerSortResponse {
+                sort_result,
+                attribute_type,
+            };
+            Ok((i, value))
+        })
+    }
+}
+
+// VirtualListViewRequest ::= SEQUENCE {
+//     beforeCount    INTEGER (0..maxInt),
+//     afterCount     INTEGER (0..maxInt),
+//     target       CHOICE {
+//                    byOffset        [0] SEQUENCE {
+//                         offset          INTEGER (1 .. maxInt),
+//                         contentCount    INTEGER (0 .. maxInt) },
+//                    greaterThanOrEqual [1] AssertionValue },
+//     contextID     OCTET STRING OPTIONAL }
+impl<'a> FromBer<'a, LdapError> for VlvRequest<'a> {
+    fn from_ber(bytes: &'a [u8]) -> ParseResult<'a, Self, LdapError> {
+        Sequence::from_ber_and_then(bytes, |i| {
+            let (i, before_count) = parse_ldap_int_constrained(i)?;
+            let (i, after_count) = parse_ldap_int_constrained(i)?;
+            let (i, target) =
+                OptTaggedParser::new(Class::ContextSpecific, Tag(0)).parse_ber(i, |_, data| {
+                    let (data, offset) = parse_ldap_int_constrained(data)?;
+                    let (data, content_count) = parse_ldap_int_constrained(data)?;
+                    let target = VlvTarget::ByOffset {
+                        offset,
+                        content_count,
+                    };
+                    Ok((data, target))
+                })?;
+            let (i, target) = match target {
+                Some(target) => (i, target),
+                None => TaggedParser::from_ber_and_then(Class::ContextSpecific, 1, i, |content| {
+                    let value = AssertionValue(Cow::Borrowed(content));
+                    Ok((&b""[..], VlvTarget::GreaterThanOrEqual(value)))
+                })?,
+            };
+            let (i, context_id) = opt(complete(parse_ldap_octet_string_as_slice))(i)?;
+            let value = VlvRequest {
+                before_count,
+                after_count,
+                target,
+                context_id: context_id.map(Cow::Borrowed),
+            };
+            Ok((i, value))
+        })
+    }
+}
+
+// VirtualListViewResponse ::= SEQUENCE {
+//     targetPosition    INTEGER (0 .. maxInt),
+//     contentCount     INTEGER (0 .. maxInt),
+//     virtualListViewResult ENUMERATED {
+//          success (0),
+//          operationsError (1),
+//          protocolError (2),
+//          unwillingToPerform (53),
+//          insufficientAccessRights (50),
+//          timeLimitExceeded (3),
+//          adminLimitExceeded (11),
+//          innapropriateMatching (18),
+//          sortControlMissing (60),
+//          offsetRangeError (61),
+//          other(80),
+//          ... },
+//     contextID     OCTET STRING OPTIONAL }
+impl<'a> FromBer<'a, LdapError> for VlvResponse<'a> {
+    fn from_ber(bytes: &'a [u8]) -> ParseResult<'a, Self, LdapError> {
+        Sequence::from_ber_and_then(bytes, |i| {
+            let (i, target_position) = parse_ldap_int_constrained(i)?;
+            let (i, content_count) = parse_ldap_int_constrained(i)?;
+            let (i, result) = map(Enumerated::from_ber, |e| VirtualListViewResult(e.0))(i)
+                .map_err(Err::convert)?;
+            let (i, context_id) = opt(complete(parse_ldap_octet_string_as_slice))(i)?;
+            let value = VlvResponse {
+                target_position,
+                content_count,
+                result,
+                context_id: context_id.map(Cow::Borrowed),
+            };
+            Ok((i, value))
+        })
+    }
+}
+
+// DirSyncRequestValue ::= SEQUENCE {
+//     Flags       integer
+//     MaxBytes    integer
+//     Cookie      OCTET STRING }
+//
+// DirSyncResponseValue ::= SEQUENCE {
+//     MoreResults     INTEGER
+//     unused          INTEGER
+//     CookieServer    OCTET STRING }
+impl<'a> FromBer<'a, LdapError> for DirSyncControl<'a> {
+    fn from_ber(bytes: &'a [u8]) -> ParseResult<'a, Self, LdapError> {
+        Sequence::from_ber_and_then(bytes, |i| {
+            // flags are a 32-bits mask, often encoded as a negative integer
+            let (i, flags) = <i64>::from_ber(i).map_err(Err::convert)?;
+            let (i, max_bytes) = <u32>::from_ber(i).map_err(Err::convert)?;
+            let (i, cookie) = parse_ldap_octet_string_as_slice(i)?;
+            let value = DirSyncControl {
+                flags: DirSyncFlags(flags as u32),
+                max_bytes,
+                cookie: SyncCookie(Cow::Borrowed(cookie)),
+            };
+            Ok((i, value))
+        })
+    }
+}
+
+// PersistentSearch ::= SEQUENCE {
+//         changeTypes INTEGER,
+//         changesOnly BOOLEAN,
+//         returnECs BOOLEAN
+// }
+impl<'a> FromBer<'a, LdapError> for PersistentSearchControl {
+    fn from_ber(bytes: &'a [u8]) -> ParseResult<'a, Self, LdapError> {
+        Sequence::from_ber_and_then(bytes, |i| {
+            let (i, change_types) = <u32>::from_ber(i).map_err(Err::convert)?;
+            let (i, changes_only) = <bool>::from_ber(i).map_err(Err::convert)?;
+            let (i, return_ecs) = <bool>::from_ber(i).map_err(Err::convert)?;
+            let value = PersistentSearchControl {
+                change_types,
+                changes_only,
+                return_ecs,
+            };
+            Ok((i, value))
+        })
+    }
+}
+
+// EntryChangeNotification ::= SEQUENCE {
+//         changeType ENUMERATED {
+//                 add             (1),
+//                 delete          (2),
+//                 modify          (4),
+//                 modDN           (8)
+//         },
+//         previousDN   LDAPDN OPTIONAL,     -- modifyDN ops. only
+//         changeNumber INTEGER OPTIONAL     -- if supported
+// }
+impl<'a> FromBer<'a, LdapError> for EntryChangeNotificationControl<'a> {
+    fn from_ber(bytes: &'a [u8]) -> ParseResult<'a, Self, LdapError> {
+        Sequence::from_ber_and_then(bytes, |i| {
+            let (i, change_type) =
+                map(Enumerated::from_ber, |e| ChangeType(e.0))(i).map_err(Err::convert)?;
+            let (i, previous_dn) = opt(complete(LdapDN::from_ber))(i)?;
+            let (i, change_number) = opt(complete(<u32>::from_ber))(i).map_err(Err::convert)?;
+            let value = EntryChangeNotificationControl {
+                change_type,
+                previous_dn,
+                change_number,
+            };
+            Ok((i, value))
+        })
+    }
+}
+
+// ValuesReturnFilter ::= SEQUENCE OF SimpleFilterItem
+//
+// SimpleFilterItem ::= CHOICE {
+//     equalityMatch   [3] AttributeValueAssertion,
+//     substrings      [4] SubstringFilter,
+//     greaterOrEqual  [5] AttributeValueAssertion,
+//     lessOrEqual     [6] AttributeValueAssertion,
+//     present         [7] AttributeDescription,
+//     approxMatch     [8] AttributeValueAssertion,
+//     extensibleMatch [9] SimpleMatchingAssertion }
+impl<'a> FromBer<'a, LdapError> for MatchedValuesControl<'a> {
+    fn from_ber(bytes: &'a [u8]) -> ParseResult<'a, Self, LdapError> {
+        Sequence::from_ber_and_then(bytes, |mut i| {
+            let mut filters = Vec::new();
+            while !i.is_empty() {
+                let (rem, filter) = Filter::from_ber(i)?;
+                if matches!(filter, Filter::And(_) | Filter::Or(_) | Filter::Not(_)) {
+                    return Err(Err::Error(LdapError::InvalidFilterType));
+                }
+                filters.push(filter);
+                i = rem;
+            }
+            if filters.is_empty() {
+                return Err(Err::Error(LdapError::EmptyFilter));
+            }
+            Ok((i, MatchedValuesControl { filters }))
+        })
+    }
+}
+
+// The Subentries control value is a BOOLEAN (visibility)
+impl<'a> FromBer<'a, LdapError> for SubentriesControl {
+    fn from_ber(bytes: &'a [u8]) -> ParseResult<'a, Self, LdapError> {
+        let (i, visibility) = <bool>::from_ber(bytes).map_err(Err::convert)?;
+        Ok((i, SubentriesControl { visibility }))
+    }
+}
+
+// syncRequestValue ::= SEQUENCE {
+//     mode ENUMERATED {
+//         -- 0 unused
+//         refreshOnly       (1),
+//         -- 2 reserved
+//         refreshAndPersist (3)
+//     },
+//     cookie     syncCookie OPTIONAL,
+//     reloadHint BOOLEAN DEFAULT FALSE
+// }
+impl<'a> FromBer<'a, LdapError> for SyncRequestControl<'a> {
+    fn from_ber(bytes: &'a [u8]) -> ParseResult<'a, Self, LdapError> {
+        Sequence::from_ber_and_then(bytes, |i| {
+            let (i, mode) =
+                map(Enumerated::from_ber, |e| SyncRequestMode(e.0))(i).map_err(Err::convert)?;
+            let (i, cookie) = parse_opt_sync_cookie(i)?;
+            let (i, reload_hint) = parse_opt_bool(i, false)?;
+            let value = SyncRequestControl {
+                mode,
+                cookie,
+                reload_hint,
+            };
+            Ok((i, value))
+        })
+    }
+}
+
+// syncStateValue ::= SEQUENCE {
+//     state ENUMERATED {
+//         present (0),
+//         add (1),
+//         modify (2),
+//         delete (3)
+//     },
+//     entryUUID syncUUID,
+//     cookie    syncCookie OPTIONAL
+// }
+impl<'a> FromBer<'a, LdapError> for SyncStateControl<'a> {
+    fn from_ber(bytes: &'a [u8]) -> ParseResult<'a, Self, LdapError> {
+        Sequence::from_ber_and_then(bytes, |i| {
+            let (i, state) =
+                map(Enumerated::from_ber, |e| SyncStateValue(e.0))(i).map_err(Err::convert)?;
+            let (i, entry_uuid) = parse_sync_uuid(i)?;
+            let (i, cookie) = parse_opt_sync_cookie(i)?;
+            let value = SyncStateControl {
+                state,
+                entry_uuid,
+                cookie,
+            };
+            Ok((i, value))
+        })
+    }
+}
+
+// syncDoneValue ::= SEQUENCE {
+//     cookie          syncCookie OPTIONAL,
+//     refreshDeletes  BOOLEAN DEFAULT FALSE
+// }
+impl<'a> FromBer<'a, LdapError> for SyncDoneControl<'a> {
+    fn from_ber(bytes: &'a [u8]) -> ParseResult<'a, Self, LdapError> {
+        Sequence::from_ber_and_then(bytes, |i| {
+            let (i, cookie) = parse_opt_sync_cookie(i)?;
+            let (i, refresh_deletes) = parse_opt_bool(i, false)?;
+            let value = SyncDoneControl {
+                cookie,
+                refresh_deletes,
+            };
+            Ok((i, value))
+        })
+    }
+}
+
+// PasswordPolicyResponseValue ::= SEQUENCE {
+//    warning [0] CHOICE {
+//       timeBeforeExpiration [0] INTEGER (0 .. maxInt),
+//       graceAuthNsRemaining [1] INTEGER (0 .. maxInt) } OPTIONAL,
+//    error   [1] ENUMERATED {
+//       passwordExpired             (0),
+//       accountLocked               (1),
+//       changeAfterReset            (2),
+//       passwordModNotAllowed       (3),
+//       mustSupplyOldPassword       (4),
+//       insufficientPasswordQuality (5),
+//       passwordTooShort            (6),
+//       passwordTooYoung            (7),
+//       passwordInHistory           (8) } OPTIONAL }
+impl<'a> FromBer<'a, LdapError> for PasswordPolicyResponse {
+    fn from_ber(bytes: &'a [u8]) -> ParseResult<'a, Self, LdapError> {
+        Sequence::from_ber_and_then(bytes, |i| {
+            let (i, warning) =
+                OptTaggedParser::new(Class::ContextSpecific, Tag(0)).parse_ber(i, |_, data| {
+                    let (rem, t) = OptTaggedImplicit::<u32, asn1_rs::Error, 0>::from_ber(data)
+                        .map_err(Err::convert)?;
+                    if let Some(t) = t {
+                        return Ok((
+                            rem,
+                            PasswordPolicyWarning::TimeBeforeExpiration(t.into_inner()),
+                        ));
+                    }
+                    let (rem, t) = TaggedImplicit::<u32, asn1_rs::Error, 1>::from_ber(data)
+                        .map_err(Err::convert)?;
+                    Ok((
+                        rem,
+                        PasswordPolicyWarning::GraceAuthNsRemaining(t.into_inner()),
+                    ))
+                })?;
+            let (i, error) = OptTaggedImplicit::<Enumerated, asn1_rs::Error, 1>::from_ber(i)
+                .map_err(Err::convert)?;
+            let error = error.map(|e| PasswordPolicyError(e.into_inner().0));
+            Ok((i, PasswordPolicyResponse { warning, error }))
+        })
+    }
+}
+
+// ACCOUNT_USABLE_RESPONSE ::= CHOICE {
+//     is_available          [0] INTEGER, -- seconds before expiration --
+//     is_not_available      [1] MORE_INFO }
+//
+// MORE_INFO ::= SEQUENCE {
+//     inactive              [0] BOOLEAN DEFAULT FALSE,
+//     reset                 [1] BOOLEAN DEFAULT FALSE,
+//     expired               [2] BOOLEAN DEFAULT FALSE,
+//     remaining_grace       [3] INTEGER OPTIONAL,
+//     seconds_before_unlock [4] INTEGER OPTIONAL }
+impl<'a> FromBer<'a, LdapError> for AccountUsableResponse {
+    fn from_ber(bytes: &'a [u8]) -> ParseResult<'a, Self, LdapError> {
+        let (rem, t) =
+            OptTaggedImplicit::<i32, asn1_rs::Error, 0>::from_ber(bytes).map_err(Err::convert)?;
+        if let Some(t) = t {
+            return Ok((rem, AccountUsableResponse::Available(t.into_inner())));
+        }
+        TaggedParser::from_ber_and_then(Class::ContextSpecific, 1, bytes, |i| {
+            let (i, inactive) =
+                OptTaggedImplicit::<bool, asn1_rs::Error, 0>::from_ber(i).map_err(Err::convert)?;
+            let (i, reset) =
+                OptTaggedImplicit::<bool, asn1_rs::Error, 1>::from_ber(i).map_err(Err::convert)?;
+            let (i, expired) =
+                OptTaggedImplicit::<bool, asn1_rs::Error, 2>::from_ber(i).map_err(Err::convert)?;
+            let (i, remaining_grace) =
+                OptTaggedImplicit::<i32, asn1_rs::Error, 3>::from_ber(i).map_err(Err::convert)?;
+            let (i, seconds_before_unlock) =
+                OptTaggedImplicit::<i32, asn1_rs::Error, 4>::from_ber(i).map_err(Err::convert)?;
+            let value = AccountNotAvailable {
+                inactive: inactive.map(|t| t.into_inner()).unwrap_or(false),
+                reset: reset.map(|t| t.into_inner()).unwrap_or(false),
+                expired: expired.map(|t| t.into_inner()).unwrap_or(false),
+                remaining_grace: remaining_grace.map(|t| t.into_inner()),
+                seconds_before_unlock: seconds_before_unlock.map(|t| t.into_inner()),
+            };
+            Ok((i, AccountUsableResponse::NotAvailable(value)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    fn control<'a>(oid: &'a str, value: Option<&'a [u8]>) -> Control<'a> {
+        Control {
+            control_type: LdapOID(Cow::Borrowed(oid)),
+            criticality: false,
+            control_value: value.map(Cow::Borrowed),
+        }
+    }
+
+    #[test]
+    fn test_paged_results_control() {
+        const DATA: &[u8] = &hex!("30 0b 02 01 0a 04 06 01 02 03 04 05 06");
+        let c = control(OID_PAGED_RESULTS, Some(DATA));
+        let value = PagedResultsControl::from_control(&c)
+            .expect("control not recognized")
+            .expect("parsing failed");
+        assert_eq!(value.size, 10);
+        assert_eq!(value.cookie.as_ref(), &hex!("01 02 03 04 05 06"));
+        // wrong OID
+        let c = control("1.2.840.113556.1.4.473", Some(DATA));
+        assert!(PagedResultsControl::from_control(&c).is_none());
+        // missing value
+        let c = control(OID_PAGED_RESULTS, None);
+        assert_eq!(
+            PagedResultsControl::from_control(&c),
+            Some(Err(LdapError::MissingControlValue))
+        );
+        // negative size
+        let c = control(OID_PAGED_RESULTS, Some(&hex!("30 05 02 01 ff 04 00")));
+        assert_eq!(
+            PagedResultsControl::from_control(&c),
+            Some(Err(LdapError::IntegerOutOfRange))
+        );
+    }
+
+    #[test]
+    fn test_control_value_trailing_data() {
+        const DATA: &[u8] = &hex!("30 05 02 01 0a 04 00 ff");
+        let c = control(OID_PAGED_RESULTS, Some(DATA));
+        assert_eq!(
+            PagedResultsControl::from_control(&c),
+            Some(Err(LdapError::TrailingData))
+        );
+        assert!(matches!(
+            c.parse_known(),
+            KnownControl::Invalid {
+                error: LdapError::TrailingData,
+                ..
+            }
+        ));
+        assert_eq!(
+            c.parse_value_as::<PagedResultsControl>(),
+            Some(Err(LdapError::TrailingData))
+        );
+    }
+
+    #[test]
+    fn test_server_sort_controls() {
+        const REQ: &[u8] = &hex!(
+            "30 19 30 07 04 02 63 6e 81 01 ff
+             30 0e 04 02 73 6e 80 08 32 2e 35 2e 31 33 2e 33"
+        );
+        let c = control(OID_SERVER_SORT_REQUEST, Some(REQ));
+        let value = ServerSortRequest::from_control(&c)
+            .expect("control not recognized")
+            .expect("parsing failed");
+        assert_eq!(value.sort_keys.len(), 2);
+        assert_eq!(&value.sort_keys[0].attribute_type.0, "cn");
+        assert!(value.sort_keys[0].ordering_rule.is_none());
+        assert!(value.sort_keys[0].reverse);
+        assert_eq!(
+            value.sort_keys[1]
+                .ordering_rule
+                .as_ref()
+                .map(|r| r.0.as_ref()),
+            Some("2.5.13.3")
+        );
+        assert!(!value.sort_keys[1].reverse);
+
+        const RESP: &[u8] = &hex!("30 07 0a 01 10 80 02 63 6e");
+        let c = control(OID_SERVER_SORT_RESPONSE, Some(RESP));
+        let value = ServerSortResponse::from_control(&c)
+            .expect("control not recognized")
+            .expect("parsing failed");
+        assert_eq!(value.sort_result, SortResult::NoSuchAttribute);
+        assert_eq!(
+            value.attribute_type.as_ref().map(|t| t.0.as_ref()),
+            Some("cn")
+        );
+
+        // invalid UTF-8
+        let c = control(
+            OID_SERVER_SORT_REQUEST,
+            Some(&hex!("30 0b 30 09 04 02 63 6e 80 03 32 2e ff")),
+        );
+        assert_eq!(
+            ServerSortRequest::from_control(&c),
+            Some(Err(LdapError::InvalidUtf8 {
+                field: "MatchingRuleId",
+                valid_up_to: 2
+            }))
+        );
+        let c = control(
+            OID_SERVER_SORT_RESPONSE,
+            Some(&hex!("30 07 0a 01 10 80 02 63 ff")),
+        );
+        assert_eq!(
+            ServerSortResponse::from_control(&c),
+            Some(Err(LdapError::InvalidUtf8 {
+                field: "AttributeDescription",
+                valid_up_to: 1
+            }))
+        );
+    }
+
+    #[test]
+    fn test_sync_controls() {
+        const REQ: &[u8] = &hex!("30 08 0a 01 03 04 03 61 62 63");
+        let c = control(OID_SYNC_REQUEST, Some(REQ));
+        let value = SyncRequestControl::from_control(&c)
+            .expect("control not recognized")
+            .expect("parsing failed");
+        assert_eq!(value.mode, SyncRequestMode::RefreshAndPersist);
+        assert_eq!(
+            value.cookie.as_ref().map(SyncCookie::as_bytes),
+            Some(&b"abc"[..])
+        );
+        assert!(!value.reload_hint);
+
+        const STATE: &[u8] = &hex!(
+            "30 17 0a 01 01 04 10 00 01 02 03 04 05 06 07 08 09 0a 0b 0c 0d 0e 0f
+             04 00"
+        );
+        let c = control(OID_SYNC_STATE, Some(STATE));
+        let value = SyncStateControl::from_control(&c)
+            .expect("control not recognized")
+            .expect("parsing failed");
+        assert_eq!(value.state, SyncStateValue::Add);
+        assert_eq!(
+            value.entry_uuid,
+            hex!("00 01 02 03 04 05 06 07 08 09 0a 0b 0c 0d 0e 0f")
+        );
+        // empty cookie is not the same as an absent cookie
+        assert!(matches!(value.cookie, Some(ref c) if c.is_empty()));
+        // invalid UUID
+        let c = control(OID_SYNC_STATE, Some(&hex!("30 07 0a 01 01 04 02 00 01")));
+        assert_eq!(
+            SyncStateControl::from_control(&c),
+            Some(Err(LdapError::InvalidUUID))
+        );
+
+        const DONE: &[u8] = &hex!("30 03 01 01 ff");
+        let c = control(OID_SYNC_DONE, Some(DONE));
+        let value = SyncDoneControl::from_control(&c)
+            .expect("control not recognized")
+            .expect("parsing failed");
+        assert!(value.cookie.is_none());
+        assert!(value.refresh_deletes);
+    }
+
+    #[test]
+    fn test_has_cookie() {
+        fn cookie_of<C: HasCookie>(c: &C) -> Option<Vec<u8>> {
+            c.cookie().map(|c| c.to_vec())
+        }
+        let paged = PagedResultsControl {
+            size: 0,
+            cookie: SyncCookie(Cow::Borrowed(b"")),
+        };
+        assert_eq!(cookie_of(&paged), Some(Vec::new()));
+        let done = SyncDoneControl {
+            cookie: Some(SyncCookie(Cow::Borrowed(b"abc"))),
+            refresh_deletes: false,
+        };
+        assert_eq!(cookie_of(&done), Some(b"abc".to_vec()));
+        let state = SyncStateControl {
+            state: SyncStateValue::Present,
+            entry_uuid: [0; 16],
+            cookie: None,
+        };
+        assert_eq!(cookie_of(&state), None);
+    }
+
+    #[test]
+    fn test_parse_known() {
+        let c = control(OID_SYNC_DONE, Some(&hex!("30 00")));
+        assert_eq!(
+            c.parse_known(),
+            KnownControl::SyncDone(SyncDoneControl {
+                cookie: None,
+                refresh_deletes: false
+            })
+        );
+        let c = control(OID_PAGED_RESULTS, None);
+        assert_eq!(
+            c.parse_known(),
+            KnownControl::Invalid {
+                control: &c,
+                error: LdapError::MissingControlValue
+            }
+        );
+        let c = control("1.2.3.4", None);
+        assert_eq!(c.parse_known(), KnownControl::Unknown(&c));
+    }
+
+    #[test]
+    fn test_password_policy_response() {
+        const DATA: &[u8] = &hex!("30 06 a0 04 80 02 0e 10");
+        let c = control(OID_PASSWORD_POLICY, Some(DATA));
+        let value = PasswordPolicyResponse::from_control(&c)
+            .expect("control not recognized")
+            .expect("parsing failed");
+        assert_eq!(
+            value.warning,
+            Some(PasswordPolicyWarning::TimeBeforeExpiration(3600))
+        );
+        assert!(value.error.is_none());
+
+        const GRACE: &[u8] = &hex!("30 08 a0 03 81 01 02 81 01 00");
+        let c = control(OID_PASSWORD_POLICY, Some(GRACE));
+        let value = PasswordPolicyResponse::from_control(&c)
+            .expect("control not recognized")
+            .expect("parsing failed");
+        assert_eq!(
+            value.warning,
+            Some(PasswordPolicyWarning::GraceAuthNsRemaining(2))
+        );
+        assert_eq!(value.error, Some(PasswordPolicyError::PasswordExpired));
+
+        // empty value
+        let c = control(OID_PASSWORD_POLICY, Some(&hex!("30 00")));
+        assert_eq!(
+            PasswordPolicyResponse::from_control(&c),
+            Some(Ok(PasswordPolicyResponse {
+                warning: None,
+                error: None
+            }))
+        );
+    }
+
+    #[test]
+    fn test_password_policy_request() {
+        let c = control(OID_PASSWORD_POLICY, None);
+        assert!(c.is_password_policy_request());
+        assert_eq!(
+            PasswordPolicyRequestControl::from_control(&c),
+            Some(Ok(PasswordPolicyRequestControl))
+        );
+        assert_eq!(
+            c.parse_known(),
+            KnownControl::PasswordPolicyRequest(PasswordPolicyRequestControl)
+        );
+        let c = control(OID_PASSWORD_POLICY, Some(&hex!("30 00")));
+        assert!(!c.is_password_policy_request());
+        assert_eq!(
+            PasswordPolicyRequestControl::from_control(&c),
+            Some(Err(LdapError::UnexpectedControlValue))
+        );
+        assert!(matches!(c.parse_known(), KnownControl::PasswordPolicy(_)));
+        assert!(!control(OID_MANAGE_DSA_IT, None).is_password_policy_request());
+    }
+
+    #[test]
+    fn test_account_usable_controls() {
+        let c = control(OID_ACCOUNT_USABLE, None);
+        assert_eq!(
+            c.parse_known(),
+            KnownControl::AccountUsableRequest(AccountUsableRequestControl)
+        );
+        let c = control(OID_ACCOUNT_USABLE, Some(&hex!("80 02 0e 10")));
+        assert_eq!(
+            AccountUsableResponse::from_control(&c),
+            Some(Ok(AccountUsableResponse::Available(3600)))
+        );
+        let c = control(OID_ACCOUNT_USABLE, Some(&hex!("a1 06 80 01 ff 83 01 02")));
+        let expected = AccountNotAvailable {
+            inactive: true,
+            remaining_grace: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(
+            c.parse_known(),
+            KnownControl::AccountUsableResponse(AccountUsableResponse::NotAvailable(expected))
+        );
+        assert_eq!(
+            AccountUsableRequestControl::from_control(&c),
+            Some(Err(LdapError::UnexpectedControlValue))
+        );
+        let c = control(OID_ACCOUNT_USABLE, Some(&hex!("04 00")));
+        assert!(matches!(
+            AccountUsableResponse::from_control(&c),
+            Some(Err(_))
+        ));
+    }
+
+    #[test]
+    fn test_manage_dsa_it_assertion_read_controls() {
+        let c = control(OID_MANAGE_DSA_IT, None);
+        assert_eq!(
+            ManageDsaITControl::from_control(&c),
+            Some(Ok(ManageDsaITControl))
+        );
+        let c = control(OID_MANAGE_DSA_IT, Some(b""));
+        assert_eq!(
+            ManageDsaITControl::from_control(&c),
+            Some(Err(LdapError::UnexpectedControlValue))
+        );
+
+        // (cn=*)
+        let c = control(OID_ASSERTION, Some(&hex!("87 02 63 6e")));
+        let value = AssertionControl::from_control(&c)
+            .expect("control not recognized")
+            .expect("parsing failed");
+        assert!(matches!(value.filter, Filter::Present(ref s) if s.0 == "cn"));
+
+        const ENTRY: &[u8] = include_bytes!("../assets/search_result_entry.bin");
+        let c = control(OID_POST_READ, Some(ENTRY));
+        let value = ReadEntryControl::from_control(&c)
+            .expect("control not recognized")
+            .expect("parsing failed");
+        assert_eq!(value.entry.attributes.len(), 1);
+        assert!(matches!(c.parse_known(), KnownControl::PostRead(_)));
+        let c = control(OID_PRE_READ, Some(ENTRY));
+        assert!(matches!(c.parse_known(), KnownControl::PreRead(_)));
+    }
+
+    #[test]
+    fn test_vlv_controls() {
+        // byOffset, with contextID
+        const REQ_OFFSET: &[u8] = &hex!("30 11 02 01 00 02 01 13 a0 06 02 01 01 02 01 00 04 01 2a");
+        let c = control(OID_VLV_REQUEST, Some(REQ_OFFSET));
+        let value = VlvRequest::from_control(&c)
+            .expect("control not recognized")
+            .expect("parsing failed");
+        assert_eq!((value.before_count, value.after_count), (0, 19));
+        assert_eq!(
+            value.target,
+            VlvTarget::ByOffset {
+                offset: 1,
+                content_count: 0
+            }
+        );
+        assert_eq!(value.context_id.as_deref(), Some(&b"*"[..]));
+        // greaterThanOrEqual
+        const REQ_GE: &[u8] = &hex!("30 0c 02 01 01 02 01 02 81 04 4a 6f 68 6e");
+        let c = control(OID_VLV_REQUEST, Some(REQ_GE));
+        let value = VlvRequest::from_control(&c)
+            .expect("control not recognized")
+            .expect("parsing failed");
+        assert!(
+            matches!(value.target, VlvTarget::GreaterThanOrEqual(ref v) if v.0.as_ref() == b"John")
+        );
+        assert!(value.context_id.is_none());
+        // invalid target
+        const REQ_BAD: &[u8] = &hex!("30 0c 02 01 01 02 01 02 82 04 4a 6f 68 6e");
+        let c = control(OID_VLV_REQUEST, Some(REQ_BAD));
+        assert!(matches!(VlvRequest::from_control(&c), Some(Err(_))));
+
+        const RESP: &[u8] = &hex!("30 09 02 01 05 02 01 64 0a 01 3d");
+        let c = control(OID_VLV_RESPONSE, Some(RESP));
+        let value = VlvResponse::from_control(&c)
+            .expect("control not recognized")
+            .expect("parsing failed");
+        assert_eq!((value.target_position, value.content_count), (5, 100));
+        assert_eq!(value.result, VirtualListViewResult::OffsetRangeError);
+        assert!(matches!(c.parse_known(), KnownControl::VlvResponse(_)));
+        // contentCount larger than maxInt
+        const RESP_BAD: &[u8] = &hex!("30 0d 02 01 05 02 05 00 80 00 00 00 0a 01 3d");
+        let c = control(OID_VLV_RESPONSE, Some(RESP_BAD));
+        assert_eq!(
+            VlvResponse::from_control(&c),
+            Some(Err(LdapError::IntegerOutOfRange))
+        );
+    }
+
+    #[test]
+    fn test_proxy_authz_control() {
+        let mut c = control(OID_PROXY_AUTHZ, Some(b"dn:cn=admin,dc=example,dc=com"));
+        c.criticality = true;
+        let value = ProxyAuthzControl::from_control(&c)
+            .expect("control not recognized")
+            .expect("parsing failed");
+        assert_eq!(value.authz_id.0, "dn:cn=admin,dc=example,dc=com");
+        assert!(value.criticality);
+        assert!(!value.is_anonymous());
+        let c = control(OID_PROXY_AUTHZ, Some(b""));
+        assert!(matches!(c.parse_known(), KnownControl::ProxyAuthz(ref v) if v.is_anonymous()));
+        let c = control(OID_PROXY_AUTHZ, Some(&[0xff]));
+        assert_eq!(
+            ProxyAuthzControl::from_control(&c),
+            Some(Err(LdapError::InvalidUtf8 {
+                field: "authzId",
+                valid_up_to: 0
+            }))
+        );
+    }
+
+    #[test]
+    fn test_dirsync_control() {
+        // flags = INCREMENTAL_VALUES | OBJECT_SECURITY (encoded as a negative integer)
+        const DATA: &[u8] = &hex!("30 11 02 04 80 00 00 01 02 03 10 00 00 04 04 de ad be ef");
+        let c = control(OID_DIRSYNC, Some(DATA));
+        let value = DirSyncControl::from_control(&c)
+            .expect("control not recognized")
+            .expect("parsing failed");
+        assert!(value.flags.contains(DirSyncFlags::INCREMENTAL_VALUES));
+        assert!(value.flags.contains(DirSyncFlags::OBJECT_SECURITY));
+        assert!(!value.flags.contains(DirSyncFlags::PUBLIC_DATA_ONLY));
+        assert_eq!(value.max_bytes, 0x10_0000);
+        assert_eq!(value.cookie.as_ref(), &hex!("de ad be ef"));
+    }
+
+    #[test]
+    fn test_persistent_search_controls() {
+        const PSEARCH: &[u8] = &hex!("30 09 02 01 0f 01 01 ff 01 01 ff");
+        let c = control(OID_PERSISTENT_SEARCH, Some(PSEARCH));
+        let value = PersistentSearchControl::from_control(&c)
+            .expect("control not recognized")
+            .expect("parsing failed");
+        assert!(value.has_change_type(ChangeType::ModDN));
+        assert!(value.changes_only && value.return_ecs);
+
+        // modDN, with previousDN and changeNumber
+        const ECN_MODDN: &[u8] = &hex!("30 0f 0a 01 08 04 07 63 6e 3d 6f 6c 64 31 02 01 2a");
+        let c = control(OID_ENTRY_CHANGE_NOTIFICATION, Some(ECN_MODDN));
+        let value = EntryChangeNotificationControl::from_control(&c)
+            .expect("control not recognized")
+            .expect("parsing failed");
+        assert_eq!(value.change_type, ChangeType::ModDN);
+        assert_eq!(
+            value.previous_dn.as_ref().map(|dn| dn.0.as_ref()),
+            Some("cn=old1")
+        );
+        assert_eq!(value.change_number, Some(42));
+        // modify, without optional fields
+        const ECN_MODIFY: &[u8] = &hex!("30 03 0a 01 04");
+        let c = control(OID_ENTRY_CHANGE_NOTIFICATION, Some(ECN_MODIFY));
+        let value = EntryChangeNotificationControl::from_control(&c)
+            .expect("control not recognized")
+            .expect("parsing failed");
+        assert_eq!(value.change_type, ChangeType::Modify);
+        assert!(value.previous_dn.is_none() && value.change_number.is_none());
+        assert!(matches!(
+            c.parse_known(),
+            KnownControl::EntryChangeNotification(_)
+        ));
+    }
+
+    #[test]
+    fn test_matched_values_control() {
+        // (cn=*)(mail=*@example.com)
+        const DATA: &[u8] = &hex!(
+            "30 1c 87 02 63 6e a4 16 04 04 6d 61 69 6c 30 0e 82 0c 40 65 78 61 6d 70 6c 65 2e 63 6f 6d"
+        );
+        let c = control(OID_MATCHED_VALUES, Some(DATA));
+        let value = MatchedValuesControl::from_control(&c)
+            .expect("control not recognized")
+            .expect("parsing failed");
+        assert_eq!(value.filters.len(), 2);
+        assert!(matches!(c.parse_known(), KnownControl::MatchedValues(_)));
+        assert!(matches!(value.filters[1], Filter::Substrings(_)));
+        // (!(cn=*))
+        const NOT: &[u8] = &hex!("30 06 a2 04 87 02 63 6e");
+        let c = control(OID_MATCHED_VALUES, Some(NOT));
+        assert_eq!(
+            MatchedValuesControl::from_control(&c),
+            Some(Err(LdapError::InvalidFilterType))
+        );
+        let c = control(OID_MATCHED_VALUES, Some(&hex!("30 00")));
+        assert_eq!(
+            MatchedValuesControl::from_control(&c),
+            Some(Err(LdapError::EmptyFilter))
+        );
+    }
+
+    #[test]
+    fn test_authz_id_controls() {
+        let c = control(OID_AUTHZ_ID_REQUEST, None);
+        assert_eq!(
+            AuthzIdRequestControl::from_control(&c),
+            Some(Ok(AuthzIdRequestControl))
+        );
+        let c = control(OID_AUTHZ_ID_RESPONSE, Some(b"u:bob"));
+        let value = AuthzIdResponseControl::from_control(&c)
+            .expect("control not recognized")
+            .expect("parsing failed");
+        assert_eq!(value.authz_id.0, "u:bob");
+        assert!(matches!(c.parse_known(), KnownControl::AuthzIdResponse(_)));
+        assert!(AuthzIdRequestControl::from_control(&c).is_none());
+    }
+
+    #[test]
+    fn test_parse_value_as() {
+        let c = control(OID_PAGED_RESULTS, Some(&hex!("30 05 02 01 0a 04 00")));
+        let value = c
+            .parse_value_as::<PagedResultsControl>()
+            .expect("no value")
+            .expect("parsing failed");
+        assert_eq!(value.size, 10);
+        let c = control(OID_PAGED_RESULTS, Some(&hex!("04 00")));
+        assert!(matches!(
+            c.parse_value_as::<PagedResultsControl>(),
+            Some(Err(_))
+        ));
+        let c = control(OID_PAGED_RESULTS, None);
+        assert!(c.parse_value_as::<PagedResultsControl>().is_none());
+    }
+
+    #[test]
+    fn test_subentries_control() {
+        let c = control(OID_SUBENTRIES, Some(&hex!("01 01 ff")));
+        let value = SubentriesControl::from_control(&c)
+            .expect("control not recognized")
+            .expect("parsing failed");
+        assert!(value.visibility);
+        assert_eq!(
+            c.parse_known(),
+            KnownControl::Subentries(SubentriesControl { visibility: true })
+        );
+        let c = control(OID_SUBENTRIES, None);
+        assert_eq!(
+            SubentriesControl::from_control(&c),
+            Some(Err(LdapError::MissingControlValue))
+        );
+        let c = control(OID_SUBENTRIES, Some(&hex!("04 00")));
+        assert!(matches!(SubentriesControl::from_control(&c), Some(Err(_))));
+    }
+
+    #[test]
+    fn test_valueless_controls() {
+        let c = control(OID_DONT_USE_COPY, None);
+        assert_eq!(
+            DontUseCopyControl::from_control(&c),
+            Some(Ok(DontUseCopyControl))
+        );
+        assert_eq!(
+            c.parse_known(),
+            KnownControl::DontUseCopy(DontUseCopyControl)
+        );
+        let mut c = control(OID_NO_OP, Some(b"x"));
+        assert_eq!(NoOpControl::from_control(&c), Some(Ok(NoOpControl)));
+        c.criticality = true;
+        assert_eq!(
+            NoOpControl::from_control(&c),
+            Some(Err(LdapError::UnexpectedControlValue))
+        );
+        assert!(matches!(
+            c.parse_known(),
+            KnownControl::Invalid {
+                error: LdapError::UnexpectedControlValue,
+                ..
+            }
+        ));
+        assert!(DontUseCopyControl::from_control(&c).is_none());
+    }
+
+    #[test]
+    fn test_active_directory_valueless_controls() {
+        let c = control(OID_PERMISSIVE_MODIFY, None);
+        assert_eq!(
+            PermissiveModifyControl::from_control(&c),
+            Some(Ok(PermissiveModifyControl))
+        );
+        assert_eq!(
+            c.parse_known(),
+            KnownControl::PermissiveModify(PermissiveModifyControl)
+        );
+        let c = control(OID_SHOW_DELETED, None);
+        assert_eq!(
+            c.parse_known(),
+            KnownControl::ShowDeleted(ShowDeletedControl)
+        );
+        assert!(PermissiveModifyControl::from_control(&c).is_none());
+        let c = control(OID_SHOW_DELETED, Some(b""));
+        assert_eq!(
+            ShowDeletedControl::from_control(&c),
+            Some(Err(LdapError::UnexpectedControlValue))
+        );
+        assert!(matches!(
+            c.parse_known(),
+            KnownControl::Invalid {
+                error: LdapError::UnexpectedControlValue,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_transaction_specification_control() {
+        let c = control(OID_TRANSACTION_SPECIFICATION, Some(b"txn-1"));
+        let value = TransactionSpecificationControl::from_control(&c)
+            .expect("control not recognized")
+            .expect("parsing failed");
+        assert_eq!(value.identifier.as_ref(), b"txn-1");
+        assert!(matches!(
+            c.parse_known(),
+            KnownControl::TransactionSpecification(_)
+        ));
+        let c = control(OID_TRANSACTION_SPECIFICATION, None);
+        assert_eq!(
+            TransactionSpecificationControl::from_control(&c),
+            Some(Err(LdapError::MissingControlValue))
+        );
+    }
+}
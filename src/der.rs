@@ -0,0 +1,219 @@
+//! Strict DER parsing
+//!
+//! LDAP messages are encoded using BER, with the restrictions of [RFC4511] section 5.1. The
+//! `FromDer` implementations in this module first check that the input is in DER canonical form
+//! (definite and minimal lengths, minimal integers, canonical booleans, primitive strings), and
+//! then use the BER parsers.
+//!
+//! [RFC4511]: https://tools.ietf.org/html/rfc4511
+
+use crate::error::*;
+use crate::filter::{Filter, MAX_FILTER_DEPTH};
+use crate::ldap::*;
+use asn1_rs::nom;
+use asn1_rs::{Class, DerConstraint, Error, FromBer, FromDer, Header, Length, ParseResult, Tag};
+use nom::bytes::complete::take;
+use nom::{Err, Needed};
+
+#[inline]
+fn der_constraint_failed<T>(constraint: DerConstraint) -> Result<'static, T> {
+    Err(Err::Error(LdapError::Ber(Error::DerConstraintFailed(
+        constraint,
+    ))))
+}
+
+/// Check that the length of the object starting at `bytes` is encoded using the minimal number
+/// of octets
+fn check_minimal_length(bytes: &[u8]) -> Result<'_, ()> {
+    let identifier = match bytes.first() {
+        Some(&b) => b,
+        None => return Err(Err::Incomplete(Needed::new(1))),
+    };
+    // skip identifier octets
+    let mut idx = 1;
+    if identifier & 0x1f == 0x1f {
+        while matches!(bytes.get(idx), Some(b) if b & 0x80 != 0) {
+            idx += 1;
+        }
+        idx += 1;
+    }
+    let first = match bytes.get(idx) {
+        Some(&b) => b,
+        None => return Err(Err::Error(LdapError::Ber(Error::InvalidLength))),
+    };
+    // indefinite length (0x80) is checked later
+    if first > 0x80 {
+        let len_bytes = &bytes[idx + 1..];
+        // long form must not be used for short lengths, and must not have leading zeroes
+        if len_bytes.first() == Some(&0)
+            || (first == 0x81 && !matches!(len_bytes.first(), Some(&b) if b >= 0x80))
+        {
+            return Err(Err::Error(LdapError::Ber(Error::InvalidLength)));
+        }
+    }
+    Ok((bytes, ()))
+}
+
+/// Check that the content of a primitive object respects DER constraints
+fn check_der_primitive(header: &Header, content: &[u8]) -> Result<'static, ()> {
+    if header.class() != Class::Universal {
+        // implicitly tagged values cannot be checked without knowing their type
+        return Ok((&[], ()));
+    }
+    match header.tag() {
+        Tag::Boolean if content != [0] && content != [0xff] => {
+            return der_constraint_failed(DerConstraint::InvalidBoolean)
+        }
+        Tag::Integer | Tag::Enumerated => match content {
+            [] => return der_constraint_failed(DerConstraint::IntegerEmpty),
+            [0, b, ..] if *b < 0x80 => {
+                return der_constraint_failed(DerConstraint::IntegerLeadingZeroes)
+            }
+            [0xff, b, ..] if *b >= 0x80 => {
+                return der_constraint_failed(DerConstraint::IntegerLeadingFF)
+            }
+            _ => (),
+        },
+        _ => (),
+    }
+    Ok((&[], ()))
+}
+
+/// Maximum nesting depth of constructed objects checked by [`check_der`]
+///
+/// Filters are the only recursive type in LDAP messages: allow [`MAX_FILTER_DEPTH`] levels of
+/// filters, plus the levels of the message, operation and filter items.
+const MAX_DER_DEPTH: usize = MAX_FILTER_DEPTH + 16;
+
+/// Check that the object starting at `bytes` (and all its sub-objects) is DER-encoded
+fn check_der(bytes: &[u8]) -> Result<'_, ()> {
+    check_der_with_depth(bytes, MAX_DER_DEPTH)
+}
+
+fn check_der_with_depth(bytes: &[u8], max_depth: usize) -> Result<'_, ()> {
+    if max_depth == 0 {
+        return Err(Err::Error(LdapError::RecursionLimitExceeded));
+    }
+    check_minimal_length(bytes)?;
+    let (i, header) = Header::from_der(bytes).map_err(Err::convert)?;
+    let len = match header.length() {
+        Length::Definite(len) => len,
+        Length::Indefinite => return der_constraint_failed(DerConstraint::IndefiniteLength),
+    };
+    let (rem, content) = take(len)(i)?;
+    if header.is_constructed() {
+        // strings must be primitive
+        if header.class() == Class::Universal && header.tag() == Tag::OctetString {
+            return der_constraint_failed(DerConstraint::Constructed);
+        }
+        let mut i = content;
+        while !i.is_empty() {
+            let (rem, _) = check_der_with_depth(i, max_depth - 1)?;
+            i = rem;
+        }
+    } else {
+        check_der_primitive(&header, content)?;
+    }
+    Ok((rem, ()))
+}
+
+macro_rules! impl_from_der {
+    ($($name:ident),* $(,)?) => {
+        $(
+            impl<'a> FromDer<'a, LdapError> for $name<'a> {
+                fn from_der(bytes: &'a [u8]) -> ParseResult<'a, Self, LdapError> {
+                    check_der(bytes)?;
+                    Self::from_ber(bytes)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_der!(LdapMessage, BindRequest, SearchRequest, Filter);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    #[test]
+    fn test_from_der() {
+        const DATA: &[u8] = include_bytes!("../assets/message-search-request-01.bin");
+        let (rem, msg) = LdapMessage::from_der(DATA).expect("parsing failed");
+        assert!(rem.is_empty());
+        assert_eq!(msg, LdapMessage::from_ber(DATA).expect("parsing failed").1);
+    }
+
+    #[test]
+    fn test_from_der_reject() {
+        // indefinite length
+        const INDEFINITE: &[u8] = &hex!("30 80 02 01 01 42 00 00 00");
+        assert!(LdapMessage::from_ber(INDEFINITE).is_ok());
+        assert_eq!(
+            LdapMessage::from_der(INDEFINITE),
+            Err(Err::Error(LdapError::Ber(Error::DerConstraintFailed(
+                DerConstraint::IndefiniteLength
+            ))))
+        );
+        // non-minimal integer (message ID)
+        const LEADING_ZERO: &[u8] = &hex!("30 06 02 02 00 01 42 00");
+        assert!(LdapMessage::from_ber(LEADING_ZERO).is_ok());
+        assert_eq!(
+            LdapMessage::from_der(LEADING_ZERO),
+            Err(Err::Error(LdapError::Ber(Error::DerConstraintFailed(
+                DerConstraint::IntegerLeadingZeroes
+            ))))
+        );
+        // non-minimal length
+        const LONG_LENGTH: &[u8] = &hex!("30 81 05 02 01 01 42 00");
+        assert!(LdapMessage::from_ber(LONG_LENGTH).is_ok());
+        assert_eq!(
+            LdapMessage::from_der(LONG_LENGTH),
+            Err(Err::Error(LdapError::Ber(Error::InvalidLength)))
+        );
+    }
+
+    #[test]
+    fn test_from_der_depth_limit() {
+        // 200k nested SEQUENCEs, with minimal lengths: compute the headers from the innermost
+        // object, then write them from the outermost
+        let mut headers = Vec::new();
+        let mut len = 0usize;
+        for _ in 0..200_000 {
+            let len_bytes = (len as u32).to_be_bytes();
+            let mut header = vec![0x30];
+            if len < 0x80 {
+                header.push(len as u8);
+            } else {
+                let skip = len_bytes.iter().take_while(|&&b| b == 0).count();
+                header.push(0x80 | (4 - skip) as u8);
+                header.extend_from_slice(&len_bytes[skip..]);
+            }
+            len += header.len();
+            headers.push(header);
+        }
+        let data: Vec<u8> = headers.into_iter().rev().flatten().collect();
+        assert_eq!(
+            LdapMessage::from_der(&data),
+            Err(Err::Error(LdapError::RecursionLimitExceeded))
+        );
+        // filters up to the default limit are accepted
+        let mut filter = hex!("87 02 63 6e").to_vec();
+        for _ in 0..MAX_FILTER_DEPTH - 1 {
+            let mut outer = vec![0xa2, filter.len() as u8];
+            outer.extend_from_slice(&filter);
+            filter = outer;
+        }
+        Filter::from_der(&filter).expect("parsing failed");
+    }
+
+    #[test]
+    fn test_from_der_empty() {
+        assert!(matches!(
+            LdapMessage::from_der(&[]),
+            Err(Err::Incomplete(_))
+        ));
+        assert!(matches!(Filter::from_der(&[]), Err(Err::Incomplete(_))));
+    }
+}
@@ -0,0 +1,259 @@
+//! Parsing of the string representation of distinguished names ([RFC4514])
+//!
+//! [RFC4514]: https://tools.ietf.org/html/rfc4514
+
+use crate::error::LdapError;
+use crate::filter_string::hex_value;
+use crate::ldap::LdapDN;
+
+impl LdapDN<'_> {
+    /// Split the distinguished name into its relative distinguished names ([RFC4514])
+    ///
+    /// Each RDN is returned as a list of `(attributeType, attributeValue)` pairs (more than one
+    /// pair for multi-valued RDNs joined by `+`), in the order they appear in the string.
+    /// Escaped characters in values are decoded. Values using the `#` hexstring form are the
+    /// BER encoding of the value, and are returned as-is (including the leading `#`).
+    ///
+    /// Unescaped whitespace around separators is ignored. The empty DN returns an empty list.
+    ///
+    /// ```rust
+    /// use ldap_parser::ldap::LdapDN;
+    /// use std::borrow::Cow;
+    ///
+    /// let dn = LdapDN(Cow::Borrowed(r"cn=Smith\, John+uid=jsmith,dc=example,dc=net"));
+    /// let rdns = dn.parse_components().expect("invalid DN");
+    /// assert_eq!(rdns.len(), 3);
+    /// assert_eq!(rdns[0][0], ("cn".to_string(), "Smith, John".to_string()));
+    /// assert_eq!(rdns[0][1], ("uid".to_string(), "jsmith".to_string()));
+    /// ```
+    ///
+    /// [RFC4514]: https://tools.ietf.org/html/rfc4514
+    pub fn parse_components(&self) -> Result<Vec<Vec<(String, String)>>, LdapError> {
+        parse_dn_components(&self.0)
+    }
+
+    /// Compare two distinguished names, using a syntactic normalization
+    ///
+    /// Both DNs are split using [`parse_components`](Self::parse_components), then attribute
+    /// types and values are compared ignoring case, and the order of the values of multi-valued
+    /// RDNs is ignored. Whitespace around separators and escaping are not significant.
+    ///
+    /// This does not implement schema-aware matching: attribute types are not resolved (`cn` is
+    /// different from `2.5.4.3`), and all values are compared as case-insensitive strings.
+    /// If either DN is invalid, the DNs are equal only if their strings are identical.
+    ///
+    /// ```rust
+    /// use ldap_parser::ldap::LdapDN;
+    /// use std::borrow::Cow;
+    ///
+    /// let dn1 = LdapDN(Cow::Borrowed(r"CN=Smith\, John+UID=jsmith, DC=Example,DC=net"));
+    /// let dn2 = LdapDN(Cow::Borrowed(r"uid=JSmith+cn=smith\2C john,dc=example,dc=net"));
+    /// assert!(dn1.eq_normalized(&dn2));
+    /// ```
+    pub fn eq_normalized(&self, other: &LdapDN) -> bool {
+        match (normalize_dn(&self.0), normalize_dn(&other.0)) {
+            (Ok(dn1), Ok(dn2)) => dn1 == dn2,
+            _ => self.0 == other.0,
+        }
+    }
+}
+
+// Split the DN, convert types and values to lowercase, and sort the values of each RDN
+fn normalize_dn(s: &str) -> Result<Vec<Vec<(String, String)>>, LdapError> {
+    let mut rdns = parse_dn_components(s)?;
+    for rdn in rdns.iter_mut() {
+        for (attr_type, attr_value) in rdn.iter_mut() {
+            *attr_type = attr_type.to_ascii_lowercase();
+            *attr_value = attr_value.to_lowercase();
+        }
+        rdn.sort();
+    }
+    Ok(rdns)
+}
+
+// distinguishedName = [ relativeDistinguishedName
+//     *( COMMA relativeDistinguishedName ) ]
+// relativeDistinguishedName = attributeTypeAndValue
+//     *( PLUS attributeTypeAndValue )
+fn parse_dn_components(s: &str) -> Result<Vec<Vec<(String, String)>>, LdapError> {
+    let b = s.as_bytes();
+    let mut rdns = Vec::new();
+    if s.trim().is_empty() {
+        return Ok(rdns);
+    }
+    let mut rdn = Vec::new();
+    let mut pos = 0;
+    loop {
+        let (attr_type, attr_value, next) = parse_attribute_type_and_value(b, pos)?;
+        rdn.push((attr_type, attr_value));
+        match b.get(next) {
+            None => {
+                rdns.push(rdn);
+                return Ok(rdns);
+            }
+            Some(b'+') => (),
+            Some(b',') => rdns.push(std::mem::take(&mut rdn)),
+            Some(_) => return Err(LdapError::InvalidDN),
+        }
+        pos = next + 1;
+    }
+}
+
+// attributeTypeAndValue = attributeType EQUALS attributeValue
+// attributeType = descr / numericoid
+// attributeValue = string / hexstring
+//
+// Returns the type, the value, and the position of the next separator (or end of input)
+fn parse_attribute_type_and_value(
+    b: &[u8],
+    pos: usize,
+) -> Result<(String, String, usize), LdapError> {
+    let eq = b[pos..]
+        .iter()
+        .position(|&c| c == b'=')
+        .ok_or(LdapError::InvalidDN)?;
+    let attr_type = std::str::from_utf8(&b[pos..pos + eq])
+        .or(Err(LdapError::InvalidDN))?
+        .trim();
+    if attr_type.is_empty()
+        || !attr_type
+            .bytes()
+            .all(|c| c.is_ascii_alphanumeric() || c == b'-' || c == b'.')
+    {
+        return Err(LdapError::InvalidDN);
+    }
+    let mut pos = pos + eq + 1;
+    while b.get(pos) == Some(&b' ') {
+        pos += 1;
+    }
+    let (attr_value, pos) = if b.get(pos) == Some(&b'#') {
+        parse_hexstring(b, pos)?
+    } else {
+        parse_string(b, pos)?
+    };
+    Ok((attr_type.to_owned(), attr_value, pos))
+}
+
+// hexstring = SHARP 1*hexpair
+fn parse_hexstring(b: &[u8], pos: usize) -> Result<(String, usize), LdapError> {
+    let len = b[pos + 1..]
+        .iter()
+        .position(|c| !c.is_ascii_hexdigit())
+        .unwrap_or(b.len() - pos - 1);
+    if len == 0 || len % 2 != 0 {
+        return Err(LdapError::InvalidDN);
+    }
+    let end = pos + 1 + len;
+    // all characters are ASCII
+    let value = String::from_utf8_lossy(&b[pos..end]).into_owned();
+    let mut next = end;
+    while b.get(next) == Some(&b' ') {
+        next += 1;
+    }
+    match b.get(next) {
+        None | Some(b',') | Some(b'+') => Ok((value, next)),
+        Some(_) => Err(LdapError::InvalidDN),
+    }
+}
+
+// string = [ ( leadchar / pair ) [ *( stringchar / pair )
+//    ( trailchar / pair ) ] ]
+// pair = ESC ( ESC / special / hexpair )
+// special = escaped / SPACE / SHARP / EQUALS
+// escaped = DQUOTE / PLUS / COMMA / SEMI / LANGLE / RANGLE
+fn parse_string(b: &[u8], pos: usize) -> Result<(String, usize), LdapError> {
+    let mut v = Vec::new();
+    let mut pos = pos;
+    // number of unescaped trailing spaces, which are not part of the value
+    let mut trailing = 0;
+    while let Some(&c) = b.get(pos) {
+        match c {
+            b',' | b'+' => break,
+            b'\\' => {
+                let c1 = *b.get(pos + 1).ok_or(LdapError::InvalidDN)?;
+                match (hex_value(c1), b.get(pos + 2).and_then(|&c2| hex_value(c2))) {
+                    (Some(hi), Some(lo)) => {
+                        v.push((hi << 4) | lo);
+                        pos += 3;
+                    }
+                    _ if b" \"#+,;<=>\\".contains(&c1) => {
+                        v.push(c1);
+                        pos += 2;
+                    }
+                    _ => return Err(LdapError::InvalidDN),
+                }
+                trailing = 0;
+                continue;
+            }
+            0 | b'"' | b';' | b'<' | b'>' => return Err(LdapError::InvalidDN),
+            b' ' => trailing += 1,
+            _ => trailing = 0,
+        }
+        v.push(c);
+        pos += 1;
+    }
+    v.truncate(v.len() - trailing);
+    let value = String::from_utf8(v).or(Err(LdapError::InvalidDN))?;
+    Ok((value, pos))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::borrow::Cow;
+
+    fn components(s: &str) -> Result<Vec<Vec<(String, String)>>, LdapError> {
+        LdapDN(Cow::Borrowed(s)).parse_components()
+    }
+
+    #[test]
+    fn test_dn_components() {
+        let rdns = components("cn=username1,ou=users,dc=xxx,dc=internet").expect("invalid DN");
+        assert_eq!(rdns.len(), 4);
+        assert_eq!(rdns[1], vec![("ou".to_string(), "users".to_string())]);
+        assert!(components("").expect("invalid DN").is_empty());
+        // whitespace around separators is ignored, escaped whitespace is kept
+        let rdns = components(r"cn = a\20 , ou=b\,c\2b\3D").expect("invalid DN");
+        assert_eq!(rdns[0][0].1, "a ");
+        assert_eq!(rdns[1][0].1, "b,c+=");
+        // UTF-8 encoded as hex pairs
+        let rdns = components(r"cn=Lu\C4\8Di\C4\87").expect("invalid DN");
+        assert_eq!(rdns[0][0].1, "Lučić");
+        // hexstring values are kept as-is
+        let rdns = components("1.3.6.1.4.1.1466.0=#04024869,O=Test").expect("invalid DN");
+        assert_eq!(rdns[0][0].0, "1.3.6.1.4.1.1466.0");
+        assert_eq!(rdns[0][0].1, "#04024869");
+        assert_eq!(rdns[1][0].1, "Test");
+    }
+
+    #[test]
+    fn test_dn_components_invalid() {
+        assert_eq!(components("cn"), Err(LdapError::InvalidDN));
+        assert_eq!(components("=a"), Err(LdapError::InvalidDN));
+        assert_eq!(components("cn=a,"), Err(LdapError::InvalidDN));
+        assert_eq!(components(r"cn=a\q"), Err(LdapError::InvalidDN));
+        assert_eq!(components("cn=#123"), Err(LdapError::InvalidDN));
+        assert_eq!(components(r"cn=\ff"), Err(LdapError::InvalidDN));
+    }
+
+    #[test]
+    fn test_dn_eq_normalized() {
+        let eq = |s1: &str, s2: &str| {
+            LdapDN(Cow::Borrowed(s1)).eq_normalized(&LdapDN(Cow::Borrowed(s2)))
+        };
+        assert!(eq(
+            "cn=John Smith,dc=example,dc=net",
+            "CN=john smith , DC=Example,dc=NET"
+        ));
+        // escaped commas and multi-valued RDNs
+        assert!(eq(r"cn=Smith\, John,dc=net", r"cn=smith\2c john,dc=net"));
+        assert!(eq("cn=a+uid=b,dc=net", "UID=B + CN=A,dc=net"));
+        assert!(!eq(r"cn=a\,dc=net", "cn=a,dc=net"));
+        assert!(!eq("cn=a+uid=b,dc=net", "cn=a,uid=b,dc=net"));
+        assert!(!eq("cn=a,dc=net", "dc=net,cn=a"));
+        assert!(eq("", " "));
+        // invalid DNs are compared as strings
+        assert!(eq("cn", "cn"));
+        assert!(!eq("cn", "CN"));
+    }
+}
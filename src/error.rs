@@ -1,7 +1,8 @@
 //! LDAP errors
 
+use crate::ldap::ProtocolOpTag;
 use asn1_rs::nom;
-use asn1_rs::Error;
+use asn1_rs::{Class, Error};
 use nom::error::{ErrorKind, FromExternalError, ParseError};
 use nom::IResult;
 
@@ -18,6 +19,12 @@ pub enum LdapError {
     #[error("Invalid LDAP String encoding")]
     InvalidString,
 
+    #[error("Invalid UTF-8 in {field} (valid up to byte {valid_up_to})")]
+    InvalidUtf8 {
+        field: &'static str,
+        valid_up_to: usize,
+    },
+
     #[error("Invalid LDAP Authentication Type")]
     InvalidAuthenticationType,
 
@@ -29,24 +36,135 @@ pub enum LdapError {
 
     #[error("Invalid Type for Filter")]
     InvalidFilterType,
+    #[error("Unexpected class for Filter (expected {expected}, found {found})")]
+    UnexpectedFilterClass { expected: Class, found: Class },
     #[error("Invalid Type for Message")]
     InvalidMessageType,
+    #[error("Invalid Message ID")]
+    InvalidMessageId,
+    #[error("Integer out of range (0 .. maxInt)")]
+    IntegerOutOfRange,
+    #[error("Invalid search scope")]
+    InvalidSearchScope,
+    #[error("Invalid value for alias dereferencing")]
+    InvalidDerefAliases,
+    #[error("Invalid modify operation")]
+    InvalidModifyOperation,
+    #[error("Attribute without values")]
+    EmptyAttributeValues,
+    #[error("Trailing data after the last field of an operation")]
+    TrailingData,
+    #[error("Too many messages")]
+    TooManyMessages,
+    #[error("Frame length does not match the message length")]
+    InvalidFrameLength,
+
+    #[error("Recursion limit exceeded")]
+    RecursionLimitExceeded,
+
+    #[error("Empty filter")]
+    EmptyFilter,
+    #[error("Unbalanced parentheses in filter string")]
+    UnbalancedParentheses,
+    #[error("Invalid escape sequence in filter string")]
+    InvalidEscape,
+    #[error("Invalid filter string")]
+    InvalidFilterString,
+    #[error("Invalid LDAP URL")]
+    InvalidUrl,
+    #[error("Invalid SASL directive")]
+    InvalidSaslDirective,
+
+    #[error("Missing control value")]
+    MissingControlValue,
+    #[error("Unexpected control value")]
+    UnexpectedControlValue,
+    #[error("Invalid control encoding")]
+    InvalidControl,
+    #[error("Missing extended operation value")]
+    MissingExtendedValue,
+    #[error("Invalid UUID length")]
+    InvalidUUID,
 
     #[error("Unknown error")]
     Unknown,
 
+    #[error("{error} (at offset {offset})")]
+    AtOffset {
+        offset: usize,
+        error: Box<LdapError>,
+    },
+
+    #[error("{source} (in {tag} operation)")]
+    OperationParse {
+        tag: ProtocolOpTag,
+        source: Box<LdapError>,
+    },
+
     #[error("BER error: {0}")]
     Ber(#[from] Error),
     #[error("nom error: {0:?}")]
     NomError(ErrorKind),
 }
 
+impl LdapError {
+    /// Record that the error occurred at `offset` bytes from the start of the input
+    ///
+    /// If the error already has an offset (relative to an inner object), `offset` is added to it.
+    pub fn at_offset(self, offset: usize) -> LdapError {
+        match self {
+            LdapError::AtOffset { offset: o, error } => LdapError::AtOffset {
+                offset: offset + o,
+                error,
+            },
+            e => LdapError::AtOffset {
+                offset,
+                error: Box::new(e),
+            },
+        }
+    }
+
+    /// Get the offset where the error occurred, if known
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            LdapError::AtOffset { offset, .. } => Some(*offset),
+            _ => None,
+        }
+    }
+
+    /// Get the tag of the operation being parsed when the error occurred, if known
+    pub fn operation_tag(&self) -> Option<ProtocolOpTag> {
+        match self {
+            LdapError::AtOffset { error, .. } => error.operation_tag(),
+            LdapError::OperationParse { tag, .. } => Some(*tag),
+            _ => None,
+        }
+    }
+
+    /// Get the underlying error, without offset
+    pub fn into_inner(self) -> LdapError {
+        match self {
+            LdapError::AtOffset { error, .. } => *error,
+            e => e,
+        }
+    }
+}
+
 impl From<LdapError> for nom::Err<LdapError> {
     fn from(e: LdapError) -> nom::Err<LdapError> {
         nom::Err::Error(e)
     }
 }
 
+impl From<nom::Err<LdapError>> for LdapError {
+    fn from(e: nom::Err<LdapError>) -> LdapError {
+        match e {
+            nom::Err::Error(e) | nom::Err::Failure(e) => e,
+            nom::Err::Incomplete(n) => LdapError::Ber(Error::Incomplete(n)),
+        }
+    }
+}
+
 impl From<ErrorKind> for LdapError {
     fn from(e: ErrorKind) -> LdapError {
         LdapError::NomError(e)
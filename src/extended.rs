@@ -0,0 +1,829 @@
+//! Definitions and parsers for well-known LDAP extended operations
+//!
+//! Extended requests and responses are parsed as opaque values in [`ExtendedRequest`] and
+//! [`ExtendedResponse`] (and [`IntermediateResponse`]). The helpers in this module recognize
+//! well-known operations by their OID, and decode their values.
+
+use crate::error::*;
+use crate::ldap::*;
+use crate::oids;
+use crate::parser::*;
+use asn1_rs::nom;
+use asn1_rs::{Any, Class, FromBer, OptTaggedParser, ParseResult, Sequence, Set, Tag};
+use nom::combinator::{complete, opt};
+use nom::Err;
+use std::borrow::Cow;
+use std::convert::TryFrom;
+
+/// Password Modify extended operation ([RFC3062])
+///
+/// [RFC3062]: https://tools.ietf.org/html/rfc3062
+pub const OID_PASSWORD_MODIFY: &str = oids::PASSWORD_MODIFY;
+/// Cancel extended operation ([RFC3909])
+///
+/// [RFC3909]: https://tools.ietf.org/html/rfc3909
+pub const OID_CANCEL: &str = oids::CANCEL;
+/// StartTLS extended operation ([RFC4511] section 4.14)
+///
+/// [RFC4511]: https://tools.ietf.org/html/rfc4511
+pub const OID_START_TLS: &str = oids::START_TLS;
+/// "Who am I?" extended operation ([RFC4532])
+///
+/// [RFC4532]: https://tools.ietf.org/html/rfc4532
+pub const OID_WHOAMI: &str = oids::WHOAMI;
+
+/// Sync Info message of the Content Synchronization operation ([RFC4533])
+///
+/// [RFC4533]: https://tools.ietf.org/html/rfc4533
+pub const OID_SYNC_INFO: &str = oids::SYNC_INFO;
+
+/// Notice of Disconnection unsolicited notification ([RFC4511] section 4.4.1)
+///
+/// [RFC4511]: https://tools.ietf.org/html/rfc4511
+pub const OID_NOTICE_OF_DISCONNECTION: &str = oids::NOTICE_OF_DISCONNECTION;
+
+/// Start Transaction extended operation ([RFC5805])
+///
+/// [RFC5805]: https://tools.ietf.org/html/rfc5805
+pub const OID_START_TRANSACTION: &str = oids::START_TRANSACTION;
+/// End Transaction extended operation ([RFC5805])
+///
+/// [RFC5805]: https://tools.ietf.org/html/rfc5805
+pub const OID_END_TRANSACTION: &str = oids::END_TRANSACTION;
+
+/// Decode an extended operation value using parser `f`
+///
+/// The value must be decoded entirely: trailing bytes are rejected with
+/// [`LdapError::TrailingData`].
+fn parse_extended_value<'a, T, F>(data: &'a [u8], f: F) -> std::result::Result<T, LdapError>
+where
+    F: FnOnce(&'a [u8]) -> Result<'a, T>,
+{
+    match f(data) {
+        Ok((rem, _)) if !rem.is_empty() => Err(LdapError::TrailingData),
+        Ok((_, v)) => Ok(v),
+        Err(e) => Err(LdapError::from(e)),
+    }
+}
+
+/// Password Modify request value ([RFC3062])
+///
+/// [RFC3062]: https://tools.ietf.org/html/rfc3062
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PasswdModifyRequestValue<'a> {
+    pub user_identity: Option<LdapString<'a>>,
+    pub old_passwd: Option<Cow<'a, [u8]>>,
+    pub new_passwd: Option<Cow<'a, [u8]>>,
+}
+
+/// Cancel request value ([RFC3909])
+///
+/// [RFC3909]: https://tools.ietf.org/html/rfc3909
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CancelRequestValue {
+    /// Message ID of the operation to cancel
+    pub cancel_id: MessageID,
+}
+
+/// Password Modify response value ([RFC3062])
+///
+/// [RFC3062]: https://tools.ietf.org/html/rfc3062
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PasswdModifyResponseValue<'a> {
+    pub gen_passwd: Option<Cow<'a, [u8]>>,
+}
+
+/// Sync Info message value ([RFC4533])
+///
+/// [RFC4533]: https://tools.ietf.org/html/rfc4533
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SyncInfo<'a> {
+    NewCookie(SyncCookie<'a>),
+    RefreshDelete {
+        cookie: Option<SyncCookie<'a>>,
+        refresh_done: bool,
+    },
+    RefreshPresent {
+        cookie: Option<SyncCookie<'a>>,
+        refresh_done: bool,
+    },
+    SyncIdSet {
+        cookie: Option<SyncCookie<'a>>,
+        refresh_deletes: bool,
+        sync_uuids: Vec<[u8; 16]>,
+    },
+}
+
+/// Notice of Disconnection ([RFC4511] section 4.4.1)
+///
+/// [RFC4511]: https://tools.ietf.org/html/rfc4511
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NoticeOfDisconnection<'a> {
+    /// Reason of the disconnection (for ex. `protocolError` or `strongerAuthRequired`)
+    pub reason: ResultCode,
+    /// Diagnostic message of the notification
+    pub message: LdapString<'a>,
+}
+
+/// End Transaction request value ([RFC5805])
+///
+/// [RFC5805]: https://tools.ietf.org/html/rfc5805
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EndTransactionRequestValue<'a> {
+    /// `true` to commit the transaction, `false` to abort it
+    pub commit: bool,
+    /// Transaction identifier
+    pub identifier: Cow<'a, [u8]>,
+}
+
+/// Controls of an update, in an End Transaction response ([RFC5805])
+///
+/// [RFC5805]: https://tools.ietf.org/html/rfc5805
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UpdateControls<'a> {
+    /// Message ID of the update request
+    pub message_id: MessageID,
+    pub controls: Vec<Control<'a>>,
+}
+
+/// End Transaction response value ([RFC5805])
+///
+/// [RFC5805]: https://tools.ietf.org/html/rfc5805
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct EndTransactionResponseValue<'a> {
+    /// Message ID of the update request that caused the transaction to fail
+    pub message_id: Option<MessageID>,
+    /// Response controls of the updates of the transaction
+    pub updates_controls: Option<Vec<UpdateControls<'a>>>,
+}
+
+impl ExtendedRequest<'_> {
+    /// Decode the value of a Password Modify extended request
+    ///
+    /// Returns `None` if the request name is not [`OID_PASSWORD_MODIFY`]. Since all fields are
+    /// optional, an absent request value is decoded as an empty `PasswdModifyRequestValue`.
+    pub fn as_password_modify(
+        &self,
+    ) -> Option<std::result::Result<PasswdModifyRequestValue<'_>, LdapError>> {
+        if self.request_name.0 != OID_PASSWORD_MODIFY {
+            return None;
+        }
+        let res = match &self.request_value {
+            Some(data) => parse_extended_value(data, PasswdModifyRequestValue::from_ber),
+            None => Ok(PasswdModifyRequestValue::default()),
+        };
+        Some(res)
+    }
+
+    /// Test if this is a "Who am I?" extended request ([RFC4532])
+    ///
+    /// The request has no value, so this only checks the request name.
+    ///
+    /// [RFC4532]: https://tools.ietf.org/html/rfc4532
+    pub fn is_whoami(&self) -> bool {
+        self.request_name.0 == OID_WHOAMI
+    }
+
+    /// Decode the value of a Cancel extended request ([RFC3909])
+    ///
+    /// Returns `None` if the request name is not [`OID_CANCEL`]. The request value is required.
+    ///
+    /// [RFC3909]: https://tools.ietf.org/html/rfc3909
+    pub fn as_cancel(&self) -> Option<std::result::Result<CancelRequestValue, LdapError>> {
+        if self.request_name.0 != OID_CANCEL {
+            return None;
+        }
+        let res = match &self.request_value {
+            Some(data) => parse_extended_value(data, CancelRequestValue::from_ber),
+            None => Err(LdapError::MissingExtendedValue),
+        };
+        Some(res)
+    }
+
+    /// Test if this is a StartTLS extended request ([RFC4511] section 4.14)
+    ///
+    /// [RFC4511]: https://tools.ietf.org/html/rfc4511
+    pub fn is_start_tls(&self) -> bool {
+        self.request_name.0 == OID_START_TLS
+    }
+
+    /// Test if this is a Start Transaction extended request ([RFC5805])
+    ///
+    /// The request has no value, so this only checks the request name.
+    ///
+    /// [RFC5805]: https://tools.ietf.org/html/rfc5805
+    pub fn is_start_transaction(&self) -> bool {
+        self.request_name.0 == OID_START_TRANSACTION
+    }
+
+    /// Decode the value of an End Transaction extended request ([RFC5805])
+    ///
+    /// Returns `None` if the request name is not [`OID_END_TRANSACTION`]. The request value is
+    /// required.
+    ///
+    /// [RFC5805]: https://tools.ietf.org/html/rfc5805
+    pub fn as_end_transaction(
+        &self,
+    ) -> Option<std::result::Result<EndTransactionRequestValue<'_>, LdapError>> {
+        if self.request_name.0 != OID_END_TRANSACTION {
+            return None;
+        }
+        let res = match &self.request_value {
+            Some(data) => parse_extended_value(data, EndTransactionRequestValue::from_ber),
+            None => Err(LdapError::MissingExtendedValue),
+        };
+        Some(res)
+    }
+}
+
+impl ExtendedResponse<'_> {
+    /// Test if this is a StartTLS extended response ([RFC4511] section 4.14)
+    ///
+    /// The `responseName` of a StartTLS response must be [`OID_START_TLS`]. The result code
+    /// must still be checked before starting the TLS handshake.
+    ///
+    /// [RFC4511]: https://tools.ietf.org/html/rfc4511
+    pub fn is_start_tls_response(&self) -> bool {
+        matches!(&self.response_name, Some(name) if name.0 == OID_START_TLS)
+    }
+
+    /// Decode the value of a Password Modify extended response
+    ///
+    /// The response to a Password Modify request has no `responseName`, so the caller must
+    /// match the response with the request (using the message ID).
+    ///
+    /// Returns `None` if the response has no value, or if the response name is present and is
+    /// not [`OID_PASSWORD_MODIFY`].
+    pub fn as_password_modify(
+        &self,
+    ) -> Option<std::result::Result<PasswdModifyResponseValue<'_>, LdapError>> {
+        match &self.response_name {
+            Some(name) if name.0 != OID_PASSWORD_MODIFY => return None,
+            _ => (),
+        }
+        let data = self.response_value.as_ref()?;
+        Some(parse_extended_value(
+            data,
+            PasswdModifyResponseValue::from_ber,
+        ))
+    }
+
+    /// Get the authorization identity (`authzId`) of a "Who am I?" extended response
+    /// ([RFC4532])
+    ///
+    /// The response to a "Who am I?" request has no `responseName`, so the caller must match the
+    /// response with the request (using the message ID). The value is returned as-is, for ex.
+    /// `dn:cn=admin,dc=example,dc=com` or `u:bob`. An empty value denotes the anonymous identity.
+    ///
+    /// Returns `None` if the response has no value, or if the response name is present and is
+    /// not [`OID_WHOAMI`].
+    ///
+    /// [RFC4532]: https://tools.ietf.org/html/rfc4532
+    pub fn as_whoami(&self) -> Option<std::result::Result<LdapString<'_>, LdapError>> {
+        match &self.response_name {
+            Some(name) if name.0 != OID_WHOAMI => return None,
+            _ => (),
+        }
+        let data = self.response_value.as_ref()?;
+        let res = parse_utf8(data, "authzId")
+            .map(|s| LdapString(Cow::Borrowed(s)))
+            .map_err(LdapError::from);
+        Some(res)
+    }
+
+    /// Get the reason and message of a Notice of Disconnection ([RFC4511] section 4.4.1)
+    ///
+    /// This unsolicited notification is sent by the server before closing the connection, with
+    /// a message ID of 0 (the caller must check the message ID).
+    ///
+    /// Returns `None` if the response name is not [`OID_NOTICE_OF_DISCONNECTION`].
+    ///
+    /// [RFC4511]: https://tools.ietf.org/html/rfc4511
+    pub fn as_notice_of_disconnection(&self) -> Option<NoticeOfDisconnection<'_>> {
+        match &self.response_name {
+            Some(name) if name.0 == OID_NOTICE_OF_DISCONNECTION => (),
+            _ => return None,
+        }
+        let notice = NoticeOfDisconnection {
+            reason: self.result.result_code,
+            message: LdapString(Cow::Borrowed(&self.result.diagnostic_message.0)),
+        };
+        Some(notice)
+    }
+
+    /// Get the transaction identifier of a Start Transaction extended response ([RFC5805])
+    ///
+    /// The response to a Start Transaction request has no `responseName`, so the caller must
+    /// match the response with the request (using the message ID).
+    ///
+    /// Returns `None` if the response has no value, or if the response name is present and is
+    /// not [`OID_START_TRANSACTION`].
+    ///
+    /// [RFC5805]: https://tools.ietf.org/html/rfc5805
+    pub fn as_start_transaction(&self) -> Option<&[u8]> {
+        match &self.response_name {
+            Some(name) if name.0 != OID_START_TRANSACTION => None,
+            _ => self.response_value.as_deref(),
+        }
+    }
+
+    /// Decode the value of an End Transaction extended response ([RFC5805])
+    ///
+    /// The response to an End Transaction request has no `responseName`, so the caller must
+    /// match the response with the request (using the message ID).
+    ///
+    /// Returns `None` if the response has no value, or if the response name is present and is
+    /// not [`OID_END_TRANSACTION`].
+    ///
+    /// [RFC5805]: https://tools.ietf.org/html/rfc5805
+    pub fn as_end_transaction(
+        &self,
+    ) -> Option<std::result::Result<EndTransactionResponseValue<'_>, LdapError>> {
+        match &self.response_name {
+            Some(name) if name.0 != OID_END_TRANSACTION => return None,
+            _ => (),
+        }
+        let data = self.response_value.as_ref()?;
+        Some(parse_extended_value(
+            data,
+            EndTransactionResponseValue::from_ber,
+        ))
+    }
+}
+
+impl IntermediateResponse<'_> {
+    /// Decode the value of a Sync Info message ([RFC4533])
+    ///
+    /// Returns `None` if the response name is not [`OID_SYNC_INFO`].
+    ///
+    /// [RFC4533]: https://tools.ietf.org/html/rfc4533
+    pub fn as_sync_info(&self) -> Option<std::result::Result<SyncInfo<'_>, LdapError>> {
+        match &self.response_name {
+            Some(name) if name.0 == OID_SYNC_INFO => (),
+            _ => return None,
+        }
+        let res = match &self.response_value {
+            Some(data) => parse_extended_value(data, SyncInfo::from_ber),
+            None => Err(LdapError::MissingExtendedValue),
+        };
+        Some(res)
+    }
+}
+
+// PasswdModifyRequestValue ::= SEQUENCE {
+//   userIdentity    [0]  OCTET STRING OPTIONAL
+//   oldPasswd       [1]  OCTET STRING OPTIONAL
+//   newPasswd       [2]  OCTET STRING OPTIONAL }
+impl<'a> FromBer<'a, LdapError> for PasswdModifyRequestValue<'a> {
+    fn from_ber(bytes: &'a [u8]) -> ParseResult<'a, Self, LdapError> {
+        Sequence::from_ber_and_then(bytes, |i| {
+            let (i, user_identity) = OptTaggedParser::new(Class::ContextSpecific, Tag(0))
+                .parse_ber(i, |_, content| {
+                    let s = parse_utf8(content, "userIdentity")?;
+                    Ok((&b""[..], LdapString(Cow::Borrowed(s))))
+                })?;
+            let (i, old_passwd) = OptTaggedParser::new(Class::ContextSpecific, Tag(1))
+                .parse_ber(i, |_, data| Ok((&b""[..], Cow::Borrowed(data))))?;
+            let (i, new_passwd) = OptTaggedParser::new(Class::ContextSpecific, Tag(2))
+                .parse_ber(i, |_, data| Ok((&b""[..], Cow::Borrowed(data))))?;
+            let value = PasswdModifyRequestValue {
+                user_identity,
+                old_passwd,
+                new_passwd,
+            };
+            Ok((i, value))
+        })
+    }
+}
+
+// cancelRequestValue ::= SEQUENCE {
+//     cancelID        MessageID
+//                     -- MessageID is as defined in [RFC2251]
+// }
+impl<'a> FromBer<'a, LdapError> for CancelRequestValue {
+    fn from_ber(bytes: &'a [u8]) -> ParseResult<'a, Self, LdapError> {
+        Sequence::from_ber_and_then(bytes, |i| {
+            let (i, cancel_id) = MessageID::from_ber(i)?;
+            Ok((i, CancelRequestValue { cancel_id }))
+        })
+    }
+}
+
+// PasswdModifyResponseValue ::= SEQUENCE {
+//   genPasswd       [0]     OCTET STRING OPTIONAL }
+impl<'a> FromBer<'a, LdapError> for PasswdModifyResponseValue<'a> {
+    fn from_ber(bytes: &'a [u8]) -> ParseResult<'a, Self, LdapError> {
+        Sequence::from_ber_and_then(bytes, |i| {
+            let (i, gen_passwd) = OptTaggedParser::new(Class::ContextSpecific, Tag(0))
+                .parse_ber(i, |_, data| Ok((&b""[..], Cow::Borrowed(data))))?;
+            Ok((i, PasswdModifyResponseValue { gen_passwd }))
+        })
+    }
+}
+
+// syncUUID ::= OCTET STRING (SIZE(16))
+pub(crate) fn parse_sync_uuid(i: &[u8]) -> Result<'_, [u8; 16]> {
+    let (i, b) = parse_ldap_octet_string_as_slice(i)?;
+    let uuid = <[u8; 16]>::try_from(b).or(Err(Err::Error(LdapError::InvalidUUID)))?;
+    Ok((i, uuid))
+}
+
+// syncCookie ::= OCTET STRING
+#[inline]
+pub(crate) fn parse_opt_sync_cookie(i: &[u8]) -> Result<'_, Option<SyncCookie<'_>>> {
+    let (i, cookie) = opt(complete(parse_ldap_octet_string_as_slice))(i)?;
+    Ok((i, cookie.map(|c| SyncCookie(Cow::Borrowed(c)))))
+}
+
+// txnEndReq ::= SEQUENCE {
+//      commit         BOOLEAN DEFAULT TRUE,
+//      identifier     OCTET STRING }
+impl<'a> FromBer<'a, LdapError> for EndTransactionRequestValue<'a> {
+    fn from_ber(bytes: &'a [u8]) -> ParseResult<'a, Self, LdapError> {
+        Sequence::from_ber_and_then(bytes, |i| {
+            let (i, commit) = parse_opt_bool(i, true)?;
+            let (i, identifier) = parse_ldap_octet_string_as_slice(i)?;
+            let value = EndTransactionRequestValue {
+                commit,
+                identifier: Cow::Borrowed(identifier),
+            };
+            Ok((i, value))
+        })
+    }
+}
+
+// updateControls SEQUENCE {
+//      messageID MessageID,
+//      controls  Controls }
+impl<'a> FromBer<'a, LdapError> for UpdateControls<'a> {
+    fn from_ber(bytes: &'a [u8]) -> ParseResult<'a, Self, LdapError> {
+        Sequence::from_ber_and_then(bytes, |i| {
+            let (i, message_id) = MessageID::from_ber(i)?;
+            let (i, controls) = parse_controls(i)?;
+            Ok((
+                i,
+                UpdateControls {
+                    message_id,
+                    controls,
+                },
+            ))
+        })
+    }
+}
+
+// txnEndRes ::= SEQUENCE {
+//      messageID MessageID OPTIONAL,
+//           -- msgid associated with non-success resultCode
+//      updatesControls SEQUENCE OF updateControls SEQUENCE {
+//           messageID MessageID,
+//                -- msgid associated with controls
+//           controls  Controls
+//      } OPTIONAL
+// }
+impl<'a> FromBer<'a, LdapError> for EndTransactionResponseValue<'a> {
+    fn from_ber(bytes: &'a [u8]) -> ParseResult<'a, Self, LdapError> {
+        Sequence::from_ber_and_then(bytes, |i| {
+            let (i, message_id) = opt(complete(MessageID::from_ber))(i)?;
+            let (i, updates_controls) = if i.is_empty() {
+                (i, None)
+            } else {
+                let (i, updates) = Sequence::from_ber_and_then(i, |mut i| {
+                    let mut updates = Vec::new();
+                    while !i.is_empty() {
+                        let (rem, update) = UpdateControls::from_ber(i)?;
+                        updates.push(update);
+                        i = rem;
+                    }
+                    Ok((i, updates))
+                })?;
+                (i, Some(updates))
+            };
+            let value = EndTransactionResponseValue {
+                message_id,
+                updates_controls,
+            };
+            Ok((i, value))
+        })
+    }
+}
+
+#[inline]
+pub(crate) fn parse_opt_bool(i: &[u8], default: bool) -> Result<'_, bool> {
+    let (i, b) = opt(complete(<bool>::from_ber))(i).map_err(Err::convert)?;
+    Ok((i, b.unwrap_or(default)))
+}
+
+// syncInfoValue ::= CHOICE {
+//     newcookie      [0] syncCookie,
+//     refreshDelete  [1] SEQUENCE {
+//         cookie         syncCookie OPTIONAL,
+//         refreshDone    BOOLEAN DEFAULT TRUE
+//     },
+//     refreshPresent [2] SEQUENCE {
+//         cookie         syncCookie OPTIONAL,
+//         refreshDone    BOOLEAN DEFAULT TRUE
+//     },
+//     syncIdSet      [3] SEQUENCE {
+//         cookie         syncCookie OPTIONAL,
+//         refreshDeletes BOOLEAN DEFAULT FALSE,
+//         syncUUIDs      SET OF syncUUID
+//     }
+// }
+impl<'a> FromBer<'a, LdapError> for SyncInfo<'a> {
+    fn from_ber(bytes: &'a [u8]) -> ParseResult<'a, Self, LdapError> {
+        let (rem, any) = Any::from_ber(bytes).map_err(Err::convert)?;
+        if any.class() != Class::ContextSpecific {
+            return Err(Err::Error(LdapError::InvalidMessageType));
+        }
+        let i = any.data;
+        let info = match any.tag().0 {
+            0 => SyncInfo::NewCookie(SyncCookie(Cow::Borrowed(i))),
+            1 => {
+                let (i, cookie) = parse_opt_sync_cookie(i)?;
+                let (_, refresh_done) = parse_opt_bool(i, true)?;
+                SyncInfo::RefreshDelete {
+                    cookie,
+                    refresh_done,
+                }
+            }
+            2 => {
+                let (i, cookie) = parse_opt_sync_cookie(i)?;
+                let (_, refresh_done) = parse_opt_bool(i, true)?;
+                SyncInfo::RefreshPresent {
+                    cookie,
+                    refresh_done,
+                }
+            }
+            3 => {
+                let (i, cookie) = parse_opt_sync_cookie(i)?;
+                let (i, refresh_deletes) = parse_opt_bool(i, false)?;
+                let (_, sync_uuids) = Set::from_ber_and_then(i, |mut i| {
+                    let mut uuids = Vec::new();
+                    while !i.is_empty() {
+                        let (rem, uuid) = parse_sync_uuid(i)?;
+                        uuids.push(uuid);
+                        i = rem;
+                    }
+                    Ok((i, uuids))
+                })?;
+                SyncInfo::SyncIdSet {
+                    cookie,
+                    refresh_deletes,
+                    sync_uuids,
+                }
+            }
+            _ => return Err(Err::Error(LdapError::InvalidMessageType)),
+        };
+        Ok((rem, info))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    fn extended_request<'a>(oid: &'a str, value: Option<&'a [u8]>) -> ExtendedRequest<'a> {
+        ExtendedRequest {
+            request_name: LdapOID(Cow::Borrowed(oid)),
+            request_value: value.map(Cow::Borrowed),
+        }
+    }
+
+    fn extended_response<'a>(
+        oid: Option<&'a str>,
+        value: Option<&'a [u8]>,
+    ) -> ExtendedResponse<'a> {
+        ExtendedResponse {
+            result: LdapResult {
+                result_code: ResultCode::Success,
+                matched_dn: LdapDN(Cow::Borrowed("")),
+                diagnostic_message: LdapString(Cow::Borrowed("")),
+                referral: None,
+            },
+            response_name: oid.map(|s| LdapOID(Cow::Borrowed(s))),
+            response_value: value.map(Cow::Borrowed),
+        }
+    }
+
+    #[test]
+    fn test_password_modify() {
+        const DATA: &[u8] = &hex!("30 0d 80 05 75 3a 62 6f 62 82 04 70 61 73 73");
+        let req = extended_request(OID_PASSWORD_MODIFY, Some(DATA));
+        let value = req
+            .as_password_modify()
+            .expect("request not recognized")
+            .expect("parsing failed");
+        assert_eq!(
+            value.user_identity.as_ref().map(|s| s.0.as_ref()),
+            Some("u:bob")
+        );
+        assert!(value.old_passwd.is_none());
+        assert_eq!(value.new_passwd.as_deref(), Some(&b"pass"[..]));
+        // absent value
+        let req = extended_request(OID_PASSWORD_MODIFY, None);
+        assert_eq!(
+            req.as_password_modify(),
+            Some(Ok(PasswdModifyRequestValue::default()))
+        );
+        // other operation
+        let req = extended_request("1.3.6.1.4.1.1466.20037", None);
+        assert!(req.as_password_modify().is_none());
+
+        const RESP: &[u8] = &hex!("30 06 80 04 67 65 6e 31");
+        let resp = extended_response(None, Some(RESP));
+        let value = resp
+            .as_password_modify()
+            .expect("response not recognized")
+            .expect("parsing failed");
+        assert_eq!(value.gen_passwd.as_deref(), Some(&b"gen1"[..]));
+        // trailing data
+        let resp = extended_response(None, Some(&hex!("30 06 80 04 67 65 6e 31 30 00")));
+        assert_eq!(
+            resp.as_password_modify(),
+            Some(Err(LdapError::TrailingData))
+        );
+    }
+
+    #[test]
+    fn test_cancel() {
+        let req = extended_request(OID_CANCEL, Some(&hex!("30 03 02 01 05")));
+        assert_eq!(
+            req.as_cancel(),
+            Some(Ok(CancelRequestValue {
+                cancel_id: MessageID(5)
+            }))
+        );
+        // message ID larger than maxInt
+        let req = extended_request(OID_CANCEL, Some(&hex!("30 07 02 05 00 80 00 00 00")));
+        assert_eq!(req.as_cancel(), Some(Err(LdapError::InvalidMessageId)));
+        // trailing data
+        let req = extended_request(OID_CANCEL, Some(&hex!("30 03 02 01 05 00")));
+        assert_eq!(req.as_cancel(), Some(Err(LdapError::TrailingData)));
+        // missing value
+        let req = extended_request(OID_CANCEL, None);
+        assert_eq!(req.as_cancel(), Some(Err(LdapError::MissingExtendedValue)));
+        // other operation
+        let req = extended_request(OID_WHOAMI, None);
+        assert!(req.as_cancel().is_none());
+    }
+
+    #[test]
+    fn test_sync_info() {
+        fn sync_info(value: &[u8]) -> std::result::Result<SyncInfo<'_>, LdapError> {
+            parse_extended_value(value, SyncInfo::from_ber)
+        }
+        let resp = IntermediateResponse {
+            response_name: Some(LdapOID(Cow::Borrowed(OID_SYNC_INFO))),
+            response_value: Some(Cow::Borrowed(&hex!("80 03 61 62 63"))),
+        };
+        assert_eq!(
+            resp.as_sync_info(),
+            Some(Ok(SyncInfo::NewCookie(SyncCookie(Cow::Borrowed(b"abc")))))
+        );
+        assert_eq!(
+            sync_info(&hex!("a1 08 04 03 61 62 63 01 01 00")),
+            Ok(SyncInfo::RefreshDelete {
+                cookie: Some(SyncCookie(Cow::Borrowed(b"abc"))),
+                refresh_done: false
+            })
+        );
+        assert_eq!(
+            sync_info(&hex!("a2 00")),
+            Ok(SyncInfo::RefreshPresent {
+                cookie: None,
+                refresh_done: true
+            })
+        );
+        let uuid = hex!("00 01 02 03 04 05 06 07 08 09 0a 0b 0c 0d 0e 0f");
+        assert_eq!(
+            sync_info(&hex!(
+                "a3 17 01 01 ff 31 12 04 10
+                 00 01 02 03 04 05 06 07 08 09 0a 0b 0c 0d 0e 0f"
+            )),
+            Ok(SyncInfo::SyncIdSet {
+                cookie: None,
+                refresh_deletes: true,
+                sync_uuids: vec![uuid]
+            })
+        );
+        // invalid UUID length
+        assert_eq!(
+            sync_info(&hex!("a3 07 31 05 04 03 01 02 03")),
+            Err(LdapError::InvalidUUID)
+        );
+        // other message
+        let resp = IntermediateResponse {
+            response_name: None,
+            response_value: Some(Cow::Borrowed(&hex!("80 00"))),
+        };
+        assert!(resp.as_sync_info().is_none());
+    }
+
+    #[test]
+    fn test_start_tls() {
+        const DATA: &[u8] = include_bytes!("../assets/extended-req.bin");
+        let (_, req) = ExtendedRequest::from_ber(DATA).expect("parsing failed");
+        assert!(req.is_start_tls());
+        assert!(!req.is_whoami());
+
+        let resp = extended_response(Some(OID_START_TLS), None);
+        assert!(resp.is_start_tls_response());
+        assert!(!extended_response(None, None).is_start_tls_response());
+    }
+
+    #[test]
+    fn test_whoami() {
+        let req = extended_request(OID_WHOAMI, None);
+        assert!(req.is_whoami());
+        assert!(!extended_request(OID_PASSWORD_MODIFY, None).is_whoami());
+
+        let resp = extended_response(None, Some(b"dn:cn=admin,dc=example,dc=com"));
+        let authz_id = resp
+            .as_whoami()
+            .expect("response not recognized")
+            .expect("parsing failed");
+        assert_eq!(&authz_id.0, "dn:cn=admin,dc=example,dc=com");
+        // empty and absent values
+        let resp = extended_response(None, Some(b""));
+        assert_eq!(resp.as_whoami(), Some(Ok(LdapString(Cow::Borrowed("")))));
+        let resp = extended_response(None, None);
+        assert!(resp.as_whoami().is_none());
+    }
+
+    #[test]
+    fn test_notice_of_disconnection() {
+        // messageID 0, protocolError, message "bye"
+        const DATA: &[u8] = &hex!(
+            "30 27 02 01 00 78 22 0a 01 02 04 00 04 03 62 79 65
+             8a 16 31 2e 33 2e 36 2e 31 2e 34 2e 31 2e 31 34 36 36 2e 32 30 30 33 36"
+        );
+        let (_, msg) = LdapMessage::from_ber(DATA).expect("parsing failed");
+        assert_eq!(msg.message_id, MessageID(0));
+        let resp = match msg.protocol_op {
+            ProtocolOp::ExtendedResponse(resp) => resp,
+            _ => panic!("unexpected operation"),
+        };
+        let notice = resp
+            .as_notice_of_disconnection()
+            .expect("response not recognized");
+        assert_eq!(notice.reason, ResultCode::ProtocolError);
+        assert_eq!(notice.message.0, "bye");
+        assert!(extended_response(None, None)
+            .as_notice_of_disconnection()
+            .is_none());
+    }
+
+    #[test]
+    fn test_transactions() {
+        let req = extended_request(OID_START_TRANSACTION, None);
+        assert!(req.is_start_transaction());
+        let resp = extended_response(None, Some(b"txn-1"));
+        assert_eq!(resp.as_start_transaction(), Some(&b"txn-1"[..]));
+        assert!(extended_response(Some(OID_WHOAMI), Some(b"txn-1"))
+            .as_start_transaction()
+            .is_none());
+
+        // abort transaction "txn-1"
+        let req = extended_request(
+            OID_END_TRANSACTION,
+            Some(&hex!("30 0a 01 01 00 04 05 74 78 6e 2d 31")),
+        );
+        let value = req
+            .as_end_transaction()
+            .expect("request not recognized")
+            .expect("parsing failed");
+        assert!(!value.commit);
+        assert_eq!(value.identifier.as_ref(), b"txn-1");
+        let req = extended_request(OID_END_TRANSACTION, Some(&hex!("30 03 04 01 aa")));
+        let value = req
+            .as_end_transaction()
+            .expect("request not recognized")
+            .expect("parsing failed");
+        assert!(value.commit);
+        assert_eq!(
+            extended_request(OID_END_TRANSACTION, None).as_end_transaction(),
+            Some(Err(LdapError::MissingExtendedValue))
+        );
+
+        // failed update 5, and empty controls for update 6
+        let resp = extended_response(
+            None,
+            Some(&hex!("30 0c 02 01 05 30 07 30 05 02 01 06 30 00")),
+        );
+        let value = resp
+            .as_end_transaction()
+            .expect("response not recognized")
+            .expect("parsing failed");
+        assert_eq!(value.message_id, Some(MessageID(5)));
+        let updates = value.updates_controls.expect("no update controls");
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].message_id, MessageID(6));
+        assert!(updates[0].controls.is_empty());
+        let resp = extended_response(None, Some(&hex!("30 00")));
+        assert_eq!(
+            resp.as_end_transaction(),
+            Some(Ok(EndTransactionResponseValue::default()))
+        );
+    }
+}
@@ -1,69 +1,405 @@
 //! Definition for types used in LDAP filters
 
+pub use crate::filter_string::{escape_filter_value, unescape_filter_value};
 use crate::ldap::LdapString;
 use std::borrow::Cow;
 
+/// Default maximum nesting depth of filters (using `and`, `or` and `not`) when parsing
+pub const MAX_FILTER_DEPTH: usize = 50;
+
+/// Search filter ([RFC4511] section 4.5.1.7)
+///
+/// When parsing a filter, the input must start at the context-specific tag of the filter
+/// (`[0]` for `and` to `[9]` for `extensibleMatch`), which is the encoding of the `filter` field
+/// of a search request. There is no enclosing SEQUENCE or APPLICATION tag, so the bytes of the
+/// `filter` field can be parsed in isolation. Data after the filter is returned as the remaining
+/// input.
+///
+/// [RFC4511]: https://tools.ietf.org/html/rfc4511
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Filter<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     And(Vec<Filter<'a>>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     Or(Vec<Filter<'a>>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     Not(Box<Filter<'a>>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     EqualityMatch(AttributeValueAssertion<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     Substrings(SubstringFilter<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     GreaterOrEqual(AttributeValueAssertion<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     LessOrEqual(AttributeValueAssertion<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     Present(LdapString<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     ApproxMatch(AttributeValueAssertion<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     ExtensibleMatch(MatchingRuleAssertion<'a>),
+    /// A filter with an unknown tag
+    ///
+    /// The Filter CHOICE is extensible, so filters with an unknown (context-specific) tag are not
+    /// an error. The raw content of the element is stored in `data`.
+    Unknown { tag: u32, data: Cow<'a, [u8]> },
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PartialAttribute<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub attr_type: LdapString<'a>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub attr_vals: Vec<AttributeValue<'a>>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Attribute<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub attr_type: LdapString<'a>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub attr_vals: Vec<AttributeValue<'a>>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AttributeValueAssertion<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub attribute_desc: LdapString<'a>,
-    pub assertion_value: &'a [u8],
+    pub assertion_value: Cow<'a, [u8]>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct AttributeDescription<'a>(pub Cow<'a, str>);
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AttributeDescription<'a>(#[cfg_attr(feature = "serde", serde(borrow))] pub Cow<'a, str>);
 
+/// A matching rule assertion, used in extensible match filters
+///
+/// At least one of `matching_rule` and `rule_type` must be present ([RFC4511] section 4.5.1.7.7).
+/// The parser rejects assertions where both are absent.
+///
+/// [RFC4511]: https://tools.ietf.org/html/rfc4511
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MatchingRuleAssertion<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub matching_rule: Option<LdapString<'a>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub rule_type: Option<AttributeDescription<'a>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub assertion_value: AssertionValue<'a>,
+    /// `dnAttributes` flag, `None` if absent (see [`Self::dn_attributes_value`])
     pub dn_attributes: Option<bool>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct MatchingRuleId<'a>(pub Cow<'a, str>);
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MatchingRuleId<'a>(#[cfg_attr(feature = "serde", serde(borrow))] pub Cow<'a, str>);
 
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SubstringFilter<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub filter_type: LdapString<'a>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub substrings: Vec<Substring<'a>>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Substring<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     Initial(AssertionValue<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     Any(AssertionValue<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     Final(AssertionValue<'a>),
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AssertionValue<'a>(pub Cow<'a, [u8]>);
 
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AttributeValue<'a>(pub Cow<'a, [u8]>);
+
+/// Kind of match of an [`AttributeValueAssertion`] in a filter
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FilterMatchKind {
+    Equality,
+    GreaterOrEqual,
+    LessOrEqual,
+    Approx,
+}
+
+impl<'a> Filter<'a> {
+    /// Collect all attribute value assertions of the filter, ignoring its boolean structure
+    ///
+    /// Assertions are returned in the order they appear in the filter. Assertions under a `not`
+    /// filter are included. Presence, substrings and extensible match filters are not
+    /// attribute value assertions, and are ignored.
+    ///
+    /// ```rust
+    /// use ldap_parser::filter::FilterMatchKind;
+    /// use ldap_parser::parse_filter_str;
+    ///
+    /// let filter = parse_filter_str("(|(objectClass=person)(!(uidNumber>=1000)))").expect("invalid filter");
+    /// let assertions = filter.assertions();
+    /// assert_eq!(assertions.len(), 2);
+    /// assert_eq!(assertions[1].0, FilterMatchKind::GreaterOrEqual);
+    /// assert_eq!(assertions[1].1.attribute_desc.0, "uidNumber");
+    /// ```
+    pub fn assertions(&self) -> Vec<(FilterMatchKind, &AttributeValueAssertion<'a>)> {
+        let mut v = Vec::new();
+        self.collect_assertions(&mut v);
+        v
+    }
+
+    fn collect_assertions<'f>(
+        &'f self,
+        v: &mut Vec<(FilterMatchKind, &'f AttributeValueAssertion<'a>)>,
+    ) {
+        match self {
+            Filter::And(filters) | Filter::Or(filters) => {
+                filters.iter().for_each(|f| f.collect_assertions(v))
+            }
+            Filter::Not(f) => f.collect_assertions(v),
+            Filter::EqualityMatch(ava) => v.push((FilterMatchKind::Equality, ava)),
+            Filter::GreaterOrEqual(ava) => v.push((FilterMatchKind::GreaterOrEqual, ava)),
+            Filter::LessOrEqual(ava) => v.push((FilterMatchKind::LessOrEqual, ava)),
+            Filter::ApproxMatch(ava) => v.push((FilterMatchKind::Approx, ava)),
+            Filter::Substrings(_)
+            | Filter::Present(_)
+            | Filter::ExtensibleMatch(_)
+            | Filter::Unknown { .. } => (),
+        }
+    }
+
+    /// Test if an entry matches the filter
+    ///
+    /// The entry is a list of attributes, with their values. Attribute names are compared
+    /// case-insensitively, and values are compared as bytes (matching rules defined in the schema
+    /// are not applied). `approxMatch` is evaluated as an equality match. `extensibleMatch` and
+    /// unknown filters are not supported (they never match).
+    ///
+    /// ```rust
+    /// use ldap_parser::parse_filter_str;
+    ///
+    /// let filter = parse_filter_str("(&(objectClass=person)(cn=J*))").expect("invalid filter");
+    /// let entry: &[(&str, &[&[u8]])] = &[
+    ///     ("objectClass", &[b"top", b"person"]),
+    ///     ("cn", &[b"John Smith"]),
+    /// ];
+    /// assert!(filter.matches(entry));
+    /// ```
+    pub fn matches(&self, entry: &[(&str, &[&[u8]])]) -> bool {
+        let values = |attr| entry_values(entry, attr);
+        match self {
+            Filter::And(filters) => filters.iter().all(|f| f.matches(entry)),
+            Filter::Or(filters) => filters.iter().any(|f| f.matches(entry)),
+            Filter::Not(f) => !f.matches(entry),
+            Filter::EqualityMatch(ava) | Filter::ApproxMatch(ava) => {
+                values(&ava.attribute_desc.0).any(|v| v == &*ava.assertion_value)
+            }
+            Filter::Substrings(f) => {
+                values(&f.filter_type.0).any(|v| substrings_match(&f.substrings, v))
+            }
+            Filter::GreaterOrEqual(ava) => {
+                values(&ava.attribute_desc.0).any(|v| v >= &*ava.assertion_value)
+            }
+            Filter::LessOrEqual(ava) => {
+                values(&ava.attribute_desc.0).any(|v| v <= &*ava.assertion_value)
+            }
+            Filter::Present(attr) => values(&attr.0).next().is_some(),
+            Filter::ExtensibleMatch(_) | Filter::Unknown { .. } => false,
+        }
+    }
+}
+
+/// Iterate over the values of attribute `attr` in `entry`
+fn entry_values<'e>(
+    entry: &'e [(&str, &'e [&'e [u8]])],
+    attr: &'e str,
+) -> impl Iterator<Item = &'e [u8]> {
+    entry
+        .iter()
+        .filter(move |(name, _)| name.eq_ignore_ascii_case(attr))
+        .flat_map(|(_, values)| values.iter().copied())
+}
+
+fn substrings_match(substrings: &[Substring], mut value: &[u8]) -> bool {
+    for substring in substrings {
+        match substring {
+            Substring::Initial(s) => match value.strip_prefix(&*s.0) {
+                Some(rem) => value = rem,
+                None => return false,
+            },
+            // an empty substring always matches (and `windows` panics on a zero size)
+            Substring::Any(s) if s.0.is_empty() => (),
+            Substring::Any(s) => {
+                let pos = value.windows(s.0.len()).position(|w| w == &*s.0);
+                match pos {
+                    Some(pos) => value = &value[pos + s.0.len()..],
+                    None => return false,
+                }
+            }
+            Substring::Final(s) => match value.strip_suffix(&*s.0) {
+                Some(rem) => value = rem,
+                None => return false,
+            },
+        }
+    }
+    true
+}
+
+impl AssertionValue<'_> {
+    /// Get the value as a string slice, if it is valid UTF-8
+    pub fn as_str(&self) -> Option<&str> {
+        std::str::from_utf8(&self.0).ok()
+    }
+
+    /// Get the raw bytes of the value
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// Split an attribute description into its attribute type and options
+fn split_attribute_options(s: &str) -> (&str, Vec<&str>) {
+    let mut parts = s.split(';');
+    // split always returns at least one element
+    let base = parts.next().unwrap_or_default();
+    (base, parts.collect())
+}
+
+impl AttributeDescription<'_> {
+    /// Split the attribute description into the attribute type and its options
+    ///
+    /// For ex. `cn;lang-en;binary` returns `("cn", vec!["lang-en", "binary"])`.
+    pub fn base_and_options(&self) -> (&str, Vec<&str>) {
+        split_attribute_options(&self.0)
+    }
+}
+
+impl PartialAttribute<'_> {
+    /// Split the attribute type into the base attribute type and its options
+    ///
+    /// See [`AttributeDescription::base_and_options`].
+    pub fn base_and_options(&self) -> (&str, Vec<&str>) {
+        split_attribute_options(&self.attr_type.0)
+    }
+}
+
+impl Attribute<'_> {
+    /// Split the attribute type into the base attribute type and its options
+    ///
+    /// See [`AttributeDescription::base_and_options`].
+    pub fn base_and_options(&self) -> (&str, Vec<&str>) {
+        split_attribute_options(&self.attr_type.0)
+    }
+}
+
+impl MatchingRuleAssertion<'_> {
+    /// Get the value of the `dnAttributes` flag, using the default (`false`) if absent
+    pub fn dn_attributes_value(&self) -> bool {
+        self.dn_attributes.unwrap_or(false)
+    }
+}
+
+impl AttributeValue<'_> {
+    /// Get the value as a string slice, if it is valid UTF-8
+    ///
+    /// Binary values (for ex. `jpegPhoto` or `objectGUID`) return `None`.
+    pub fn as_str(&self) -> Option<&str> {
+        std::str::from_utf8(&self.0).ok()
+    }
+
+    /// Get the raw bytes of the value
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_value_as_str() {
+        let v = AttributeValue(Cow::Borrowed(b"John Smith"));
+        assert_eq!(v.as_str(), Some("John Smith"));
+        assert_eq!(v.as_bytes(), b"John Smith");
+        let v = AssertionValue(Cow::Borrowed(&[0xff, 0xfe]));
+        assert_eq!(v.as_str(), None);
+        assert_eq!(v.as_bytes(), &[0xff, 0xfe]);
+    }
+
+    #[test]
+    fn test_attribute_options() {
+        let desc = AttributeDescription(Cow::Borrowed("cn;lang-en;binary"));
+        assert_eq!(desc.base_and_options(), ("cn", vec!["lang-en", "binary"]));
+        let attr = PartialAttribute {
+            attr_type: LdapString(Cow::Borrowed("userCertificate")),
+            attr_vals: Vec::new(),
+        };
+        assert_eq!(attr.base_and_options(), ("userCertificate", vec![]));
+    }
+
+    #[test]
+    fn test_filter_matches() {
+        use crate::parse_filter_str;
+        use asn1_rs::FromBer;
+        use hex_literal::hex;
+
+        let entry: &[(&str, &[&[u8]])] = &[
+            ("objectClass", &[b"top", b"person"]),
+            ("CN", &[b"John Smith", b"Johnny"]),
+            ("uidNumber", &[b"1000"]),
+        ];
+        let matches = |s: &str| parse_filter_str(s).expect("invalid filter").matches(entry);
+        assert!(matches("(objectClass=person)"));
+        assert!(!matches("(objectClass=Person)"));
+        assert!(matches("(cn=*)"));
+        assert!(!matches("(mail=*)"));
+        assert!(matches("(cn=J*n S*h)"));
+        assert!(matches("(cn=*ohnn*)"));
+        assert!(!matches("(cn=*Smith*John)"));
+        assert!(!matches("(cn=Jo*hn*n)"));
+        assert!(matches("(uidNumber>=1000)"));
+        assert!(!matches("(uidNumber<=0999)"));
+        assert!(matches("(&(objectClass=top)(|(cn=Bob)(!(mail=*))))"));
+        assert!(!matches("(cn:caseExactMatch:=Johnny)"));
+        // empty `any` substring (not representable in the string form): (cn=a*<empty>*)
+        let (_, filter) = Filter::from_ber(&hex!("a4 0b 04 02 63 6e 30 05 80 01 61 81 00"))
+            .expect("invalid filter");
+        assert!(!filter.matches(entry));
+        let (_, filter) = Filter::from_ber(&hex!("a4 0b 04 02 63 6e 30 05 80 01 4a 81 00"))
+            .expect("invalid filter");
+        assert!(filter.matches(entry));
+    }
+
+    #[test]
+    fn test_filter_assertions() {
+        use crate::parse_filter_str;
+
+        let filter = parse_filter_str("(&(objectClass=user)(|(cn~=bob)(sn=*)(!(age<=18))))")
+            .expect("invalid filter");
+        let kinds: Vec<_> = filter
+            .assertions()
+            .iter()
+            .map(|(kind, ava)| (*kind, ava.attribute_desc.0.as_ref()))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (FilterMatchKind::Equality, "objectClass"),
+                (FilterMatchKind::Approx, "cn"),
+                (FilterMatchKind::LessOrEqual, "age"),
+            ]
+        );
+    }
+}
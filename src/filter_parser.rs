@@ -8,7 +8,8 @@ use asn1_rs::{
     Any, Class, FromBer, OptTaggedParser, ParseResult, Sequence, Set, Tag, TaggedParser,
 };
 use nom::combinator::{complete, map};
-use nom::multi::{many0, many1};
+use nom::error::ErrorKind;
+use nom::multi::many1;
 use nom::Err;
 // use nom::dbg_dmp;
 use std::borrow::Cow;
@@ -17,7 +18,7 @@ use std::borrow::Cow;
 //                         -- Constrained to <attributedescription>
 //                         -- [RFC4512]
 #[inline]
-fn parse_ldap_attribute_description(i: &[u8]) -> Result<LdapString> {
+fn parse_ldap_attribute_description(i: &[u8]) -> Result<'_, LdapString<'_>> {
     LdapString::from_ber(i)
 }
 
@@ -30,12 +31,14 @@ fn parse_ldap_attribute_description(i: &[u8]) -> Result<LdapString> {
 // AttributeValueAssertion ::= SEQUENCE {
 //      attributeDesc   AttributeDescription,
 //      assertionValue  AssertionValue }
-fn parse_ldap_attribute_value_assertion_content(content: &[u8]) -> Result<AttributeValueAssertion> {
+fn parse_ldap_attribute_value_assertion_content(
+    content: &[u8],
+) -> Result<'_, AttributeValueAssertion<'_>> {
     let (content, attribute_desc) = parse_ldap_attribute_description(content)?;
     let (content, assertion_value) = parse_ldap_assertion_value(content)?;
     let assertion = AttributeValueAssertion {
         attribute_desc,
-        assertion_value,
+        assertion_value: Cow::Borrowed(assertion_value),
     };
     Ok((content, assertion))
 }
@@ -48,13 +51,13 @@ impl<'a> FromBer<'a, LdapError> for AttributeValueAssertion<'a> {
 
 // AssertionValue ::= OCTET STRING
 #[inline]
-fn parse_ldap_assertion_value(i: &[u8]) -> Result<&[u8]> {
+fn parse_ldap_assertion_value(i: &[u8]) -> Result<'_, &[u8]> {
     parse_ldap_octet_string_as_slice(i)
 }
 
 // AttributeValue ::= OCTET STRING
 #[inline]
-fn parse_ldap_attribute_value(i: &[u8]) -> Result<AttributeValue> {
+fn parse_ldap_attribute_value(i: &[u8]) -> Result<'_, AttributeValue<'_>> {
     map(parse_ldap_octet_string_as_slice, |v| {
         AttributeValue(Cow::Borrowed(v))
     })(i)
@@ -67,12 +70,8 @@ impl<'a> FromBer<'a, LdapError> for PartialAttribute<'a> {
     fn from_ber(bytes: &'a [u8]) -> ParseResult<'a, Self, LdapError> {
         Sequence::from_ber_and_then(bytes, |i| {
             let (i, attr_type) = LdapString::from_ber(i)?;
-            let (i, attr_vals) = Set::from_ber_and_then(i, |inner| {
-                many0(complete(
-                    // dbg_dmp(|d| parse_ldap_attribute_value(d), "parse_partial_attribute")
-                    parse_ldap_attribute_value,
-                ))(inner)
-            })?;
+            let (i, attr_vals) =
+                Set::from_ber_and_then(i, many0_presized(parse_ldap_attribute_value))?;
             let partial_attr = PartialAttribute {
                 attr_type,
                 attr_vals,
@@ -90,6 +89,9 @@ impl<'a> FromBer<'a, LdapError> for Attribute<'a> {
         Sequence::from_ber_and_then(bytes, |i| {
             let (i, attr_type) = LdapString::from_ber(i)?;
             let (i, attr_vals) = Set::from_ber_and_then(i, |inner| {
+                if inner.is_empty() {
+                    return Err(Err::Error(LdapError::EmptyAttributeValues));
+                }
                 many1(complete(
                     // dbg_dmp(|d| parse_ldap_attribute_value(d), "parse_partial_attribute")
                     parse_ldap_attribute_value,
@@ -120,62 +122,104 @@ impl<'a> FromBer<'a, LdapError> for Attribute<'a> {
 //     ...  }
 impl<'a> FromBer<'a, LdapError> for Filter<'a> {
     fn from_ber(bytes: &'a [u8]) -> ParseResult<'a, Self, LdapError> {
-        // read next element as ANY and look tag value
-        let (rem, any) = Any::from_ber(bytes).map_err(Err::convert)?;
-        // eprintln!("parse_ldap_filter: [{}] {:?}", header.tag.0, header);
-        // tag is context-specific IMPLICIT
-        any.class()
-            .assert_eq(Class::ContextSpecific)
-            .map_err(|e| Err::Error(e.into()))?;
-        let content = any.data;
-        let (_, filter) = match any.tag().0 {
-            0 => {
-                let (rem, sub_filters) = many1(complete(Filter::from_ber))(content)?;
-                Ok((rem, Filter::And(sub_filters)))
-            }
-            1 => {
-                let (rem, sub_filters) = many1(complete(Filter::from_ber))(content)?;
-                Ok((rem, Filter::Or(sub_filters)))
-            }
-            2 => map(Filter::from_ber, |f| Filter::Not(Box::new(f)))(content),
-            3 => map(
-                parse_ldap_attribute_value_assertion_content,
-                Filter::EqualityMatch,
-            )(content),
-            4 => map(parse_ldap_substrings_filter_content, Filter::Substrings)(content),
-            5 => map(
-                parse_ldap_attribute_value_assertion_content,
-                Filter::GreaterOrEqual,
-            )(content),
-            6 => map(
-                parse_ldap_attribute_value_assertion_content,
-                Filter::LessOrEqual,
-            )(content),
-            7 => {
-                let s =
-                    std::str::from_utf8(content).or(Err(Err::Error(LdapError::InvalidString)))?;
-                let s = LdapString(Cow::Borrowed(s));
-                Ok(([].as_ref(), Filter::Present(s)))
-            }
-            8 => map(
-                parse_ldap_attribute_value_assertion_content,
-                Filter::ApproxMatch,
-            )(content),
-            9 => map(
-                parse_ldap_matching_rule_assertion_content,
-                Filter::ExtensibleMatch,
-            )(content),
-            _ => {
-                // print_hex_dump(i, 32);
-                // panic!("Filter id {} not yet implemented", header.tag.0);
-                Err(Err::Error(LdapError::InvalidFilterType))
-            }
-        }?;
-        // use the remaining bytes from the outer object
-        Ok((rem, filter))
+        parse_ldap_filter(bytes, MAX_FILTER_DEPTH)
     }
 }
 
+impl<'a> Filter<'a> {
+    /// Parse a filter, allowing at most `max_depth` levels of nested filters
+    ///
+    /// Nesting (using `and`, `or` and `not`) beyond `max_depth` levels returns a
+    /// [`LdapError::RecursionLimitExceeded`] error. The [`FromBer`] implementation uses
+    /// [`MAX_FILTER_DEPTH`] as the limit.
+    pub fn from_ber_with_limit(
+        bytes: &'a [u8],
+        max_depth: usize,
+    ) -> ParseResult<'a, Self, LdapError> {
+        parse_ldap_filter(bytes, max_depth)
+    }
+}
+
+fn parse_ldap_filter(bytes: &[u8], max_depth: usize) -> Result<'_, Filter<'_>> {
+    if max_depth == 0 {
+        return Err(Err::Error(LdapError::RecursionLimitExceeded));
+    }
+    let depth = max_depth - 1;
+    // read next element as ANY and look tag value
+    let (rem, any) = Any::from_ber(bytes).map_err(Err::convert)?;
+    // eprintln!("parse_ldap_filter: [{}] {:?}", header.tag.0, header);
+    // tag is context-specific IMPLICIT
+    if any.class() != Class::ContextSpecific {
+        return Err(Err::Error(LdapError::UnexpectedFilterClass {
+            expected: Class::ContextSpecific,
+            found: any.class(),
+        }));
+    }
+    let content = any.data;
+    let (_, filter) = match any.tag().0 {
+        0 => parse_filter_set(content, depth).map(|(rem, v)| (rem, Filter::And(v))),
+        1 => parse_filter_set(content, depth).map(|(rem, v)| (rem, Filter::Or(v))),
+        2 => map(
+            |i| parse_ldap_filter(i, depth),
+            |f| Filter::Not(Box::new(f)),
+        )(content),
+        3 => map(
+            parse_ldap_attribute_value_assertion_content,
+            Filter::EqualityMatch,
+        )(content),
+        4 => map(parse_ldap_substrings_filter_content, Filter::Substrings)(content),
+        5 => map(
+            parse_ldap_attribute_value_assertion_content,
+            Filter::GreaterOrEqual,
+        )(content),
+        6 => map(
+            parse_ldap_attribute_value_assertion_content,
+            Filter::LessOrEqual,
+        )(content),
+        7 => {
+            let s = parse_utf8(content, "AttributeDescription")?;
+            let s = LdapString(Cow::Borrowed(s));
+            Ok(([].as_ref(), Filter::Present(s)))
+        }
+        8 => map(
+            parse_ldap_attribute_value_assertion_content,
+            Filter::ApproxMatch,
+        )(content),
+        9 => map(
+            parse_ldap_matching_rule_assertion_content,
+            Filter::ExtensibleMatch,
+        )(content),
+        // Filter CHOICE is extensible: keep content of unknown filters
+        tag => Ok((
+            [].as_ref(),
+            Filter::Unknown {
+                tag,
+                data: Cow::Borrowed(content),
+            },
+        )),
+    }?;
+    // use the remaining bytes from the outer object
+    Ok((rem, filter))
+}
+
+// SET SIZE (1..MAX) OF filter Filter
+//
+// All elements must be valid: errors of sub-filters (including the recursion limit) are returned
+// unchanged, instead of stopping at the first invalid element.
+fn parse_filter_set(content: &[u8], depth: usize) -> Result<'_, Vec<Filter<'_>>> {
+    if content.is_empty() {
+        return Err(Err::Error(LdapError::NomError(ErrorKind::Many1)));
+    }
+    let mut filters = Vec::new();
+    let mut i = content;
+    while !i.is_empty() {
+        let (rem, filter) = complete(|i| parse_ldap_filter(i, depth))(i)?;
+        filters.push(filter);
+        i = rem;
+    }
+    Ok((i, filters))
+}
+
 // SubstringFilter ::= SEQUENCE {
 //      type           AttributeDescription,
 //      substrings     SEQUENCE SIZE (1..MAX) OF substring CHOICE {
@@ -183,10 +227,11 @@ impl<'a> FromBer<'a, LdapError> for Filter<'a> {
 //           any     [1] AssertionValue,
 //           final   [2] AssertionValue } -- can occur at most once
 //      }
-fn parse_ldap_substrings_filter_content(i: &[u8]) -> Result<SubstringFilter> {
+fn parse_ldap_substrings_filter_content(i: &[u8]) -> Result<'_, SubstringFilter<'_>> {
     let (i, filter_type) = parse_ldap_attribute_description(i)?;
     let (i, substrings) =
         Sequence::from_ber_and_then(i, |inner| many1(complete(parse_ldap_substring))(inner))?;
+    check_substrings_order(&substrings)?;
     let filter = SubstringFilter {
         filter_type,
         substrings,
@@ -194,7 +239,20 @@ fn parse_ldap_substrings_filter_content(i: &[u8]) -> Result<SubstringFilter> {
     Ok((i, filter))
 }
 
-fn parse_ldap_substring(bytes: &[u8]) -> Result<Substring> {
+/// Check that `initial` is only used as first element, and `final` only as last element
+fn check_substrings_order(substrings: &[Substring]) -> std::result::Result<(), LdapError> {
+    let last = substrings.len().saturating_sub(1);
+    for (idx, substring) in substrings.iter().enumerate() {
+        match substring {
+            Substring::Initial(_) if idx != 0 => return Err(LdapError::InvalidSubstring),
+            Substring::Final(_) if idx != last => return Err(LdapError::InvalidSubstring),
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+fn parse_ldap_substring(bytes: &[u8]) -> Result<'_, Substring<'_>> {
     let (rem, any) = Any::from_ber(bytes).map_err(Err::convert)?;
     // in any case, this is an AssertionValue (== OCTET STRING)
     let b = AssertionValue(Cow::Borrowed(any.data));
@@ -211,17 +269,17 @@ fn parse_ldap_substring(bytes: &[u8]) -> Result<Substring> {
 //     type            [2] AttributeDescription OPTIONAL,
 //     matchValue      [3] AssertionValue,
 //     dnAttributes    [4] BOOLEAN DEFAULT FALSE }
-fn parse_ldap_matching_rule_assertion_content(i: &[u8]) -> Result<MatchingRuleAssertion> {
+fn parse_ldap_matching_rule_assertion_content(i: &[u8]) -> Result<'_, MatchingRuleAssertion<'_>> {
     // MatchingRuleId ::= LDAPString
     let (i, matching_rule) =
         OptTaggedParser::new(Class::ContextSpecific, Tag(1)).parse_ber(i, |_, content| {
-            let s = std::str::from_utf8(content).or(Err(Err::Error(LdapError::InvalidString)))?;
+            let s = parse_utf8(content, "MatchingRuleId")?;
             let s = LdapString(Cow::Borrowed(s));
             Ok((&b""[..], s))
         })?;
     let (i, rule_type) =
         OptTaggedParser::new(Class::ContextSpecific, Tag(2)).parse_ber(i, |_, content| {
-            let s = std::str::from_utf8(content).or(Err(Err::Error(LdapError::InvalidString)))?;
+            let s = parse_utf8(content, "AttributeDescription")?;
             let s = AttributeDescription(Cow::Borrowed(s));
             Ok((&b""[..], s))
         })?;
@@ -233,6 +291,10 @@ fn parse_ldap_matching_rule_assertion_content(i: &[u8]) -> Result<MatchingRuleAs
     let (i, dn_attributes) =
         OptTaggedImplicit::<bool, asn1_rs::Error, 4>::from_ber(i).map_err(Err::convert)?;
     let dn_attributes = dn_attributes.map(|t| t.into_inner());
+    // at least one of matchingRule and type must be present
+    if matching_rule.is_none() && rule_type.is_none() {
+        return Err(Err::Error(LdapError::InvalidFilterType));
+    }
     let assertion = MatchingRuleAssertion {
         matching_rule,
         rule_type,
@@ -0,0 +1,622 @@
+//! Parser for the string representation of LDAP filters ([RFC4515])
+//!
+//! [RFC4515]: https://tools.ietf.org/html/rfc4515
+
+use crate::error::LdapError;
+use crate::filter::*;
+use crate::ldap::LdapString;
+use std::borrow::Cow;
+use std::str::FromStr;
+
+/// Parse the string representation of a LDAP filter ([RFC4515])
+///
+/// Since assertion values may contain escaped characters (`\XX`), the returned filter owns its
+/// data and does not borrow from the input.
+///
+/// ```rust
+/// use ldap_parser::filter::Filter;
+/// use ldap_parser::parse_filter_str;
+///
+/// let filter = parse_filter_str("(&(uid=jdoe)(|(mail=*@x.com)(cn=John*)))").expect("parsing failed");
+/// assert!(matches!(filter, Filter::And(ref v) if v.len() == 2));
+/// ```
+///
+/// [RFC4515]: https://tools.ietf.org/html/rfc4515
+pub fn parse_filter_str(s: &str) -> Result<Filter<'static>, LdapError> {
+    if s.trim().is_empty() {
+        return Err(LdapError::EmptyFilter);
+    }
+    let mut parser = FilterStrParser {
+        input: s.as_bytes(),
+        pos: 0,
+        depth: 0,
+    };
+    let filter = parser.parse_filter()?;
+    match parser.peek() {
+        None => Ok(filter),
+        Some(b')') => Err(LdapError::UnbalancedParentheses),
+        Some(_) => Err(LdapError::InvalidFilterString),
+    }
+}
+
+impl FromStr for Filter<'static> {
+    type Err = LdapError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_filter_str(s)
+    }
+}
+
+struct FilterStrParser<'s> {
+    input: &'s [u8],
+    pos: usize,
+    depth: usize,
+}
+
+impl<'s> FilterStrParser<'s> {
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).copied()
+    }
+
+    // filter = LPAREN filtercomp RPAREN
+    fn parse_filter(&mut self) -> Result<Filter<'static>, LdapError> {
+        if self.depth >= MAX_FILTER_DEPTH {
+            return Err(LdapError::RecursionLimitExceeded);
+        }
+        self.depth += 1;
+        let res = self.parse_filter_inner();
+        self.depth -= 1;
+        res
+    }
+
+    fn parse_filter_inner(&mut self) -> Result<Filter<'static>, LdapError> {
+        match self.peek() {
+            Some(b'(') => self.pos += 1,
+            Some(b')') => return Err(LdapError::UnbalancedParentheses),
+            Some(_) => return Err(LdapError::InvalidFilterString),
+            None => return Err(LdapError::UnbalancedParentheses),
+        }
+        let filter = self.parse_filtercomp()?;
+        match self.peek() {
+            Some(b')') => {
+                self.pos += 1;
+                Ok(filter)
+            }
+            Some(_) => Err(LdapError::InvalidFilterString),
+            None => Err(LdapError::UnbalancedParentheses),
+        }
+    }
+
+    // filtercomp = and / or / not / item
+    // and        = AMPERSAND filterlist
+    // or         = VERTBAR filterlist
+    // not        = EXCLAMATION filter
+    fn parse_filtercomp(&mut self) -> Result<Filter<'static>, LdapError> {
+        match self.peek() {
+            Some(b'&') => {
+                self.pos += 1;
+                self.parse_filterlist().map(Filter::And)
+            }
+            Some(b'|') => {
+                self.pos += 1;
+                self.parse_filterlist().map(Filter::Or)
+            }
+            Some(b'!') => {
+                self.pos += 1;
+                self.parse_filter().map(|f| Filter::Not(Box::new(f)))
+            }
+            Some(b')') => Err(LdapError::EmptyFilter),
+            Some(_) => self.parse_item(),
+            None => Err(LdapError::UnbalancedParentheses),
+        }
+    }
+
+    // filterlist = 1*filter
+    fn parse_filterlist(&mut self) -> Result<Vec<Filter<'static>>, LdapError> {
+        let mut filters = Vec::new();
+        while self.peek() == Some(b'(') {
+            filters.push(self.parse_filter()?);
+        }
+        if filters.is_empty() {
+            return Err(LdapError::EmptyFilter);
+        }
+        Ok(filters)
+    }
+
+    // item = simple / present / substring / extensible
+    fn parse_item(&mut self) -> Result<Filter<'static>, LdapError> {
+        // the item ends at the next (unescaped) parenthesis
+        let start = self.pos;
+        let len = self.input[start..]
+            .iter()
+            .position(|&c| c == b'(' || c == b')')
+            .unwrap_or(self.input.len() - start);
+        self.pos += len;
+        if self.peek() == Some(b'(') {
+            return Err(LdapError::InvalidFilterString);
+        }
+        // input is a str, and the split happens on ASCII characters
+        let item = std::str::from_utf8(&self.input[start..self.pos])
+            .or(Err(LdapError::InvalidFilterString))?;
+        parse_item_str(item)
+    }
+}
+
+fn parse_item_str(item: &str) -> Result<Filter<'static>, LdapError> {
+    let eq = item.find('=').ok_or(LdapError::InvalidFilterString)?;
+    let (left, value) = (&item[..eq], &item[eq + 1..]);
+    // simple   = attr filtertype assertionvalue
+    // filtertype = equal / approx / greaterorequal / lessorequal
+    if let Some(attr) = left.strip_suffix('~') {
+        let ava = parse_ava(attr, value)?;
+        return Ok(Filter::ApproxMatch(ava));
+    }
+    if let Some(attr) = left.strip_suffix('>') {
+        let ava = parse_ava(attr, value)?;
+        return Ok(Filter::GreaterOrEqual(ava));
+    }
+    if let Some(attr) = left.strip_suffix('<') {
+        let ava = parse_ava(attr, value)?;
+        return Ok(Filter::LessOrEqual(ava));
+    }
+    if let Some(left) = left.strip_suffix(':') {
+        return parse_extensible(left, value).map(Filter::ExtensibleMatch);
+    }
+    let attr = parse_attr(left)?;
+    // present   = attr EQUALS ASTERISK
+    if value == "*" {
+        return Ok(Filter::Present(attr));
+    }
+    // substring = attr EQUALS [initial] any [final]
+    if value.contains('*') {
+        return parse_substrings(attr, value).map(Filter::Substrings);
+    }
+    let assertion_value = Cow::Owned(unescape_value(value)?);
+    Ok(Filter::EqualityMatch(AttributeValueAssertion {
+        attribute_desc: attr,
+        assertion_value,
+    }))
+}
+
+fn parse_ava(attr: &str, value: &str) -> Result<AttributeValueAssertion<'static>, LdapError> {
+    let attribute_desc = parse_attr(attr)?;
+    if value.contains('*') {
+        return Err(LdapError::InvalidFilterString);
+    }
+    let assertion_value = Cow::Owned(unescape_value(value)?);
+    Ok(AttributeValueAssertion {
+        attribute_desc,
+        assertion_value,
+    })
+}
+
+// attr = attributedescription
+//      ; The attributedescription rule is defined in
+//      ; Section 2.5 of [RFC4512].
+fn parse_attr(s: &str) -> Result<LdapString<'static>, LdapError> {
+    if !is_valid_attr(s) {
+        return Err(LdapError::InvalidFilterString);
+    }
+    Ok(LdapString(Cow::Owned(s.to_owned())))
+}
+
+fn is_valid_attr(s: &str) -> bool {
+    !s.is_empty()
+        && s.bytes()
+            .all(|c| c.is_ascii_alphanumeric() || c == b'-' || c == b'.' || c == b';')
+}
+
+// matchingrule = COLON oid
+fn parse_matching_rule(s: &str) -> Result<LdapString<'static>, LdapError> {
+    if !is_valid_matching_rule(s) {
+        return Err(LdapError::InvalidFilterString);
+    }
+    Ok(LdapString(Cow::Owned(s.to_owned())))
+}
+
+fn is_valid_matching_rule(s: &str) -> bool {
+    !s.is_empty()
+        && s.bytes()
+            .all(|c| c.is_ascii_alphanumeric() || c == b'-' || c == b'.')
+}
+
+// initial = assertionvalue
+// any     = ASTERISK *(assertionvalue ASTERISK)
+// final   = assertionvalue
+fn parse_substrings(
+    filter_type: LdapString<'static>,
+    value: &str,
+) -> Result<SubstringFilter<'static>, LdapError> {
+    let parts: Vec<&str> = value.split('*').collect();
+    let last = parts.len() - 1;
+    let mut substrings = Vec::with_capacity(parts.len());
+    for (idx, part) in parts.iter().enumerate() {
+        if part.is_empty() {
+            // only initial and final can be omitted
+            if idx == 0 || idx == last {
+                continue;
+            }
+            return Err(LdapError::InvalidFilterString);
+        }
+        let b = AssertionValue(Cow::Owned(unescape_value(part)?));
+        let substring = if idx == 0 {
+            Substring::Initial(b)
+        } else if idx == last {
+            Substring::Final(b)
+        } else {
+            Substring::Any(b)
+        };
+        substrings.push(substring);
+    }
+    if substrings.is_empty() {
+        return Err(LdapError::InvalidFilterString);
+    }
+    Ok(SubstringFilter {
+        filter_type,
+        substrings,
+    })
+}
+
+// extensible = ( attr [dnattrs]
+//                  [matchingrule] COLON EQUALS assertionvalue )
+//              / ( [dnattrs]
+//                   matchingrule COLON EQUALS assertionvalue )
+// dnattrs = COLON "dn"
+fn parse_extensible(left: &str, value: &str) -> Result<MatchingRuleAssertion<'static>, LdapError> {
+    let mut parts = left.split(':');
+    // split always returns at least one element
+    let attr = parts.next().unwrap_or_default();
+    let rule_type = if attr.is_empty() {
+        None
+    } else {
+        let attr = parse_attr(attr)?;
+        Some(AttributeDescription(attr.0))
+    };
+    let mut dn_attributes = None;
+    let mut matching_rule = None;
+    for part in parts {
+        if matching_rule.is_some() {
+            return Err(LdapError::InvalidFilterString);
+        }
+        if dn_attributes.is_none() && part.eq_ignore_ascii_case("dn") {
+            dn_attributes = Some(true);
+        } else {
+            matching_rule = Some(parse_matching_rule(part)?);
+        }
+    }
+    if rule_type.is_none() && matching_rule.is_none() {
+        return Err(LdapError::InvalidFilterString);
+    }
+    if value.contains('*') {
+        return Err(LdapError::InvalidFilterString);
+    }
+    let assertion_value = AssertionValue(Cow::Owned(unescape_value(value)?));
+    Ok(MatchingRuleAssertion {
+        matching_rule,
+        rule_type,
+        assertion_value,
+        dn_attributes,
+    })
+}
+
+impl<'a> MatchingRuleAssertion<'a> {
+    /// Create an extensible match assertion, checking that it can be represented in a filter
+    ///
+    /// At least one of `rule_type` and `matching_rule` is required (`LdapError::InvalidFilterType`
+    /// is returned otherwise), and they must be a valid attribute description and matching rule
+    /// identifier (`LdapError::InvalidFilterString` is returned otherwise). If `dn_attributes`
+    /// is `false`, the flag is absent (`None`), since it is the default value.
+    pub fn new(
+        rule_type: Option<AttributeDescription<'a>>,
+        matching_rule: Option<LdapString<'a>>,
+        assertion_value: AssertionValue<'a>,
+        dn_attributes: bool,
+    ) -> Result<Self, LdapError> {
+        let mra = MatchingRuleAssertion {
+            matching_rule,
+            rule_type,
+            assertion_value,
+            dn_attributes: if dn_attributes { Some(true) } else { None },
+        };
+        mra.check_components()?;
+        Ok(mra)
+    }
+
+    fn check_components(&self) -> Result<(), LdapError> {
+        if self.rule_type.is_none() && self.matching_rule.is_none() {
+            return Err(LdapError::InvalidFilterType);
+        }
+        if matches!(self.rule_type, Some(ref t) if !is_valid_attr(&t.0))
+            || matches!(self.matching_rule, Some(ref r) if !is_valid_matching_rule(&r.0))
+        {
+            return Err(LdapError::InvalidFilterString);
+        }
+        Ok(())
+    }
+
+    /// Get the string representation of the assertion, as an extensible match filter ([RFC4515])
+    ///
+    /// The result has the form `([attr][:dn][:rule]:=value)`, with the value escaped using
+    /// [`escape_filter_value`]. The components are checked as in [`MatchingRuleAssertion::new`].
+    ///
+    /// ```rust
+    /// use ldap_parser::filter::{AssertionValue, AttributeDescription, MatchingRuleAssertion};
+    /// use ldap_parser::ldap::LdapString;
+    /// use std::borrow::Cow;
+    ///
+    /// let mra = MatchingRuleAssertion::new(
+    ///     Some(AttributeDescription(Cow::Borrowed("cn"))),
+    ///     Some(LdapString(Cow::Borrowed("2.5.13.5"))),
+    ///     AssertionValue(Cow::Borrowed(b"Smith*")),
+    ///     true,
+    /// )
+    /// .expect("invalid assertion");
+    /// assert_eq!(mra.to_filter_string().unwrap(), r"(cn:dn:2.5.13.5:=Smith\2a)");
+    /// ```
+    ///
+    /// [RFC4515]: https://tools.ietf.org/html/rfc4515
+    pub fn to_filter_string(&self) -> Result<String, LdapError> {
+        self.check_components()?;
+        let mut s = String::from("(");
+        if let Some(rule_type) = &self.rule_type {
+            s.push_str(&rule_type.0);
+        }
+        if self.dn_attributes_value() {
+            s.push_str(":dn");
+        }
+        if let Some(matching_rule) = &self.matching_rule {
+            s.push(':');
+            s.push_str(&matching_rule.0);
+        }
+        s.push_str(":=");
+        s.push_str(&escape_filter_value(&self.assertion_value.0));
+        s.push(')');
+        Ok(s)
+    }
+}
+
+/// Escape an assertion value, to use it in the string representation of a filter ([RFC4515])
+///
+/// `*`, `(`, `)`, `\` and NUL are escaped as `\XX`. Non-ASCII bytes are also escaped, so any
+/// byte sequence (including invalid UTF-8) can be represented.
+///
+/// ```rust
+/// use ldap_parser::filter::escape_filter_value;
+///
+/// assert_eq!(escape_filter_value(b"a*(b)\\"), r"a\2a\28b\29\5c");
+/// ```
+///
+/// [RFC4515]: https://tools.ietf.org/html/rfc4515
+pub fn escape_filter_value(input: &[u8]) -> String {
+    let mut s = String::with_capacity(input.len());
+    for &c in input {
+        match c {
+            0 | b'(' | b')' | b'*' | b'\\' | 0x80..=0xff => {
+                s.push_str(&format!("\\{:02x}", c));
+            }
+            c => s.push(char::from(c)),
+        }
+    }
+    s
+}
+
+/// Unescape an assertion value from the string representation of a filter ([RFC4515])
+///
+/// This is the inverse of [`escape_filter_value`]. Unescaped `*`, `(`, `)` and NUL characters
+/// are rejected.
+///
+/// [RFC4515]: https://tools.ietf.org/html/rfc4515
+pub fn unescape_filter_value(input: &str) -> Result<Vec<u8>, LdapError> {
+    unescape_value(input)
+}
+
+// assertionvalue = valueencoding
+// valueencoding  = 0*(normal / escaped)
+// escaped        = ESC HEX HEX
+pub(crate) fn unescape_value(s: &str) -> Result<Vec<u8>, LdapError> {
+    let b = s.as_bytes();
+    let mut v = Vec::with_capacity(b.len());
+    let mut idx = 0;
+    while idx < b.len() {
+        match b[idx] {
+            b'\\' => {
+                let hex = b.get(idx + 1..idx + 3).ok_or(LdapError::InvalidEscape)?;
+                let hi = hex_value(hex[0]).ok_or(LdapError::InvalidEscape)?;
+                let lo = hex_value(hex[1]).ok_or(LdapError::InvalidEscape)?;
+                v.push((hi << 4) | lo);
+                idx += 3;
+            }
+            0 | b'(' | b')' | b'*' => return Err(LdapError::InvalidFilterString),
+            c => {
+                v.push(c);
+                idx += 1;
+            }
+        }
+    }
+    Ok(v)
+}
+
+pub(crate) fn hex_value(c: u8) -> Option<u8> {
+    match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        b'A'..=b'F' => Some(c - b'A' + 10),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_filter_str_nested() {
+        let filter =
+            parse_filter_str("(&(uid=jdoe)(|(mail=*@x.com)(cn=John*)))").expect("parsing failed");
+        let v = match filter {
+            Filter::And(v) => v,
+            _ => panic!("wrong filter type"),
+        };
+        assert_eq!(v.len(), 2);
+        if let Filter::EqualityMatch(ava) = &v[0] {
+            assert_eq!(&ava.attribute_desc.0, "uid");
+            assert_eq!(ava.assertion_value.as_ref(), b"jdoe");
+        } else {
+            panic!("wrong filter type");
+        }
+        if let Filter::Or(v) = &v[1] {
+            assert_eq!(
+                v[0],
+                Filter::Substrings(SubstringFilter {
+                    filter_type: LdapString(Cow::Borrowed("mail")),
+                    substrings: vec![Substring::Final(AssertionValue(Cow::Borrowed(b"@x.com")))],
+                })
+            );
+            assert_eq!(
+                v[1],
+                Filter::Substrings(SubstringFilter {
+                    filter_type: LdapString(Cow::Borrowed("cn")),
+                    substrings: vec![Substring::Initial(AssertionValue(Cow::Borrowed(b"John")))],
+                })
+            );
+        } else {
+            panic!("wrong filter type");
+        }
+    }
+
+    #[test]
+    fn test_parse_filter_str_items() {
+        let filter: Filter = "(!(objectClass=*))".parse().expect("parsing failed");
+        assert_eq!(
+            filter,
+            Filter::Not(Box::new(Filter::Present(LdapString(Cow::Borrowed(
+                "objectClass"
+            )))))
+        );
+        let filter = parse_filter_str(r"(cn=a\2ab\5c\00)").expect("parsing failed");
+        if let Filter::EqualityMatch(ava) = filter {
+            assert_eq!(ava.assertion_value.as_ref(), b"a*b\\\x00");
+        } else {
+            panic!("wrong filter type");
+        }
+        let filter = parse_filter_str("(cn=*a*b*)").expect("parsing failed");
+        if let Filter::Substrings(f) = filter {
+            assert_eq!(f.substrings.len(), 2);
+            assert!(matches!(f.substrings[0], Substring::Any(_)));
+        } else {
+            panic!("wrong filter type");
+        }
+        let filter = parse_filter_str("(uidNumber>=1000)").expect("parsing failed");
+        assert!(matches!(filter, Filter::GreaterOrEqual(_)));
+    }
+
+    #[test]
+    fn test_parse_filter_str_extensible() {
+        let filter = parse_filter_str("(cn:dn:2.5.13.5:=John)").expect("parsing failed");
+        let mra = match filter {
+            Filter::ExtensibleMatch(mra) => mra,
+            _ => panic!("wrong filter type"),
+        };
+        assert_eq!(mra.rule_type.as_ref().map(|t| t.0.as_ref()), Some("cn"));
+        assert_eq!(
+            mra.matching_rule.as_ref().map(|r| r.0.as_ref()),
+            Some("2.5.13.5")
+        );
+        assert_eq!(mra.dn_attributes, Some(true));
+        assert_eq!(mra.assertion_value.0.as_ref(), b"John");
+
+        let filter = parse_filter_str("(:caseExactMatch:=Fred)").expect("parsing failed");
+        if let Filter::ExtensibleMatch(mra) = filter {
+            assert!(mra.rule_type.is_none());
+            assert!(mra.dn_attributes.is_none());
+        } else {
+            panic!("wrong filter type");
+        }
+        // at least one of type or matching rule is required
+        assert_eq!(
+            parse_filter_str("(:dn:=x)"),
+            Err(LdapError::InvalidFilterString)
+        );
+    }
+
+    #[test]
+    fn test_matching_rule_assertion_to_string() {
+        for s in [
+            "(cn:dn:2.5.13.5:=John)",
+            "(:caseExactMatch:=Fred)",
+            "(:dn:2.4.6.8.10:=Dino)",
+            r"(o:=Lu\c4\8di\c4\87)",
+        ] {
+            let mra = match parse_filter_str(s).expect("parsing failed") {
+                Filter::ExtensibleMatch(mra) => mra,
+                _ => panic!("wrong filter type"),
+            };
+            assert_eq!(mra.to_filter_string().as_deref(), Ok(s));
+        }
+        let value = AssertionValue(Cow::Borrowed(b"x"));
+        assert_eq!(
+            MatchingRuleAssertion::new(None, None, value.clone(), true),
+            Err(LdapError::InvalidFilterType)
+        );
+        assert_eq!(
+            MatchingRuleAssertion::new(
+                Some(AttributeDescription(Cow::Borrowed("c n"))),
+                None,
+                value.clone(),
+                false
+            ),
+            Err(LdapError::InvalidFilterString)
+        );
+        let mra = MatchingRuleAssertion {
+            matching_rule: None,
+            rule_type: None,
+            assertion_value: value,
+            dn_attributes: None,
+        };
+        assert_eq!(mra.to_filter_string(), Err(LdapError::InvalidFilterType));
+    }
+
+    #[test]
+    fn test_parse_filter_str_errors() {
+        assert_eq!(parse_filter_str(""), Err(LdapError::EmptyFilter));
+        assert_eq!(parse_filter_str("()"), Err(LdapError::EmptyFilter));
+        assert_eq!(
+            parse_filter_str("(&(cn=a)"),
+            Err(LdapError::UnbalancedParentheses)
+        );
+        assert_eq!(
+            parse_filter_str("(cn=a))"),
+            Err(LdapError::UnbalancedParentheses)
+        );
+        assert_eq!(parse_filter_str(r"(cn=a\4)"), Err(LdapError::InvalidEscape));
+        assert_eq!(
+            parse_filter_str(r"(cn=a\zz)"),
+            Err(LdapError::InvalidEscape)
+        );
+        assert_eq!(
+            parse_filter_str("cn=a"),
+            Err(LdapError::InvalidFilterString)
+        );
+        let deep = format!("{}(cn=a){}", "(!".repeat(100), ")".repeat(100));
+        assert_eq!(
+            parse_filter_str(&deep),
+            Err(LdapError::RecursionLimitExceeded)
+        );
+    }
+
+    #[test]
+    fn test_escape_filter_value() {
+        assert_eq!(escape_filter_value(b"John Smith"), "John Smith");
+        assert_eq!(escape_filter_value(b"\0*"), r"\00\2a");
+        assert_eq!(escape_filter_value("é".as_bytes()), r"\c3\a9");
+        assert_eq!(unescape_filter_value(r"a\2A\28"), Ok(b"a*(".to_vec()));
+        assert_eq!(
+            unescape_filter_value("a*"),
+            Err(LdapError::InvalidFilterString)
+        );
+        // round-trip over all bytes
+        let all: Vec<u8> = (0..=255).collect();
+        let escaped = escape_filter_value(&all);
+        assert_eq!(unescape_filter_value(&escaped), Ok(all));
+    }
+}
@@ -1,12 +1,24 @@
 //! Definitions for LDAP types
 
-use crate::error::Result;
+use crate::error::{LdapError, Result};
 use crate::filter::*;
-use asn1_rs::FromBer;
+use crate::url::{parse_ldap_url, LdapUrl};
+use asn1_rs::{FromBer, ToStatic};
 use rusticata_macros::newtype_enum;
 use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
 
-#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+/// Maximum value of constrained integers ([RFC4511] `maxInt`)
+///
+/// Message IDs, size limits and time limits are constrained to `(0 .. maxInt)`.
+///
+/// [RFC4511]: https://tools.ietf.org/html/rfc4511
+// maxInt INTEGER ::= 2147483647 -- (2^^31 - 1) --
+pub const LDAP_MAX_INT: u32 = 2_147_483_647;
+
+#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ProtocolOpTag(pub u32);
 
 newtype_enum! {
@@ -35,11 +47,12 @@ impl display ProtocolOpTag {
 }
 }
 
-#[derive(Default, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[derive(Default, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ResultCode(pub u32);
 
 newtype_enum! {
-impl debug ResultCode {
+impl ResultCode {
     Success = 0,
     OperationsError = 1,
     ProtocolError = 2,
@@ -89,10 +102,110 @@ impl debug ResultCode {
 }
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+impl ResultCode {
+    /// Test if the result code is `success`
+    pub fn is_success(&self) -> bool {
+        *self == ResultCode::Success
+    }
+
+    /// Test if the result code is `referral`
+    pub fn is_referral(&self) -> bool {
+        *self == ResultCode::Referral
+    }
+
+    /// Get the name of the result code, as spelled in RFC4511
+    fn name(&self) -> Option<&'static str> {
+        let name = match self.0 {
+            0 => "success",
+            1 => "operationsError",
+            2 => "protocolError",
+            3 => "timeLimitExceeded",
+            4 => "sizeLimitExceeded",
+            5 => "compareFalse",
+            6 => "compareTrue",
+            7 => "authMethodNotSupported",
+            8 => "strongerAuthRequired",
+            10 => "referral",
+            11 => "adminLimitExceeded",
+            12 => "unavailableCriticalExtension",
+            13 => "confidentialityRequired",
+            14 => "saslBindInProgress",
+            16 => "noSuchAttribute",
+            17 => "undefinedAttributeType",
+            18 => "inappropriateMatching",
+            19 => "constraintViolation",
+            20 => "attributeOrValueExists",
+            21 => "invalidAttributeSyntax",
+            32 => "noSuchObject",
+            33 => "aliasProblem",
+            34 => "invalidDNSyntax",
+            36 => "aliasDereferencingProblem",
+            48 => "inappropriateAuthentication",
+            49 => "invalidCredentials",
+            50 => "insufficientAccessRights",
+            51 => "busy",
+            52 => "unavailable",
+            53 => "unwillingToPerform",
+            54 => "loopDetect",
+            64 => "namingViolation",
+            65 => "objectClassViolation",
+            66 => "notAllowedOnNonLeaf",
+            67 => "notAllowedOnRDN",
+            68 => "entryAlreadyExists",
+            69 => "objectClassModsProhibited",
+            71 => "affectsMultipleDSAs",
+            80 => "other",
+            _ => return None,
+        };
+        Some(name)
+    }
+}
+
+/// Display the result code name as spelled in RFC4511 (for ex. `noSuchObject`), or
+/// `unknown(NN)` for codes outside the enumerated set
+impl fmt::Display for ResultCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.name() {
+            Some(name) => f.write_str(name),
+            None => write!(f, "unknown({})", self.0),
+        }
+    }
+}
+
+// Same output as the `Debug` implementation of `newtype_enum!`: the name of the constant, or
+// `ResultCode(NN / 0xNN)`
+impl fmt::Debug for ResultCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.name() {
+            Some(name) => {
+                // constants are the RFC4511 names, starting with an uppercase letter
+                let (first, rest) = name.split_at(1);
+                write!(f, "{}{}", first.to_ascii_uppercase(), rest)
+            }
+            None => write!(f, "ResultCode({} / 0x{:x})", self.0, self.0),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MessageID(pub u32);
 
-#[derive(PartialEq, Eq, Clone, Copy)]
+impl MessageID {
+    /// Get the message ID as a `u32`
+    pub fn as_u32(&self) -> u32 {
+        self.0
+    }
+}
+
+impl From<MessageID> for u32 {
+    fn from(id: MessageID) -> u32 {
+        id.0
+    }
+}
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SearchScope(pub u32);
 
 newtype_enum! {
@@ -103,7 +216,17 @@ impl debug SearchScope {
 }
 }
 
-#[derive(PartialEq, Eq, Clone, Copy)]
+impl SearchScope {
+    /// Test if the scope is one of the values defined in RFC4511
+    ///
+    /// The parser accepts any value, since the type is extensible.
+    pub fn is_valid(&self) -> bool {
+        self.0 <= 2
+    }
+}
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DerefAliases(pub u32);
 
 newtype_enum! {
@@ -115,7 +238,15 @@ impl debug DerefAliases {
 }
 }
 
-#[derive(PartialEq, Eq, Clone, Copy)]
+impl DerefAliases {
+    /// Test if the value is one of the values defined in RFC4511
+    pub fn is_valid(&self) -> bool {
+        self.0 <= 3
+    }
+}
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Operation(pub u32);
 
 newtype_enum! {
@@ -123,152 +254,585 @@ impl debug Operation {
     Add = 0,
     Delete = 1,
     Replace = 2,
+    // RFC4525
+    Increment = 3,
 }
 }
 
+impl Operation {
+    /// Test if the operation is one of the values defined in RFC4511 or RFC4525
+    pub fn is_valid(&self) -> bool {
+        self.0 <= 3
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct LdapString<'a>(pub Cow<'a, str>);
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LdapString<'a>(#[cfg_attr(feature = "serde", serde(borrow))] pub Cow<'a, str>);
 
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct LdapDN<'a>(pub Cow<'a, str>);
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LdapDN<'a>(#[cfg_attr(feature = "serde", serde(borrow))] pub Cow<'a, str>);
 
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct RelativeLdapDN<'a>(pub Cow<'a, str>);
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RelativeLdapDN<'a>(#[cfg_attr(feature = "serde", serde(borrow))] pub Cow<'a, str>);
 
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct LdapOID<'a>(pub Cow<'a, str>);
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LdapOID<'a>(#[cfg_attr(feature = "serde", serde(borrow))] pub Cow<'a, str>);
+
+// Display and AsRef<str> for string newtypes
+macro_rules! impl_str_newtype {
+    ($($t:ident),*) => {
+        $(
+            impl fmt::Display for $t<'_> {
+                fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                    f.write_str(&self.0)
+                }
+            }
+
+            impl AsRef<str> for $t<'_> {
+                fn as_ref(&self) -> &str {
+                    &self.0
+                }
+            }
+        )*
+    };
+}
+
+impl_str_newtype!(LdapString, LdapDN, RelativeLdapDN, LdapOID);
+
+/// An opaque cookie, used to resume paged or synchronized searches
+///
+/// Cookies must be sent back unmodified. When the cookie is optional in the grammar, an absent
+/// cookie (`None`) is distinct from an empty cookie.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SyncCookie<'a>(pub Cow<'a, [u8]>);
+
+impl SyncCookie<'_> {
+    /// Get the raw bytes of the cookie
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Test if the cookie is empty
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl AsRef<[u8]> for SyncCookie<'_> {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
 
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LdapResult<'a> {
     pub result_code: ResultCode,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub matched_dn: LdapDN<'a>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub diagnostic_message: LdapString<'a>,
-    // referral           [3] Referral OPTIONAL
+    /// URIs of the referral, if present
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub referral: Option<Vec<LdapString<'a>>>,
+}
+
+impl LdapResult<'_> {
+    /// Test if the result code indicates success (see [`ResultCode::is_success`])
+    pub fn is_success(&self) -> bool {
+        self.result_code.is_success()
+    }
+
+    /// Test if the diagnostic message is not empty
+    pub fn has_diagnostic(&self) -> bool {
+        !self.diagnostic_message.0.is_empty()
+    }
+
+    /// Get the diagnostic message, or `None` if it is empty
+    pub fn diagnostic(&self) -> Option<&str> {
+        Some(self.diagnostic_message.0.as_ref()).filter(|s| !s.is_empty())
+    }
+
+    /// Test if the matched DN is not empty
+    pub fn has_matched_dn(&self) -> bool {
+        !self.matched_dn.0.is_empty()
+    }
+
+    /// Parse the URIs of the referral as LDAP URLs
+    ///
+    /// Returns `None` if the result has no referral. Each URI is parsed independently, so a
+    /// malformed URI does not prevent reading the others.
+    pub fn referral_urls(&self) -> Option<Vec<std::result::Result<LdapUrl, LdapError>>> {
+        let referral = self.referral.as_ref()?;
+        Some(referral.iter().map(|uri| parse_ldap_url(&uri.0)).collect())
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BindRequest<'a> {
     pub version: u8,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub name: LdapDN<'a>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub authentication: AuthenticationChoice<'a>,
 }
 
+impl BindRequest<'_> {
+    /// Classify the authentication method of the bind request (RFC4513 section 5)
+    ///
+    /// A simple bind with an empty name and a non-empty password is classified as
+    /// [`BindAuthKind::Simple`], though RFC4513 requires servers to reject it.
+    ///
+    /// ```rust
+    /// use ldap_parser::builder::BindRequestBuilder;
+    /// use ldap_parser::ldap::BindAuthKind;
+    ///
+    /// let req = BindRequestBuilder::new().build();
+    /// assert_eq!(req.auth_kind(), BindAuthKind::Anonymous);
+    /// let req = BindRequestBuilder::new().name("cn=admin").build();
+    /// assert_eq!(req.auth_kind(), BindAuthKind::Unauthenticated);
+    /// let req = BindRequestBuilder::new().name("cn=admin").simple(b"secret").build();
+    /// assert_eq!(req.auth_kind(), BindAuthKind::Simple);
+    /// let req = BindRequestBuilder::new().sasl("EXTERNAL", None).build();
+    /// assert_eq!(req.auth_kind(), BindAuthKind::Sasl("EXTERNAL"));
+    /// ```
+    pub fn auth_kind(&self) -> BindAuthKind<'_> {
+        match &self.authentication {
+            AuthenticationChoice::Sasl(sasl) => BindAuthKind::Sasl(&sasl.mechanism.0),
+            AuthenticationChoice::Simple(password) if !password.is_empty() => BindAuthKind::Simple,
+            AuthenticationChoice::Simple(_) if self.name.0.is_empty() => BindAuthKind::Anonymous,
+            AuthenticationChoice::Simple(_) => BindAuthKind::Unauthenticated,
+        }
+    }
+}
+
+/// Authentication method of a bind request
+///
+/// See [`BindRequest::auth_kind`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BindAuthKind<'a> {
+    /// Simple bind with an empty name and an empty password (RFC4513 section 5.1.1)
+    Anonymous,
+    /// Simple bind with a non-empty name and an empty password (RFC4513 section 5.1.2)
+    Unauthenticated,
+    /// Simple bind with a non-empty password (RFC4513 section 5.1.3)
+    Simple,
+    /// SASL bind, with the name of the mechanism (RFC4513 section 5.2)
+    Sasl(&'a str),
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SaslCredentials<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub mechanism: LdapString<'a>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub credentials: Option<Cow<'a, [u8]>>,
 }
 
+impl SaslCredentials<'_> {
+    /// Get the SASL credentials, if present
+    pub fn credentials_bytes(&self) -> Option<&[u8]> {
+        self.credentials.as_deref()
+    }
+
+    /// Get the authorization identity of an `EXTERNAL` SASL bind ([RFC4422] appendix A)
+    ///
+    /// Returns `None` if the mechanism is not `EXTERNAL`, if the credentials are absent, or if
+    /// they are not valid UTF-8. Empty credentials (`Some("")`) request the authorization
+    /// identity derived from the authentication credentials, for ex. the TLS client certificate.
+    ///
+    /// ```rust
+    /// use ldap_parser::builder::BindRequestBuilder;
+    /// use ldap_parser::ldap::AuthenticationChoice;
+    ///
+    /// let req = BindRequestBuilder::new()
+    ///     .sasl("EXTERNAL", Some(b"dn:cn=admin,dc=example,dc=com"))
+    ///     .build();
+    /// if let AuthenticationChoice::Sasl(sasl) = &req.authentication {
+    ///     assert_eq!(sasl.external_authz_id(), Some("dn:cn=admin,dc=example,dc=com"));
+    /// }
+    /// ```
+    ///
+    /// [RFC4422]: https://tools.ietf.org/html/rfc4422
+    pub fn external_authz_id(&self) -> Option<&str> {
+        if self.mechanism.0 != "EXTERNAL" {
+            return None;
+        }
+        std::str::from_utf8(self.credentials.as_deref()?).ok()
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AuthenticationChoice<'a> {
     Simple(Cow<'a, [u8]>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     Sasl(SaslCredentials<'a>),
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BindResponse<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub result: LdapResult<'a>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub server_sasl_creds: Option<Cow<'a, [u8]>>,
 }
 
+impl BindResponse<'_> {
+    /// Test if the response contains SASL credentials
+    pub fn has_sasl_creds(&self) -> bool {
+        self.server_sasl_creds.is_some()
+    }
+
+    /// Get the SASL credentials sent by the server, if present
+    pub fn sasl_creds(&self) -> Option<&[u8]> {
+        self.server_sasl_creds.as_deref()
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SearchRequest<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub base_object: LdapDN<'a>,
     pub scope: SearchScope,
     pub deref_aliases: DerefAliases,
     pub size_limit: u32,
     pub time_limit: u32,
     pub types_only: bool,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub filter: Filter<'a>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub attributes: Vec<LdapString<'a>>,
 }
 
+/// An empty base object, scope `baseObject`, `neverDerefAliases`, no size and time limits,
+/// `typesOnly` false, filter `(objectClass=*)`, and no attributes
+///
+/// Unlike [`SearchRequestBuilder`](crate::builder::SearchRequestBuilder), the default scope is
+/// `baseObject`.
+impl Default for SearchRequest<'_> {
+    fn default() -> Self {
+        SearchRequest {
+            base_object: LdapDN(Cow::Borrowed("")),
+            scope: SearchScope::BaseObject,
+            deref_aliases: DerefAliases::NeverDerefAliases,
+            size_limit: 0,
+            time_limit: 0,
+            types_only: false,
+            filter: Filter::Present(LdapString(Cow::Borrowed("objectClass"))),
+            attributes: Vec::new(),
+        }
+    }
+}
+
+impl SearchRequest<'_> {
+    /// Return `true` if only attribute types are requested (`typesOnly`)
+    ///
+    /// Entries returned by such a search contain attributes without values (see
+    /// [`SearchResultEntry::is_types_only_shaped`]).
+    pub fn is_types_only(&self) -> bool {
+        self.types_only
+    }
+
+    /// Check that the scope and the alias dereferencing values are defined in RFC4511
+    ///
+    /// The parser does not reject undefined values, so this can be used for strict conformance
+    /// checks.
+    pub fn validate(&self) -> std::result::Result<(), LdapError> {
+        if !self.scope.is_valid() {
+            return Err(LdapError::InvalidSearchScope);
+        }
+        if !self.deref_aliases.is_valid() {
+            return Err(LdapError::InvalidDerefAliases);
+        }
+        Ok(())
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SearchResultEntry<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub object_name: LdapDN<'a>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub attributes: Vec<PartialAttribute<'a>>,
 }
 
+impl<'a> SearchResultEntry<'a> {
+    /// Get the attribute with type `name`
+    ///
+    /// Attribute types are compared ignoring ASCII case. If the entry contains the same type
+    /// more than once (which is invalid), the first one is returned.
+    pub fn attribute(&self, name: &str) -> Option<&PartialAttribute<'a>> {
+        self.attributes
+            .iter()
+            .find(|attr| attr.attr_type.0.eq_ignore_ascii_case(name))
+    }
+
+    /// Return `true` if no attribute of the entry has values
+    ///
+    /// This is the shape of entries returned by a search with `typesOnly` set: an empty set of
+    /// values means that the attribute is present in the entry, not that it has no data. Since
+    /// entries do not record how they were requested, this is only a hint, and entries without
+    /// attributes also return `true`.
+    pub fn is_types_only_shaped(&self) -> bool {
+        self.attributes.iter().all(|attr| attr.attr_vals.is_empty())
+    }
+
+    /// Get the values of all attributes, indexed by attribute type
+    ///
+    /// Keys are the attribute types as they appear in the entry (case is not normalized). If the
+    /// entry contains the same type more than once (which is invalid), the first one is kept.
+    pub fn attributes_map(&self) -> HashMap<&str, &[AttributeValue<'a>]> {
+        let mut map = HashMap::with_capacity(self.attributes.len());
+        for attr in &self.attributes {
+            map.entry(attr.attr_type.0.as_ref())
+                .or_insert_with(|| attr.attr_vals.as_slice());
+        }
+        map
+    }
+}
+
+/// A borrowed view of an entry: a DN and its attributes
+///
+/// This gives the same interface to the entries of an [`AddRequest`] (with [`Attribute`]s) and a
+/// [`SearchResultEntry`] (with [`PartialAttribute`]s).
+///
+/// ```rust
+/// use ldap_parser::ldap::{EntryView, SearchResultEntry};
+/// use ldap_parser::FromBer;
+///
+/// static DATA: &[u8] = include_bytes!("../assets/search_result_entry.bin");
+///
+/// let (_, entry) = SearchResultEntry::from_ber(DATA).expect("parsing failed");
+/// let view = EntryView::from(&entry);
+/// for (attr_type, values) in view.attributes() {
+///     println!("{}: {} value(s)", attr_type, values.len());
+/// }
+/// # assert_eq!(view.attributes().count(), 1);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct EntryView<'a> {
+    dn: &'a LdapDN<'a>,
+    attributes: EntryAttributes<'a>,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum EntryAttributes<'a> {
+    Attributes(&'a [Attribute<'a>]),
+    PartialAttributes(&'a [PartialAttribute<'a>]),
+}
+
+impl<'a> EntryView<'a> {
+    /// Get the DN of the entry
+    pub fn dn(&self) -> &'a LdapDN<'a> {
+        self.dn
+    }
+
+    /// Iterate over the attributes of the entry, as `(type, values)` pairs
+    pub fn attributes(&self) -> impl Iterator<Item = (&'a str, &'a [AttributeValue<'a>])> + 'a {
+        let (attributes, partial_attributes): (&[Attribute], &[PartialAttribute]) =
+            match self.attributes {
+                EntryAttributes::Attributes(a) => (a, &[]),
+                EntryAttributes::PartialAttributes(p) => (&[], p),
+            };
+        attributes
+            .iter()
+            .map(|a| (a.attr_type.0.as_ref(), a.attr_vals.as_slice()))
+            .chain(
+                partial_attributes
+                    .iter()
+                    .map(|a| (a.attr_type.0.as_ref(), a.attr_vals.as_slice())),
+            )
+    }
+}
+
+impl<'a> From<&'a AddRequest<'a>> for EntryView<'a> {
+    fn from(req: &'a AddRequest<'a>) -> Self {
+        EntryView {
+            dn: &req.entry,
+            attributes: EntryAttributes::Attributes(&req.attributes),
+        }
+    }
+}
+
+impl<'a> From<&'a SearchResultEntry<'a>> for EntryView<'a> {
+    fn from(entry: &'a SearchResultEntry<'a>) -> Self {
+        EntryView {
+            dn: &entry.object_name,
+            attributes: EntryAttributes::PartialAttributes(&entry.attributes),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ModifyRequest<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub object: LdapDN<'a>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub changes: Vec<Change<'a>>,
 }
 
+/// An empty object name, and no changes
+impl Default for ModifyRequest<'_> {
+    fn default() -> Self {
+        ModifyRequest {
+            object: LdapDN(Cow::Borrowed("")),
+            changes: Vec::new(),
+        }
+    }
+}
+
+impl<'a> ModifyRequest<'a> {
+    /// Iterate over the changes with operation `op`, in the order of the request
+    ///
+    /// Note that changes must be applied in order ([RFC4511] section 4.6), so grouping changes
+    /// by operation may give a different result if they affect the same attributes.
+    ///
+    /// [RFC4511]: https://tools.ietf.org/html/rfc4511
+    pub fn changes_with(&self, op: Operation) -> impl Iterator<Item = &Change<'a>> {
+        self.changes
+            .iter()
+            .filter(move |change| change.operation == op)
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ModifyResponse<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub result: LdapResult<'a>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Change<'a> {
     pub operation: Operation,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub modification: PartialAttribute<'a>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AddRequest<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub entry: LdapDN<'a>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub attributes: Vec<Attribute<'a>>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ModDnRequest<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub entry: LdapDN<'a>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub newrdn: RelativeLdapDN<'a>,
     pub deleteoldrdn: bool,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub newsuperior: Option<LdapDN<'a>>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CompareRequest<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub entry: LdapDN<'a>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub ava: AttributeValueAssertion<'a>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ExtendedRequest<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub request_name: LdapOID<'a>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub request_value: Option<Cow<'a, [u8]>>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ExtendedResponse<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub result: LdapResult<'a>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub response_name: Option<LdapOID<'a>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub response_value: Option<Cow<'a, [u8]>>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct IntermediateResponse<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub response_name: Option<LdapOID<'a>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub response_value: Option<Cow<'a, [u8]>>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ProtocolOp<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     BindRequest(BindRequest<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     BindResponse(BindResponse<'a>),
     UnbindRequest,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     SearchRequest(SearchRequest<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     SearchResultEntry(SearchResultEntry<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     SearchResultDone(LdapResult<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     SearchResultReference(Vec<LdapString<'a>>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     ModifyRequest(ModifyRequest<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     ModifyResponse(ModifyResponse<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     AddRequest(AddRequest<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     AddResponse(LdapResult<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     DelRequest(LdapDN<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     DelResponse(LdapResult<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     ModDnRequest(ModDnRequest<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     ModDnResponse(LdapResult<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     CompareRequest(CompareRequest<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     CompareResponse(LdapResult<'a>),
     //
     AbandonRequest(MessageID),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     ExtendedRequest(ExtendedRequest<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     ExtendedResponse(ExtendedResponse<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     IntermediateResponse(IntermediateResponse<'a>),
+    /// An operation with an unknown tag
+    ///
+    /// The protocolOp CHOICE is extensible, so operations with an unknown tag are not an error.
+    /// The raw content of the application-tagged element is stored in `data`.
+    Unknown {
+        tag: u32,
+        data: Cow<'a, [u8]>,
+    },
 }
 
 impl ProtocolOp<'_> {
@@ -296,12 +860,13 @@ impl ProtocolOp<'_> {
             ProtocolOp::ExtendedRequest(_) => 23,
             ProtocolOp::ExtendedResponse(_) => 24,
             ProtocolOp::IntermediateResponse(_) => 25,
+            ProtocolOp::Unknown { tag, .. } => *tag,
         };
         ProtocolOpTag(op)
     }
 
     /// Get the LDAP result, if present
-    pub fn result(&self) -> Option<&LdapResult> {
+    pub fn result(&self) -> Option<&LdapResult<'_>> {
         match self {
             ProtocolOp::BindResponse(r) => Some(&r.result),
             ProtocolOp::ModifyResponse(r) => Some(&r.result),
@@ -314,12 +879,83 @@ impl ProtocolOp<'_> {
             _ => None,
         }
     }
+
+    /// Test if the operation is a request (sent by the client)
+    pub fn is_request(&self) -> bool {
+        match self {
+            ProtocolOp::BindRequest(_)
+            | ProtocolOp::UnbindRequest
+            | ProtocolOp::SearchRequest(_)
+            | ProtocolOp::ModifyRequest(_)
+            | ProtocolOp::AddRequest(_)
+            | ProtocolOp::DelRequest(_)
+            | ProtocolOp::ModDnRequest(_)
+            | ProtocolOp::CompareRequest(_)
+            | ProtocolOp::AbandonRequest(_)
+            | ProtocolOp::ExtendedRequest(_) => true,
+            ProtocolOp::BindResponse(_)
+            | ProtocolOp::SearchResultEntry(_)
+            | ProtocolOp::SearchResultDone(_)
+            | ProtocolOp::SearchResultReference(_)
+            | ProtocolOp::ModifyResponse(_)
+            | ProtocolOp::AddResponse(_)
+            | ProtocolOp::DelResponse(_)
+            | ProtocolOp::ModDnResponse(_)
+            | ProtocolOp::CompareResponse(_)
+            | ProtocolOp::ExtendedResponse(_)
+            | ProtocolOp::IntermediateResponse(_) => false,
+            ProtocolOp::Unknown { .. } => false,
+        }
+    }
+
+    /// Test if the operation is a response (sent by the server)
+    ///
+    /// Unknown operations are neither requests nor responses.
+    pub fn is_response(&self) -> bool {
+        match self {
+            ProtocolOp::Unknown { .. } => false,
+            _ => !self.is_request(),
+        }
+    }
+}
+
+// Operations whose type is not shared by other variants (results, DNs and message IDs are
+// ambiguous and are not converted)
+macro_rules! impl_from_for_protocol_op {
+    ($($t:ident),*) => {
+        $(
+            impl<'a> From<$t<'a>> for ProtocolOp<'a> {
+                fn from(op: $t<'a>) -> Self {
+                    ProtocolOp::$t(op)
+                }
+            }
+        )*
+    };
 }
 
+impl_from_for_protocol_op!(
+    BindRequest,
+    BindResponse,
+    SearchRequest,
+    SearchResultEntry,
+    ModifyRequest,
+    ModifyResponse,
+    AddRequest,
+    ModDnRequest,
+    CompareRequest,
+    ExtendedRequest,
+    ExtendedResponse,
+    IntermediateResponse
+);
+
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Control<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub control_type: LdapOID<'a>,
     pub criticality: bool,
+    /// Value of the control (`None` if absent, which is different from an empty value)
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub control_value: Option<Cow<'a, [u8]>>,
 }
 
@@ -379,6 +1015,7 @@ pub struct Control<'a> {
 /// # }
 /// ```
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LdapMessage<'a> {
     /// Message Identifier (32-bits unsigned integer)
     ///
@@ -387,12 +1024,14 @@ pub struct LdapMessage<'a> {
     /// unsolicited notification message.
     pub message_id: MessageID,
     /// The LDAP operation from this LDAP message
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub protocol_op: ProtocolOp<'a>,
     /// Message controls (optional)
     ///
     /// Controls provide a mechanism whereby the semantics and arguments of existing LDAP
     /// operations may be extended.  One or more controls may be attached to a single LDAP message.
     /// A control only affects the semantics of the message it is attached to.
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub controls: Option<Vec<Control<'a>>>,
 }
 
@@ -406,4 +1045,68 @@ impl<'a> LdapMessage<'a> {
     pub fn parse(i: &'a [u8]) -> Result<'a, LdapMessage<'a>> {
         Self::from_ber(i)
     }
+
+    /// Parse a single LDAP message, and also return the bytes it was decoded from
+    ///
+    /// On success, returns the remaining input and a tuple of the message and the raw bytes of
+    /// the message (the outer `SEQUENCE`, including its tag and length).
+    pub fn from_ber_with_raw(i: &'a [u8]) -> Result<'a, (LdapMessage<'a>, &'a [u8])> {
+        let (rem, msg) = Self::from_ber(i)?;
+        let raw = &i[..i.len() - rem.len()];
+        Ok((rem, (msg, raw)))
+    }
+
+    /// Get the first control of type `oid`, if present
+    pub fn control(&self, oid: &str) -> Option<&Control<'a>> {
+        self.controls_iter().find(|c| c.control_type.0 == oid)
+    }
+
+    /// Iterate over the controls of the message (empty if the message has no controls)
+    pub fn controls_iter(&self) -> impl Iterator<Item = &Control<'a>> {
+        self.controls.iter().flatten()
+    }
+
+    /// Get the referral URIs, if the result code of the operation is `referral`
+    ///
+    /// Returns `None` if the operation has no result, if the result code is not `referral`, or
+    /// if the referral field is absent.
+    pub fn referral(&self) -> Option<&[LdapString<'_>]> {
+        let result = self.protocol_op.result()?;
+        if !result.result_code.is_referral() {
+            return None;
+        }
+        result.referral.as_deref()
+    }
+}
+
+/// Collect the URIs of all search result references and referrals in `messages`
+///
+/// URIs are returned in the order of the messages. Duplicates are not removed.
+pub fn collect_referrals<'a>(messages: &'a [LdapMessage]) -> Vec<&'a LdapString<'a>> {
+    let mut uris = Vec::new();
+    for msg in messages {
+        match &msg.protocol_op {
+            ProtocolOp::SearchResultReference(refs) => uris.extend(refs),
+            op => {
+                if let Some(referral) = op.result().and_then(|r| r.referral.as_ref()) {
+                    uris.extend(referral);
+                }
+            }
+        }
+    }
+    uris
+}
+
+impl LdapMessage<'static> {
+    /// Parse a single LDAP message, and return a message owning its data
+    ///
+    /// The input must contain exactly one message: trailing bytes are rejected with
+    /// [`LdapError::TrailingData`].
+    pub fn parse_owned(i: &[u8]) -> std::result::Result<LdapMessage<'static>, LdapError> {
+        let (rem, msg) = LdapMessage::from_ber(i)?;
+        if !rem.is_empty() {
+            return Err(LdapError::TrailingData);
+        }
+        Ok(msg.to_static())
+    }
 }
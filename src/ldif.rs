@@ -0,0 +1,220 @@
+//! LDIF representation of entries and values ([RFC2849])
+//!
+//! [RFC2849]: https://tools.ietf.org/html/rfc2849
+
+use crate::filter::AttributeValue;
+use crate::ldap::SearchResultEntry;
+
+/// Maximum length of LDIF lines, before folding
+const LDIF_LINE_LENGTH: usize = 76;
+
+impl SearchResultEntry<'_> {
+    /// Get the LDIF representation of the entry ([RFC2849])
+    ///
+    /// The result contains a `dn:` line, followed by one line per attribute value. The DN and
+    /// values which are not a `SAFE-STRING` (for ex. binary values, or non-ASCII text) are
+    /// encoded in base64 (`dn::` or `attr::`). Lines longer than 76 characters are folded.
+    /// Each line, including the last one, ends with a newline.
+    ///
+    /// ```rust
+    /// use ldap_parser::filter::{AttributeValue, PartialAttribute};
+    /// use ldap_parser::ldap::{LdapDN, LdapString, SearchResultEntry};
+    /// use std::borrow::Cow;
+    ///
+    /// let entry = SearchResultEntry {
+    ///     object_name: LdapDN(Cow::Borrowed("cn=John Smith,dc=example,dc=net")),
+    ///     attributes: vec![PartialAttribute {
+    ///         attr_type: LdapString(Cow::Borrowed("jpegPhoto")),
+    ///         attr_vals: vec![AttributeValue(Cow::Borrowed(&[0xff, 0xd8, 0xff, 0xe0]))],
+    ///     }],
+    /// };
+    /// assert_eq!(
+    ///     entry.to_ldif(),
+    ///     "dn: cn=John Smith,dc=example,dc=net\njpegPhoto:: /9j/4A==\n"
+    /// );
+    /// ```
+    ///
+    /// [RFC2849]: https://tools.ietf.org/html/rfc2849
+    pub fn to_ldif(&self) -> String {
+        let mut s = String::new();
+        push_ldif_line(&mut s, "dn", self.object_name.0.as_bytes());
+        for attr in &self.attributes {
+            for value in &attr.attr_vals {
+                push_ldif_line(&mut s, &attr.attr_type.0, &value.0);
+            }
+        }
+        s
+    }
+}
+
+// attrval-spec = AttributeDescription value-spec SEP
+// value-spec   = ":" ( FILL 0*1(SAFE-STRING) / ":" FILL (BASE64-STRING) / "<" FILL url)
+fn push_ldif_line(s: &mut String, name: &str, value: &[u8]) {
+    let line = if value.is_empty() {
+        format!("{}:", name)
+    } else if is_safe_string(value) {
+        // SAFE-STRING is ASCII
+        format!("{}: {}", name, String::from_utf8_lossy(value))
+    } else {
+        format!("{}:: {}", name, base64_encode(value))
+    };
+    // folded lines are continued on the next line, starting with a space
+    let mut remaining = line.as_str();
+    let mut max_len = LDIF_LINE_LENGTH;
+    loop {
+        let mut end = remaining.len().min(max_len);
+        while !remaining.is_char_boundary(end) {
+            end -= 1;
+        }
+        s.push_str(&remaining[..end]);
+        s.push('\n');
+        remaining = &remaining[end..];
+        if remaining.is_empty() {
+            break;
+        }
+        s.push(' ');
+        max_len = LDIF_LINE_LENGTH - 1;
+    }
+}
+
+impl AttributeValue<'_> {
+    /// Get a printable representation of the value
+    ///
+    /// Values that are valid UTF-8 are returned as-is. Other values are encoded in base64 and
+    /// prefixed with `base64:`.
+    ///
+    /// ```rust
+    /// use ldap_parser::filter::AttributeValue;
+    /// use std::borrow::Cow;
+    ///
+    /// let v = AttributeValue(Cow::Borrowed(b"John Smith"));
+    /// assert_eq!(v.to_display_string(), "John Smith");
+    /// let v = AttributeValue(Cow::Borrowed(&[0xff, 0xd8, 0xff, 0xe0]));
+    /// assert_eq!(v.to_display_string(), "base64:/9j/4A==");
+    /// ```
+    pub fn to_display_string(&self) -> String {
+        match self.as_str() {
+            Some(s) => s.to_owned(),
+            None => format!("base64:{}", base64_encode(&self.0)),
+        }
+    }
+
+    /// Get a printable representation of the value, using the rules of LDIF ([RFC2849])
+    ///
+    /// Values are returned as-is only if they are a `SAFE-STRING` (printable ASCII, not starting
+    /// with a space, `:` or `<`, and not ending with a space). Other values, including non-ASCII
+    /// UTF-8 text, are encoded in base64 and prefixed with `base64:`.
+    ///
+    /// [RFC2849]: https://tools.ietf.org/html/rfc2849
+    pub fn to_ldif_display_string(&self) -> String {
+        if is_safe_string(&self.0) {
+            // SAFE-STRING is ASCII
+            String::from_utf8_lossy(&self.0).into_owned()
+        } else {
+            format!("base64:{}", base64_encode(&self.0))
+        }
+    }
+}
+
+// SAFE-CHAR      = %x01-09 / %x0B-0C / %x0E-7F
+// SAFE-INIT-CHAR = %x01-09 / %x0B-0C / %x0E-1F / %x21-39 / %x3B / %x3D-7F
+// SAFE-STRING    = [SAFE-INIT-CHAR *SAFE-CHAR]
+//
+// Values ending with a space are also rejected, as recommended by RFC2849 (note 8)
+pub(crate) fn is_safe_string(b: &[u8]) -> bool {
+    let is_safe_char = |c: &u8| !matches!(c, 0 | b'\n' | b'\r' | 0x80..=0xff);
+    match b.first() {
+        None => true,
+        Some(b' ') | Some(b':') | Some(b'<') => false,
+        Some(_) => b.iter().all(is_safe_char) && b.last() != Some(&b' '),
+    }
+}
+
+pub(crate) fn base64_encode(b: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut s = String::with_capacity(4 * (b.len() / 3 + 1));
+    for chunk in b.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (idx, &c)| n | (u32::from(c) << (16 - 8 * idx)));
+        for idx in 0..4 {
+            if idx <= chunk.len() {
+                s.push(ALPHABET[((n >> (18 - 6 * idx)) & 0x3f) as usize] as char);
+            } else {
+                s.push('=');
+            }
+        }
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::borrow::Cow;
+
+    #[test]
+    fn test_base64_encode() {
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"f"), "Zg==");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_encode(b"foo"), "Zm9v");
+        assert_eq!(base64_encode(b"foobar"), "Zm9vYmFy");
+    }
+
+    #[test]
+    fn test_value_display_string() {
+        let value = |b: &'static [u8]| AttributeValue(Cow::Borrowed(b));
+        assert_eq!(value(b"").to_display_string(), "");
+        assert_eq!(value("Lučić".as_bytes()).to_display_string(), "Lučić");
+        assert_eq!(
+            value("Lučić".as_bytes()).to_ldif_display_string(),
+            "base64:THXEjWnEhw=="
+        );
+        assert_eq!(value(b"John").to_ldif_display_string(), "John");
+        assert_eq!(value(b"").to_ldif_display_string(), "");
+        assert_eq!(value(b":a").to_ldif_display_string(), "base64:OmE=");
+        assert_eq!(value(b"a ").to_ldif_display_string(), "base64:YSA=");
+        assert_eq!(value(b"a\nb").to_ldif_display_string(), "base64:YQpi");
+        assert_eq!(value(b"\xff").to_display_string(), "base64:/w==");
+    }
+
+    #[test]
+    fn test_entry_to_ldif() {
+        use crate::filter::PartialAttribute;
+        use crate::ldap::{LdapDN, LdapString};
+
+        let attribute = |name: &'static str, values: &[&'static [u8]]| PartialAttribute {
+            attr_type: LdapString(Cow::Borrowed(name)),
+            attr_vals: values
+                .iter()
+                .map(|v| AttributeValue(Cow::Borrowed(*v)))
+                .collect(),
+        };
+        const LONG_VALUE: &[u8] = &[b'a'; 100];
+        let entry = SearchResultEntry {
+            object_name: LdapDN(Cow::Borrowed("cn=Lučić,dc=net")),
+            attributes: vec![
+                attribute("objectClass", &[b"top", b"person"]),
+                attribute("description", &[b""]),
+                attribute("seeAlso", &[LONG_VALUE]),
+            ],
+        };
+        let ldif = entry.to_ldif();
+        let lines: Vec<&str> = ldif.lines().collect();
+        assert_eq!(lines[0], "dn:: Y249THXEjWnEhyxkYz1uZXQ=");
+        assert_eq!(lines[1], "objectClass: top");
+        assert_eq!(lines[2], "objectClass: person");
+        assert_eq!(lines[3], "description:");
+        assert_eq!(lines[4].len(), 76);
+        assert!(lines[4].starts_with("seeAlso: aaa"));
+        assert_eq!(lines[5], format!(" {}", "a".repeat(100 - 67)));
+        assert_eq!(lines.len(), 6);
+        assert!(ldif.ends_with('\n'));
+        // unfolding gives the original line
+        assert!(ldif
+            .replace("\n ", "")
+            .ends_with(&format!("\nseeAlso: {}\n", "a".repeat(100))));
+    }
+}
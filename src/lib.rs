@@ -47,6 +47,13 @@
 //! # }
 //! ```
 //!
+//! # Features
+//!
+//! - `serde`: implement `Serialize` and `Deserialize` for LDAP messages and their fields.
+//!   Binary fields are serialized as byte arrays. Deserialized values own their data.
+//! - `codec`: provide a `tokio_util` decoder, to read LDAP messages from asynchronous streams.
+//! - `sasl`: provide helpers to decode the credentials of some SASL mechanisms (DIGEST-MD5).
+//!
 //! [RFC4511]: https://tools.ietf.org/html/rfc4511
 
 #![deny(/*missing_docs,*/
@@ -66,14 +73,36 @@
 ))]
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+pub mod builder;
+#[cfg(feature = "codec")]
+#[cfg_attr(docsrs, doc(cfg(feature = "codec")))]
+pub mod codec;
+pub mod controls;
+mod der;
+mod dn;
 pub mod error;
+pub mod extended;
 pub mod filter;
 mod filter_parser;
+mod filter_string;
 pub mod ldap;
+mod ldif;
+pub mod oids;
 mod parser;
+#[cfg(feature = "sasl")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sasl")))]
+pub mod sasl;
+pub mod search;
+pub mod stats;
+mod to_static;
+pub mod url;
 
+pub use filter_string::parse_filter_str;
 pub use parser::*;
+pub use url::parse_ldap_url;
 
 pub use asn1_rs;
 pub use asn1_rs::nom::{Err, IResult};
 pub use asn1_rs::FromBer;
+pub use asn1_rs::FromDer;
+pub use asn1_rs::ToStatic;
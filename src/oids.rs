@@ -0,0 +1,299 @@
+//! Object identifiers of well-known LDAP extended operations and controls
+//!
+//! The `OID_*` constants of the [`controls`](crate::controls) and
+//! [`extended`](crate::extended) modules are aliases of these values.
+
+// Extended operations
+
+/// Password Modify extended operation ([RFC3062])
+///
+/// [RFC3062]: https://tools.ietf.org/html/rfc3062
+pub const PASSWORD_MODIFY: &str = "1.3.6.1.4.1.4203.1.11.1";
+/// Cancel extended operation ([RFC3909])
+///
+/// [RFC3909]: https://tools.ietf.org/html/rfc3909
+pub const CANCEL: &str = "1.3.6.1.1.8";
+/// StartTLS extended operation ([RFC4511] section 4.14)
+///
+/// [RFC4511]: https://tools.ietf.org/html/rfc4511
+pub const START_TLS: &str = "1.3.6.1.4.1.1466.20037";
+/// "Who am I?" extended operation ([RFC4532])
+///
+/// [RFC4532]: https://tools.ietf.org/html/rfc4532
+pub const WHOAMI: &str = "1.3.6.1.4.1.4203.1.11.3";
+/// Sync Info message of the Content Synchronization operation ([RFC4533])
+///
+/// [RFC4533]: https://tools.ietf.org/html/rfc4533
+pub const SYNC_INFO: &str = "1.3.6.1.4.1.4203.1.9.1.4";
+/// Notice of Disconnection unsolicited notification ([RFC4511] section 4.4.1)
+///
+/// [RFC4511]: https://tools.ietf.org/html/rfc4511
+pub const NOTICE_OF_DISCONNECTION: &str = "1.3.6.1.4.1.1466.20036";
+/// Start Transaction extended operation ([RFC5805])
+///
+/// [RFC5805]: https://tools.ietf.org/html/rfc5805
+pub const START_TRANSACTION: &str = "1.3.6.1.1.21.1";
+/// End Transaction extended operation ([RFC5805])
+///
+/// [RFC5805]: https://tools.ietf.org/html/rfc5805
+pub const END_TRANSACTION: &str = "1.3.6.1.1.21.3";
+
+// Controls
+
+/// Simple Paged Results control ([RFC2696])
+///
+/// [RFC2696]: https://tools.ietf.org/html/rfc2696
+pub const PAGED_RESULTS: &str = "1.2.840.113556.1.4.319";
+/// Server Side Sort request control ([RFC2891])
+///
+/// [RFC2891]: https://tools.ietf.org/html/rfc2891
+pub const SERVER_SORT_REQUEST: &str = "1.2.840.113556.1.4.473";
+/// Server Side Sort response control ([RFC2891])
+///
+/// [RFC2891]: https://tools.ietf.org/html/rfc2891
+pub const SERVER_SORT_RESPONSE: &str = "1.2.840.113556.1.4.474";
+/// Virtual List View request control ([draft-ietf-ldapext-ldapv3-vlv])
+///
+/// [draft-ietf-ldapext-ldapv3-vlv]: https://tools.ietf.org/html/draft-ietf-ldapext-ldapv3-vlv-09
+pub const VLV_REQUEST: &str = "2.16.840.1.113730.3.4.9";
+/// Virtual List View response control ([draft-ietf-ldapext-ldapv3-vlv])
+///
+/// [draft-ietf-ldapext-ldapv3-vlv]: https://tools.ietf.org/html/draft-ietf-ldapext-ldapv3-vlv-09
+pub const VLV_RESPONSE: &str = "2.16.840.1.113730.3.4.10";
+/// Sync Request control ([RFC4533])
+///
+/// [RFC4533]: https://tools.ietf.org/html/rfc4533
+pub const SYNC_REQUEST: &str = "1.3.6.1.4.1.4203.1.9.1.1";
+/// Sync State control ([RFC4533])
+///
+/// [RFC4533]: https://tools.ietf.org/html/rfc4533
+pub const SYNC_STATE: &str = "1.3.6.1.4.1.4203.1.9.1.2";
+/// Sync Done control ([RFC4533])
+///
+/// [RFC4533]: https://tools.ietf.org/html/rfc4533
+pub const SYNC_DONE: &str = "1.3.6.1.4.1.4203.1.9.1.3";
+/// Password Policy control ([draft-behera-ldap-password-policy])
+///
+/// [draft-behera-ldap-password-policy]: https://tools.ietf.org/html/draft-behera-ldap-password-policy-11
+pub const PASSWORD_POLICY: &str = "1.3.6.1.4.1.42.2.27.8.5.1";
+/// ManageDsaIT control ([RFC3296])
+///
+/// [RFC3296]: https://tools.ietf.org/html/rfc3296
+pub const MANAGE_DSA_IT: &str = "2.16.840.1.113730.3.4.2";
+/// Assertion control ([RFC4528])
+///
+/// [RFC4528]: https://tools.ietf.org/html/rfc4528
+pub const ASSERTION: &str = "1.3.6.1.1.12";
+/// Pre-Read control ([RFC4527])
+///
+/// [RFC4527]: https://tools.ietf.org/html/rfc4527
+pub const PRE_READ: &str = "1.3.6.1.1.13.1";
+/// Post-Read control ([RFC4527])
+///
+/// [RFC4527]: https://tools.ietf.org/html/rfc4527
+pub const POST_READ: &str = "1.3.6.1.1.13.2";
+/// Proxied Authorization control ([RFC4370])
+///
+/// [RFC4370]: https://tools.ietf.org/html/rfc4370
+pub const PROXY_AUTHZ: &str = "2.16.840.1.113730.3.4.18";
+/// Active Directory DirSync control ([MS-ADTS])
+///
+/// [MS-ADTS]: https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-adts/2213a7f2-0a36-483c-b2a4-8574d53aa1e3
+pub const DIRSYNC: &str = "1.2.840.113556.1.4.841";
+/// Persistent Search control ([draft-ietf-ldapext-psearch])
+///
+/// [draft-ietf-ldapext-psearch]: https://tools.ietf.org/html/draft-ietf-ldapext-psearch-03
+pub const PERSISTENT_SEARCH: &str = "2.16.840.1.113730.3.4.3";
+/// Entry Change Notification control ([draft-ietf-ldapext-psearch])
+///
+/// [draft-ietf-ldapext-psearch]: https://tools.ietf.org/html/draft-ietf-ldapext-psearch-03
+pub const ENTRY_CHANGE_NOTIFICATION: &str = "2.16.840.1.113730.3.4.7";
+/// Matched Values control ([RFC3876])
+///
+/// [RFC3876]: https://tools.ietf.org/html/rfc3876
+pub const MATCHED_VALUES: &str = "1.2.826.0.1.3344810.2.3";
+/// Authorization Identity request control ([RFC3829])
+///
+/// [RFC3829]: https://tools.ietf.org/html/rfc3829
+pub const AUTHZ_ID_REQUEST: &str = "2.16.840.1.113730.3.4.16";
+/// Authorization Identity response control ([RFC3829])
+///
+/// [RFC3829]: https://tools.ietf.org/html/rfc3829
+pub const AUTHZ_ID_RESPONSE: &str = "2.16.840.1.113730.3.4.15";
+/// Subentries control ([RFC3672])
+///
+/// [RFC3672]: https://tools.ietf.org/html/rfc3672
+pub const SUBENTRIES: &str = "1.3.6.1.4.1.4203.1.10.1";
+/// Don't Use Copy control ([RFC6171])
+///
+/// [RFC6171]: https://tools.ietf.org/html/rfc6171
+pub const DONT_USE_COPY: &str = "1.3.6.1.1.22";
+/// No-Op control ([draft-zeilenga-ldap-noop])
+///
+/// [draft-zeilenga-ldap-noop]: https://tools.ietf.org/html/draft-zeilenga-ldap-noop-01
+pub const NO_OP: &str = "1.3.6.1.4.1.4203.1.10.2";
+/// Get Effective Rights control ([draft-ietf-ldapext-acl-model])
+///
+/// [draft-ietf-ldapext-acl-model]: https://tools.ietf.org/html/draft-ietf-ldapext-acl-model-08
+pub const GET_EFFECTIVE_RIGHTS: &str = "1.3.6.1.4.1.42.2.27.9.5.2";
+/// Account Usable control (Sun Directory Server)
+pub const ACCOUNT_USABLE: &str = "1.3.6.1.4.1.42.2.27.9.5.8";
+/// Transaction Specification control ([RFC5805])
+///
+/// [RFC5805]: https://tools.ietf.org/html/rfc5805
+pub const TRANSACTION_SPECIFICATION: &str = "1.3.6.1.1.21.2";
+/// Active Directory Permissive Modify control ([MS-ADTS])
+///
+/// [MS-ADTS]: https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-adts/
+pub const PERMISSIVE_MODIFY: &str = "1.2.840.113556.1.4.1413";
+/// Active Directory Show Deleted control ([MS-ADTS])
+///
+/// [MS-ADTS]: https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-adts/
+pub const SHOW_DELETED: &str = "1.2.840.113556.1.4.417";
+
+/// Get the name of a well-known extended operation, or intermediate response
+///
+/// ```rust
+/// use ldap_parser::oids;
+///
+/// assert_eq!(oids::extended_op_name(oids::PASSWORD_MODIFY), Some("Password Modify"));
+/// assert_eq!(oids::extended_op_name("1.2.3.4"), None);
+/// ```
+pub fn extended_op_name(oid: &str) -> Option<&'static str> {
+    let name = match oid {
+        PASSWORD_MODIFY => "Password Modify",
+        CANCEL => "Cancel",
+        START_TLS => "StartTLS",
+        WHOAMI => "Who am I?",
+        SYNC_INFO => "Sync Info",
+        NOTICE_OF_DISCONNECTION => "Notice of Disconnection",
+        START_TRANSACTION => "Start Transaction",
+        END_TRANSACTION => "End Transaction",
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// Description of a well-known control
+///
+/// See [`describe`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ControlInfo {
+    /// Name of the control
+    pub name: &'static str,
+    /// Document defining the control (for ex. `RFC2696`)
+    pub rfc: &'static str,
+    /// Short description of the control value (`"none"` if the control has no value)
+    pub value_shape: &'static str,
+}
+
+/// Describe a well-known control
+///
+/// Controls are described even if the crate has no decoder for their value.
+///
+/// ```rust
+/// use ldap_parser::oids;
+///
+/// let info = oids::describe(oids::PAGED_RESULTS).expect("unknown control");
+/// assert_eq!(info.name, "Simple Paged Results");
+/// assert_eq!(info.rfc, "RFC2696");
+/// assert!(oids::describe("1.2.3.4").is_none());
+/// ```
+pub fn describe(oid: &str) -> Option<ControlInfo> {
+    let (name, rfc, value_shape) = match oid {
+        PAGED_RESULTS => (
+            "Simple Paged Results",
+            "RFC2696",
+            "SEQUENCE { size, cookie }",
+        ),
+        SERVER_SORT_REQUEST => ("Server Side Sort Request", "RFC2891", "SEQUENCE OF SortKey"),
+        SERVER_SORT_RESPONSE => (
+            "Server Side Sort Response",
+            "RFC2891",
+            "SEQUENCE { sortResult, attributeType }",
+        ),
+        VLV_REQUEST => (
+            "Virtual List View Request",
+            "draft-ietf-ldapext-ldapv3-vlv",
+            "SEQUENCE { beforeCount, afterCount, target, contextID }",
+        ),
+        VLV_RESPONSE => (
+            "Virtual List View Response",
+            "draft-ietf-ldapext-ldapv3-vlv",
+            "SEQUENCE { targetPosition, contentCount, result, contextID }",
+        ),
+        SYNC_REQUEST => (
+            "Sync Request",
+            "RFC4533",
+            "SEQUENCE { mode, cookie, reloadHint }",
+        ),
+        SYNC_STATE => (
+            "Sync State",
+            "RFC4533",
+            "SEQUENCE { state, entryUUID, cookie }",
+        ),
+        SYNC_DONE => (
+            "Sync Done",
+            "RFC4533",
+            "SEQUENCE { cookie, refreshDeletes }",
+        ),
+        PASSWORD_POLICY => (
+            "Password Policy",
+            "draft-behera-ldap-password-policy",
+            "none or SEQUENCE { warning, error }",
+        ),
+        MANAGE_DSA_IT => ("ManageDsaIT", "RFC3296", "none"),
+        ASSERTION => ("Assertion", "RFC4528", "Filter"),
+        PRE_READ => (
+            "Pre-Read",
+            "RFC4527",
+            "AttributeSelection or SearchResultEntry",
+        ),
+        POST_READ => (
+            "Post-Read",
+            "RFC4527",
+            "AttributeSelection or SearchResultEntry",
+        ),
+        PROXY_AUTHZ => ("Proxied Authorization", "RFC4370", "authzId"),
+        DIRSYNC => ("DirSync", "MS-ADTS", "SEQUENCE { flags, maxBytes, cookie }"),
+        PERSISTENT_SEARCH => (
+            "Persistent Search",
+            "draft-ietf-ldapext-psearch",
+            "SEQUENCE { changeTypes, changesOnly, returnECs }",
+        ),
+        ENTRY_CHANGE_NOTIFICATION => (
+            "Entry Change Notification",
+            "draft-ietf-ldapext-psearch",
+            "SEQUENCE { changeType, previousDN, changeNumber }",
+        ),
+        MATCHED_VALUES => ("Matched Values", "RFC3876", "SEQUENCE OF SimpleFilterItem"),
+        AUTHZ_ID_REQUEST => ("Authorization Identity Request", "RFC3829", "none"),
+        AUTHZ_ID_RESPONSE => ("Authorization Identity Response", "RFC3829", "authzId"),
+        SUBENTRIES => ("Subentries", "RFC3672", "BOOLEAN"),
+        DONT_USE_COPY => ("Don't Use Copy", "RFC6171", "none"),
+        NO_OP => ("No-Op", "draft-zeilenga-ldap-noop", "none"),
+        GET_EFFECTIVE_RIGHTS => (
+            "Get Effective Rights",
+            "draft-ietf-ldapext-acl-model",
+            "SEQUENCE { authzId, attributes }",
+        ),
+        ACCOUNT_USABLE => (
+            "Account Usable",
+            "Sun Directory Server",
+            "none or CHOICE { isAvailable, isNotAvailable }",
+        ),
+        TRANSACTION_SPECIFICATION => (
+            "Transaction Specification",
+            "RFC5805",
+            "transaction identifier",
+        ),
+        PERMISSIVE_MODIFY => ("Permissive Modify", "MS-ADTS", "none"),
+        SHOW_DELETED => ("Show Deleted", "MS-ADTS", "none"),
+        _ => return None,
+    };
+    Some(ControlInfo {
+        name,
+        rfc,
+        value_shape,
+    })
+}
@@ -7,48 +7,142 @@ use crate::filter::*;
 use crate::ldap::*;
 use asn1_rs::nom;
 use asn1_rs::{
-    Class, Enumerated, FromBer, Header, Implicit, OptTaggedParser, ParseResult, Sequence, Tag,
-    TaggedParser, TaggedValue,
+    Any, Class, Enumerated, FromBer, Header, Implicit, Length, OptTaggedParser, ParseResult,
+    Sequence, Tag, TaggedParser, TaggedValue,
 };
 use nom::bytes::streaming::take;
 use nom::combinator::{complete, map, opt, verify};
-use nom::multi::{many0, many1};
+use nom::error::ErrorKind;
+use nom::multi::{many0, many1, many_m_n};
+use nom::number::streaming::be_u32;
 use nom::Err;
 use std::borrow::Cow;
 
-// // maxInt INTEGER ::= 2147483647 -- (2^^31 - 1) --
-// const MAX_INT: u32 = 2_147_483_647;
-
 // MessageID ::= INTEGER (0 ..  maxInt)
 impl<'a> FromBer<'a, LdapError> for MessageID {
     fn from_ber(bytes: &'a [u8]) -> ParseResult<'a, Self, LdapError> {
-        map(u32::from_ber, MessageID)(bytes).map_err(Err::convert)
+        let (i, id) = parse_ldap_int_constrained(bytes).map_err(|e| {
+            e.map(|e| match e {
+                LdapError::IntegerOutOfRange => LdapError::InvalidMessageId,
+                e => e,
+            })
+        })?;
+        Ok((i, MessageID(id)))
     }
 }
 
+/// Check that all the content of an operation with a fixed list of fields has been parsed
+#[inline]
+fn check_no_trailing_data(i: &[u8]) -> std::result::Result<(), LdapError> {
+    if !i.is_empty() {
+        return Err(LdapError::TrailingData);
+    }
+    Ok(())
+}
+
+/// Count the elements of the content of a `SEQUENCE OF` or `SET OF`, to pre-allocate vectors
+///
+/// Only the headers are read. Counting stops at the first invalid element, so the result is only
+/// a hint.
+pub(crate) fn count_elements(mut i: &[u8]) -> usize {
+    let mut count = 0;
+    while let Ok((rem, header)) = Header::from_ber(i) {
+        match header.length() {
+            Length::Definite(len) if len <= rem.len() => i = &rem[len..],
+            _ => break,
+        }
+        count += 1;
+    }
+    count
+}
+
+/// Same as `many0(complete(f))`, with a vector pre-allocated using [`count_elements`]
+pub(crate) fn many0_presized<'a, T, F>(mut f: F) -> impl FnMut(&'a [u8]) -> Result<'a, Vec<T>>
+where
+    F: FnMut(&'a [u8]) -> Result<'a, T>,
+{
+    move |mut i| {
+        let mut v = Vec::with_capacity(count_elements(i));
+        loop {
+            match f(i) {
+                Ok((rem, o)) => {
+                    // infinite loop check, as in many0
+                    if rem.len() == i.len() {
+                        return Err(Err::Error(LdapError::NomError(ErrorKind::Many0)));
+                    }
+                    v.push(o);
+                    i = rem;
+                }
+                Err(Err::Error(_)) | Err(Err::Incomplete(_)) => return Ok((i, v)),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+#[inline]
+fn check_message_id(id: u32) -> std::result::Result<(), LdapError> {
+    check_ldap_int(id).or(Err(LdapError::InvalidMessageId))
+}
+
+/// Check that an integer is in the range `(0 .. maxInt)`
+#[inline]
+fn check_ldap_int(v: u32) -> std::result::Result<(), LdapError> {
+    if v > LDAP_MAX_INT {
+        return Err(LdapError::IntegerOutOfRange);
+    }
+    Ok(())
+}
+
 // LDAPString ::= OCTET STRING -- UTF-8 encoded,
 //                             -- [ISO10646] characters
 impl<'a> FromBer<'a, LdapError> for LdapString<'a> {
     fn from_ber(bytes: &'a [u8]) -> ParseResult<'a, Self, LdapError> {
         let (i, b) = parse_ldap_octet_string_as_slice(bytes)?;
         // convert to UTF-8
-        let s = std::str::from_utf8(b).or(Err(Err::Error(LdapError::InvalidString)))?;
+        let s = parse_utf8(b, "LDAPString")?;
         Ok((i, LdapString(Cow::Borrowed(s))))
     }
 }
 
+/// Convert `b` to a string, recording the position of the first invalid UTF-8 sequence in errors
 #[inline]
-pub(crate) fn parse_ldap_octet_string_as_slice(i: &[u8]) -> Result<&[u8]> {
+pub(crate) fn parse_utf8<'a>(
+    b: &'a [u8],
+    field: &'static str,
+) -> std::result::Result<&'a str, Err<LdapError>> {
+    std::str::from_utf8(b).map_err(|e| {
+        Err::Error(LdapError::InvalidUtf8 {
+            field,
+            valid_up_to: e.valid_up_to(),
+        })
+    })
+}
+
+#[inline]
+pub(crate) fn parse_ldap_octet_string_as_slice(i: &[u8]) -> Result<'_, &[u8]> {
     <&[u8]>::from_ber(i).map_err(Err::convert)
 }
 
+/// Parse an INTEGER constrained to `(0 .. maxInt)`
+///
+/// Negative values and values larger than [`LDAP_MAX_INT`] return `LdapError::IntegerOutOfRange`.
 #[inline]
-fn parse_ldap_int_as_u32(i: &[u8]) -> Result<u32> {
-    <u32>::from_ber(i).map_err(Err::convert)
+pub(crate) fn parse_ldap_int_constrained(i: &[u8]) -> Result<'_, u32> {
+    let (i, v) = <u32>::from_ber(i).map_err(|e| {
+        e.map(|e| match e {
+            asn1_rs::Error::IntegerTooLarge | asn1_rs::Error::IntegerNegative => {
+                LdapError::IntegerOutOfRange
+            }
+            e => LdapError::Ber(e),
+        })
+    })?;
+    check_ldap_int(v)?;
+    Ok((i, v))
 }
 
 #[inline]
-fn parse_ldap_enum_as_u32(i: &[u8]) -> Result<u32> {
+fn parse_ldap_enum_as_u32(i: &[u8]) -> Result<'_, u32> {
     let (i, obj) = Enumerated::from_ber(i).map_err(Err::convert)?;
     Ok((i, obj.0))
 }
@@ -60,7 +154,7 @@ impl<'a> FromBer<'a, LdapError> for LdapDN<'a> {
         // read bytes
         let (i, b) = <&[u8]>::from_ber(bytes).map_err(Err::convert)?;
         // convert to UTF-8
-        let s = std::str::from_utf8(b).or(Err(Err::Error(LdapError::InvalidDN)))?;
+        let s = parse_utf8(b, "LDAPDN")?;
         Ok((i, LdapDN(Cow::Borrowed(s))))
     }
 }
@@ -72,7 +166,7 @@ impl<'a> FromBer<'a, LdapError> for RelativeLdapDN<'a> {
         // read bytes
         let (i, b) = <&[u8]>::from_ber(bytes).map_err(Err::convert)?;
         // convert to UTF-8
-        let s = std::str::from_utf8(b).or(Err(Err::Error(LdapError::InvalidDN)))?;
+        let s = parse_utf8(b, "RelativeLDAPDN")?;
         Ok((i, RelativeLdapDN(Cow::Borrowed(s))))
     }
 }
@@ -84,7 +178,7 @@ impl<'a> FromBer<'a, LdapError> for LdapOID<'a> {
         // read bytes
         let (i, b) = <&[u8]>::from_ber(bytes).map_err(Err::convert)?;
         // convert to UTF-8
-        let s = std::str::from_utf8(b).or(Err(Err::Error(LdapError::InvalidDN)))?;
+        let s = parse_utf8(b, "LDAPOID")?;
         Ok((i, LdapOID(Cow::Borrowed(s))))
     }
 }
@@ -92,7 +186,7 @@ impl<'a> FromBer<'a, LdapError> for LdapOID<'a> {
 // URI ::= LDAPString     -- limited to characters permitted in
 //                                -- URIs
 #[inline]
-fn parse_ldap_uri(i: &[u8]) -> Result<LdapString> {
+fn parse_ldap_uri(i: &[u8]) -> Result<'_, LdapString<'_>> {
     LdapString::from_ber(i)
 }
 
@@ -161,19 +255,32 @@ fn parse_ldap_uri(i: &[u8]) -> Result<LdapString> {
 //      matchedDN          LDAPDN,
 //      diagnosticMessage  LDAPString,
 //      referral           [3] Referral OPTIONAL }
-fn parse_ldap_result_content(i: &[u8]) -> Result<LdapResult> {
+fn parse_ldap_result_content(i: &[u8]) -> Result<'_, LdapResult<'_>> {
     let (i, result_code) = map(parse_ldap_enum_as_u32, ResultCode)(i)?;
     let (i, matched_dn) = LdapDN::from_ber(i)?;
     let (i, diagnostic_message) = LdapString::from_ber(i)?;
-    // TODO: referral
+    let (i, referral) =
+        OptTaggedParser::new(Class::ContextSpecific, Tag(3)).parse_ber(i, |_, data| {
+            let (rem, uris) = many1(complete(parse_ldap_uri))(data)?;
+            check_no_trailing_data(rem)?;
+            Ok((rem, uris))
+        })?;
     let result = LdapResult {
         result_code,
         matched_dn,
         diagnostic_message,
+        referral,
     };
     Ok((i, result))
 }
 
+// Content of operations defined as LDAPResult
+fn parse_ldap_result_op_content(i: &[u8]) -> Result<'_, LdapResult<'_>> {
+    let (i, result) = parse_ldap_result_content(i)?;
+    check_no_trailing_data(i)?;
+    Ok((i, result))
+}
+
 // LDAPMessage ::= SEQUENCE {
 //      messageID       MessageID,
 //      protocolOp      CHOICE {
@@ -202,6 +309,12 @@ fn parse_ldap_result_content(i: &[u8]) -> Result<LdapResult> {
 //      controls       [0] Controls OPTIONAL }
 /// Parse a single LDAP message and return a structure borrowing fields from the input buffer
 ///
+/// If the input contains only the beginning of a message, `Err::Incomplete` is returned, and
+/// parsing can be retried when more data is available.
+///
+/// Errors occurring while parsing the operation are wrapped in
+/// [`LdapError::OperationParse`], with the tag of the operation.
+///
 /// ```rust
 /// use ldap_parser::FromBer;
 /// use ldap_parser::ldap::{LdapMessage, MessageID, ProtocolOp, ProtocolOpTag};
@@ -229,73 +342,360 @@ fn parse_ldap_result_content(i: &[u8]) -> Result<LdapResult> {
 /// ```
 impl<'a> FromBer<'a, LdapError> for LdapMessage<'a> {
     fn from_ber(bytes: &'a [u8]) -> ParseResult<'a, Self, LdapError> {
-        Sequence::from_ber_and_then(bytes, |i| {
-            let (i, message_id) = MessageID::from_ber(i)?;
-            // read header of next element and look tag value
-            let (_, header) = Header::from_ber(i).map_err(Err::convert)?;
-            let (i, protocol_op) = match header.tag().0 {
-                0 => map(BindRequest::from_ber, ProtocolOp::BindRequest)(i),
-                1 => map(BindResponse::from_ber, ProtocolOp::BindResponse)(i),
-                2 => parse_ldap_unbind_request(i),
-                3 => map(SearchRequest::from_ber, ProtocolOp::SearchRequest)(i),
-                4 => map(SearchResultEntry::from_ber, ProtocolOp::SearchResultEntry)(i),
-                5 => map(parse_ldap_search_result_done, ProtocolOp::SearchResultDone)(i),
-                6 => map(ModifyRequest::from_ber, ProtocolOp::ModifyRequest)(i),
-                7 => map(parse_ldap_modify_response, ProtocolOp::ModifyResponse)(i),
-                8 => map(AddRequest::from_ber, ProtocolOp::AddRequest)(i),
-                9 => map(parse_ldap_add_response, ProtocolOp::AddResponse)(i),
-                10 => map(parse_ldap_del_request, ProtocolOp::DelRequest)(i),
-                11 => map(parse_ldap_del_response, ProtocolOp::DelResponse)(i),
-                12 => map(ModDnRequest::from_ber, ProtocolOp::ModDnRequest)(i),
-                13 => map(parse_ldap_moddn_response, ProtocolOp::ModDnResponse)(i),
-                14 => map(CompareRequest::from_ber, ProtocolOp::CompareRequest)(i),
-                15 => map(parse_ldap_compare_response, ProtocolOp::CompareResponse)(i),
-                16 => map(parse_ldap_abandon_request, ProtocolOp::AbandonRequest)(i),
-                19 => map(
-                    parse_ldap_search_result_ref,
-                    ProtocolOp::SearchResultReference,
-                )(i),
-                23 => map(ExtendedRequest::from_ber, ProtocolOp::ExtendedRequest)(i),
-                24 => map(ExtendedResponse::from_ber, ProtocolOp::ExtendedResponse)(i),
-                25 => map(
-                    IntermediateResponse::from_ber,
-                    ProtocolOp::IntermediateResponse,
-                )(i),
-                _ => {
-                    // print_hex_dump(i, 32);
-                    // panic!("Protocol op {} not yet implemented", header.tag.0);
-                    Err(Err::Error(LdapError::InvalidMessageType))
-                }
-            }?;
-            let (i, controls) = OptTaggedParser::new(Class::ContextSpecific, Tag(0))
-                .parse_ber(i, |_, i| many0(complete(Control::from_ber))(i))?;
-            let msg = LdapMessage {
-                message_id,
-                protocol_op,
-                controls,
-            };
-            Ok((i, msg))
-        })
+        let (rem, (msg, _)) =
+            parse_ldap_message_at_offset(bytes, false).map_err(|e| e.map(LdapError::into_inner))?;
+        Ok((rem, msg))
+    }
+}
+
+impl<'a> LdapMessage<'a> {
+    /// Parse a single LDAP message, ignoring invalid controls
+    ///
+    /// Some non-conformant servers send controls with an invalid encoding. With this function,
+    /// if parsing the controls fails, the message is returned with `controls` set to `None`, and
+    /// the error is returned as a warning with the message. Errors in other parts of the message
+    /// are not ignored.
+    ///
+    /// [`LdapMessage::from_ber`] rejects messages with invalid controls.
+    ///
+    /// ```rust
+    /// use ldap_parser::ldap::{LdapMessage, ProtocolOp};
+    ///
+    /// // unbind request, with an OCTET STRING instead of a control
+    /// const DATA: &[u8] = &[0x30, 0x0a, 0x02, 0x01, 0x01, 0x42, 0x00, 0xa0, 0x03, 0x04, 0x01, 0x61];
+    /// let (rem, (msg, warning)) = LdapMessage::from_ber_lenient(DATA).expect("parsing failed");
+    /// assert!(rem.is_empty());
+    /// assert_eq!(msg.protocol_op, ProtocolOp::UnbindRequest);
+    /// assert!(msg.controls.is_none());
+    /// assert!(warning.is_some());
+    /// ```
+    pub fn from_ber_lenient(bytes: &'a [u8]) -> Result<'a, (LdapMessage<'a>, Option<LdapError>)> {
+        let (rem, (msg, warning)) =
+            parse_ldap_message_at_offset(bytes, true).map_err(|e| e.map(LdapError::into_inner))?;
+        Ok((rem, (msg, warning.map(LdapError::into_inner))))
     }
 }
 
+/// Add the offset of `i` relative to the start of the message to errors
+#[inline]
+fn located<'a, T>(res: Result<'a, T>, msg_len: usize, i: &[u8]) -> Result<'a, T> {
+    res.map_err(|e| e.map(|e| e.at_offset(msg_len - i.len())))
+}
+
+/// Parse a LDAP message, recording the offset of the failing element in errors
+///
+/// If `lenient` is true, errors in controls are returned as a warning with the message.
+fn parse_ldap_message_at_offset(
+    bytes: &[u8],
+    lenient: bool,
+) -> Result<'_, (LdapMessage<'_>, Option<LdapError>)> {
+    // offsets of elements are relative to the start of the message
+    let hdr_len = match Header::from_ber(bytes) {
+        Ok((rem, _)) => bytes.len() - rem.len(),
+        Err(_) => 0,
+    };
+    Sequence::from_ber_and_then(bytes, |i| {
+        let msg_len = hdr_len + i.len();
+        let (i, message_id) = located(MessageID::from_ber(i), msg_len, i)?;
+        // read header of next element and look tag value
+        let (_, header) = located(Header::from_ber(i).map_err(Err::convert), msg_len, i)?;
+        let op_start = i;
+        let res = match header.tag().0 {
+            0 => map(BindRequest::from_ber, ProtocolOp::BindRequest)(i),
+            1 => map(BindResponse::from_ber, ProtocolOp::BindResponse)(i),
+            2 => parse_ldap_unbind_request(i),
+            3 => map(SearchRequest::from_ber, ProtocolOp::SearchRequest)(i),
+            4 => map(SearchResultEntry::from_ber, ProtocolOp::SearchResultEntry)(i),
+            5 => map(parse_ldap_search_result_done, ProtocolOp::SearchResultDone)(i),
+            6 => map(ModifyRequest::from_ber, ProtocolOp::ModifyRequest)(i),
+            7 => map(parse_ldap_modify_response, ProtocolOp::ModifyResponse)(i),
+            8 => map(AddRequest::from_ber, ProtocolOp::AddRequest)(i),
+            9 => map(parse_ldap_add_response, ProtocolOp::AddResponse)(i),
+            10 => map(parse_ldap_del_request, ProtocolOp::DelRequest)(i),
+            11 => map(parse_ldap_del_response, ProtocolOp::DelResponse)(i),
+            12 => map(ModDnRequest::from_ber, ProtocolOp::ModDnRequest)(i),
+            13 => map(parse_ldap_moddn_response, ProtocolOp::ModDnResponse)(i),
+            14 => map(CompareRequest::from_ber, ProtocolOp::CompareRequest)(i),
+            15 => map(parse_ldap_compare_response, ProtocolOp::CompareResponse)(i),
+            16 => map(parse_ldap_abandon_request, ProtocolOp::AbandonRequest)(i),
+            19 => map(
+                parse_ldap_search_result_ref,
+                ProtocolOp::SearchResultReference,
+            )(i),
+            23 => map(ExtendedRequest::from_ber, ProtocolOp::ExtendedRequest)(i),
+            24 => map(ExtendedResponse::from_ber, ProtocolOp::ExtendedResponse)(i),
+            25 => map(
+                IntermediateResponse::from_ber,
+                ProtocolOp::IntermediateResponse,
+            )(i),
+            tag => parse_ldap_unknown_op(tag, i),
+        };
+        let res = res.map_err(|e| {
+            e.map(|e| LdapError::OperationParse {
+                tag: ProtocolOpTag(header.tag().0),
+                source: Box::new(e),
+            })
+        });
+        let (i, protocol_op) = located(res, msg_len, op_start)?;
+        let res = located(
+            OptTaggedParser::new(Class::ContextSpecific, Tag(0))
+                .parse_ber(i, |_, i| parse_control_list(i)),
+            msg_len,
+            i,
+        );
+        let (i, controls, warning) = match res {
+            Ok((i, controls)) => (i, controls, None),
+            // the message is complete, so Incomplete is also an invalid encoding
+            Err(e) if lenient => (&i[i.len()..], None, Some(LdapError::from(e))),
+            Err(e) => return Err(e),
+        };
+        let msg = LdapMessage {
+            message_id,
+            protocol_op,
+            controls,
+        };
+        Ok((i, (msg, warning)))
+    })
+}
+
 #[deprecated(
     since = "0.3.0",
     note = "Parsing functions are deprecated. Users should instead use the FromBer trait"
 )]
 #[inline]
-pub fn parse_ldap_message(i: &[u8]) -> Result<LdapMessage> {
+pub fn parse_ldap_message(i: &[u8]) -> Result<'_, LdapMessage<'_>> {
     LdapMessage::from_ber(i)
 }
 
 /// Parse a list of LDAP messages and return a structure borrowing fields from the input buffer
+///
+/// Parsing stops at the first message that cannot be parsed. To locate errors in a batch of
+/// messages, use [`LdapMessageIterator`].
 // Note: we don't use the trait because Vec<_>::from_ber forces the Error type
-pub fn parse_ldap_messages(i: &[u8]) -> Result<Vec<LdapMessage>> {
+pub fn parse_ldap_messages(i: &[u8]) -> Result<'_, Vec<LdapMessage<'_>>> {
     // println!("parse_ldap_message: len={}", i.len());
     // print_hex_dump(i, 32);
     many1(complete(LdapMessage::from_ber))(i)
 }
 
+/// Parse a list of at most `max` LDAP messages
+///
+/// This is the same as [`parse_ldap_messages`], except that if the input contains more than `max`
+/// messages, a [`LdapError::TooManyMessages`] error is returned. This bounds the memory used to
+/// store the messages.
+///
+/// After `max` messages, only the header of the next message is read (see
+/// [`peek_protocol_op_tag`]) to detect that there are more messages.
+pub fn parse_ldap_messages_limited(i: &[u8], max: usize) -> Result<'_, Vec<LdapMessage<'_>>> {
+    if max == 0 {
+        return Err(Err::Error(LdapError::TooManyMessages));
+    }
+    let (rem, messages) = many_m_n(1, max, complete(LdapMessage::from_ber))(i)?;
+    if messages.len() == max && peek_protocol_op_tag(rem).is_ok() {
+        return Err(Err::Error(LdapError::TooManyMessages));
+    }
+    Ok((rem, messages))
+}
+
+/// Parse all the complete LDAP messages of a stream buffer
+///
+/// Messages are parsed until the end of the buffer, or until an incomplete message is found.
+/// Returns the messages, and the unconsumed bytes (the beginning of the next message, if any),
+/// which should be kept and completed with the next read from the stream.
+///
+/// Unlike [`parse_ldap_messages`], an empty list of messages is not an error. A message
+/// which is complete but invalid returns an error.
+pub fn parse_ldap_messages_streaming(
+    i: &[u8],
+) -> std::result::Result<(Vec<LdapMessage<'_>>, &[u8]), LdapError> {
+    let mut messages = Vec::new();
+    let mut i = i;
+    while !i.is_empty() {
+        match LdapMessage::from_ber(i) {
+            Ok((rem, msg)) => {
+                messages.push(msg);
+                i = rem;
+            }
+            Err(Err::Incomplete(n)) => {
+                // the message can also be complete, with an invalid length inside
+                if let Ok((rem, header)) = Header::from_ber(i) {
+                    if let Length::Definite(len) = header.length() {
+                        if rem.len() >= len {
+                            return Err(LdapError::Ber(asn1_rs::Error::Incomplete(n)));
+                        }
+                    }
+                }
+                break;
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok((messages, i))
+}
+
+/// Get the tag of the operation of a LDAP message, without parsing the operation
+///
+/// Only the header of the message, the message ID and the header of the operation are read, so
+/// the input does not need to contain the complete message. The input is not consumed: the
+/// returned remaining bytes are the input.
+///
+/// ```rust
+/// use ldap_parser::ldap::ProtocolOpTag;
+/// use ldap_parser::peek_protocol_op_tag;
+///
+/// // truncated BindRequest
+/// let (_, tag) = peek_protocol_op_tag(&[0x30, 0x0c, 0x02, 0x01, 0x01, 0x60, 0x07]).expect("invalid header");
+/// assert_eq!(tag, ProtocolOpTag::BindRequest);
+/// ```
+pub fn peek_protocol_op_tag(bytes: &[u8]) -> Result<'_, ProtocolOpTag> {
+    let (i, header) = Header::from_ber(bytes).map_err(Err::convert)?;
+    header
+        .assert_tag(Tag::Sequence)
+        .map_err(|e| Err::Error(LdapError::Ber(e)))?;
+    let (i, _) = MessageID::from_ber(i)?;
+    let (_, header) = Header::from_ber(i).map_err(Err::convert)?;
+    if header.class() != Class::Application {
+        return Err(Err::Error(LdapError::InvalidMessageType));
+    }
+    Ok((bytes, ProtocolOpTag(header.tag().0)))
+}
+
+/// Parse a connectionless LDAP (CLDAP) datagram
+///
+/// A CLDAP datagram (for ex. a rootDSE query sent by Active Directory clients on UDP port 389)
+/// contains a single LDAP message, without any framing. Some implementations add padding after
+/// the message: it is returned as the remaining bytes instead of an error, so callers can check
+/// if the datagram contained exactly one message.
+///
+/// Since a datagram cannot be continued, a truncated message returns an error instead of
+/// `Err::Incomplete`.
+///
+/// Messages are expected in the LDAPv3 format ([RFC4511]). The format of [RFC1798], which adds
+/// a `user` field before the operation, is not supported.
+///
+/// [RFC4511]: https://tools.ietf.org/html/rfc4511
+/// [RFC1798]: https://tools.ietf.org/html/rfc1798
+pub fn parse_cldap_message(datagram: &[u8]) -> Result<'_, LdapMessage<'_>> {
+    LdapMessage::from_ber(datagram).map_err(|e| match e {
+        Err::Incomplete(n) => Err::Error(LdapError::Ber(asn1_rs::Error::Incomplete(n))),
+        e => e,
+    })
+}
+
+/// Parse a LDAP message prefixed by its length, as a 4-bytes big-endian integer
+///
+/// This framing is used by some capture and test tools to store messages. The frame must
+/// contain exactly one message: if the length of the frame and of the BER-encoded message are
+/// different, `LdapError::InvalidFrameLength` is returned.
+///
+/// If the input is shorter than the frame, `Err::Incomplete` is returned.
+///
+/// ```rust
+/// use ldap_parser::ldap::{MessageID, ProtocolOp};
+/// use ldap_parser::parse_ldap_message_framed;
+///
+/// // unbind request
+/// const DATA: &[u8] = &[0, 0, 0, 7, 0x30, 0x05, 0x02, 0x01, 0x01, 0x42, 0x00];
+/// let (rem, msg) = parse_ldap_message_framed(DATA).expect("parsing failed");
+/// assert!(rem.is_empty());
+/// assert_eq!(msg.message_id, MessageID(1));
+/// assert_eq!(msg.protocol_op, ProtocolOp::UnbindRequest);
+/// ```
+pub fn parse_ldap_message_framed(i: &[u8]) -> Result<'_, LdapMessage<'_>> {
+    let (i, len) = be_u32(i)?;
+    let (i, frame) = take(len)(i)?;
+    match LdapMessage::from_ber(frame) {
+        Ok((&[], msg)) => Ok((i, msg)),
+        Ok(_) | Err(Err::Incomplete(_)) => Err(Err::Error(LdapError::InvalidFrameLength)),
+        Err(e) => Err(e),
+    }
+}
+
+/// Iterator over a buffer containing LDAP messages
+///
+/// Messages are parsed lazily, one at a time, and borrow fields from the input buffer. Iteration
+/// stops when the buffer is exhausted, or after the first error.
+///
+/// ```rust
+/// use ldap_parser::LdapMessageIterator;
+/// use ldap_parser::ldap::ProtocolOpTag;
+///
+/// static DATA: &[u8] = include_bytes!("../assets/message-search-request-01.bin");
+///
+/// # fn main() {
+/// let mut iter = LdapMessageIterator::new(DATA);
+/// let bind = iter.find(|res| match res {
+///     Ok(msg) => msg.protocol_op.tag() == ProtocolOpTag::BindRequest,
+///     Err(_) => true,
+/// });
+/// assert!(bind.is_none());
+/// # }
+/// ```
+///
+/// Errors are returned as [`LdapError::AtOffset`], with the offset of the failing element relative
+/// to the start of the input buffer.
+#[derive(Debug)]
+pub struct LdapMessageIterator<'a> {
+    data: &'a [u8],
+    offset: usize,
+    done: bool,
+}
+
+impl<'a> LdapMessageIterator<'a> {
+    /// Create an iterator over the messages of `data`
+    pub fn new(data: &'a [u8]) -> Self {
+        LdapMessageIterator {
+            data,
+            offset: 0,
+            done: false,
+        }
+    }
+
+    /// Get the input that has not been parsed yet
+    pub fn remaining(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Get the offset of the next message, relative to the start of the input buffer
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl<'a> Iterator for LdapMessageIterator<'a> {
+    type Item = std::result::Result<LdapMessage<'a>, LdapError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.data.is_empty() {
+            return None;
+        }
+        match parse_ldap_message_at_offset(self.data, false) {
+            Ok((rem, (msg, _))) => {
+                self.offset += self.data.len() - rem.len();
+                self.data = rem;
+                Some(Ok(msg))
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(LdapError::from(e).at_offset(self.offset)))
+            }
+        }
+    }
+}
+
+// protocolOp CHOICE is extensible: keep content of unknown operations
+fn parse_ldap_unknown_op(tag: u32, bytes: &[u8]) -> Result<'_, ProtocolOp<'_>> {
+    let (rem, any) = Any::from_ber(bytes).map_err(Err::convert)?;
+    if any.class() != Class::Application {
+        return Err(Err::Error(LdapError::InvalidMessageType));
+    }
+    let op = ProtocolOp::Unknown {
+        tag,
+        data: Cow::Borrowed(any.data),
+    };
+    Ok((rem, op))
+}
+
 // BindRequest ::= [APPLICATION 0] SEQUENCE {
 //      version                 INTEGER (1 ..  127),
 //      name                    LDAPDN,
@@ -312,6 +712,7 @@ impl<'a> FromBer<'a, LdapError> for BindRequest<'a> {
                 name,
                 authentication,
             };
+            check_no_trailing_data(i)?;
             Ok((i, req))
             // })
         })
@@ -335,13 +736,14 @@ impl<'a> FromBer<'a, LdapError> for BindResponse<'a> {
                 result,
                 server_sasl_creds,
             };
+            check_no_trailing_data(i)?;
             Ok((i, req))
         })
     }
 }
 
 // UnbindRequest ::= [APPLICATION 2] NULL
-fn parse_ldap_unbind_request(bytes: &[u8]) -> Result<ProtocolOp> {
+fn parse_ldap_unbind_request(bytes: &[u8]) -> Result<'_, ProtocolOp<'_>> {
     TaggedParser::from_ber_and_then(Class::Application, 2, bytes, |i| {
         // accept empty input, otherwise expect NULL
         if !i.is_empty() {
@@ -374,11 +776,12 @@ impl<'a> FromBer<'a, LdapError> for SearchRequest<'a> {
             let (i, base_object) = LdapDN::from_ber(i)?;
             let (i, scope) = map(parse_ldap_enum_as_u32, SearchScope)(i)?;
             let (i, deref_aliases) = map(parse_ldap_enum_as_u32, DerefAliases)(i)?;
-            let (i, size_limit) = parse_ldap_int_as_u32(i)?;
-            let (i, time_limit) = parse_ldap_int_as_u32(i)?;
+            let (i, size_limit) = parse_ldap_int_constrained(i)?;
+            let (i, time_limit) = parse_ldap_int_constrained(i)?;
             let (i, types_only) = <bool>::from_ber(i).map_err(Err::convert)?;
             let (i, filter) = Filter::from_ber(i)?;
             let (i, attributes) = parse_attribute_selection(i)?;
+            check_no_trailing_data(i)?;
             let req = SearchRequest {
                 base_object,
                 scope,
@@ -402,6 +805,7 @@ impl<'a> FromBer<'a, LdapError> for SearchResultEntry<'a> {
         TaggedParser::from_ber_and_then(Class::Application, 4, bytes, |i| {
             let (i, object_name) = LdapDN::from_ber(i)?;
             let (i, attributes) = parse_partial_attribute_list(i)?;
+            check_no_trailing_data(i)?;
             let res = SearchResultEntry {
                 object_name,
                 attributes,
@@ -412,8 +816,8 @@ impl<'a> FromBer<'a, LdapError> for SearchResultEntry<'a> {
 }
 
 // SearchResultDone ::= [APPLICATION 5] LDAPResult
-fn parse_ldap_search_result_done(bytes: &[u8]) -> Result<LdapResult> {
-    TaggedParser::from_ber_and_then(Class::Application, 5, bytes, parse_ldap_result_content)
+fn parse_ldap_search_result_done(bytes: &[u8]) -> Result<'_, LdapResult<'_>> {
+    TaggedParser::from_ber_and_then(Class::Application, 5, bytes, parse_ldap_result_op_content)
 }
 
 // ModifyRequest ::= [APPLICATION 6] SEQUENCE {
@@ -429,7 +833,16 @@ impl<'a> FromBer<'a, LdapError> for ModifyRequest<'a> {
     fn from_ber(bytes: &'a [u8]) -> ParseResult<'a, Self, LdapError> {
         TaggedParser::from_ber_and_then(Class::Application, 6, bytes, |i| {
             let (i, object) = LdapDN::from_ber(i)?;
-            let (i, changes) = Sequence::from_ber_and_then(i, many1(complete(Change::from_ber)))?;
+            // changes has no SIZE constraint: an empty list is valid
+            let (i, changes) = Sequence::from_ber_and_then(i, |mut i| {
+                let mut changes = Vec::new();
+                while !i.is_empty() {
+                    let (rem, change) = Change::from_ber(i)?;
+                    changes.push(change);
+                    i = rem;
+                }
+                Ok((i, changes))
+            })?;
             let res = ModifyRequest { object, changes };
             Ok((i, res))
         })
@@ -437,10 +850,11 @@ impl<'a> FromBer<'a, LdapError> for ModifyRequest<'a> {
 }
 
 // ModifyResponse ::= [APPLICATION 7] LDAPResult
-fn parse_ldap_modify_response(bytes: &[u8]) -> Result<ModifyResponse> {
+fn parse_ldap_modify_response(bytes: &[u8]) -> Result<'_, ModifyResponse<'_>> {
     TaggedParser::from_ber_and_then(Class::Application, 7, bytes, |i| {
         let (i, result) = parse_ldap_result_content(i)?;
         let res = ModifyResponse { result };
+        check_no_trailing_data(i)?;
         Ok((i, res))
     })
 }
@@ -453,6 +867,7 @@ impl<'a> FromBer<'a, LdapError> for AddRequest<'a> {
         TaggedParser::from_ber_and_then(Class::Application, 8, bytes, |i| {
             let (i, entry) = LdapDN::from_ber(i)?;
             let (i, attributes) = parse_attribute_list(i)?;
+            check_no_trailing_data(i)?;
             let res = AddRequest { entry, attributes };
             Ok((i, res))
         })
@@ -460,22 +875,22 @@ impl<'a> FromBer<'a, LdapError> for AddRequest<'a> {
 }
 
 // AddResponse ::= [APPLICATION 9] LDAPResult
-fn parse_ldap_add_response(bytes: &[u8]) -> Result<LdapResult> {
-    TaggedParser::from_ber_and_then(Class::Application, 9, bytes, parse_ldap_result_content)
+fn parse_ldap_add_response(bytes: &[u8]) -> Result<'_, LdapResult<'_>> {
+    TaggedParser::from_ber_and_then(Class::Application, 9, bytes, parse_ldap_result_op_content)
 }
 
 // DelRequest ::= [APPLICATION 10] LDAPDN
-fn parse_ldap_del_request(bytes: &[u8]) -> Result<LdapDN> {
+fn parse_ldap_del_request(bytes: &[u8]) -> Result<'_, LdapDN<'_>> {
     TaggedParser::from_ber_and_then(Class::Application, 10, bytes, |i| {
-        let s = std::str::from_utf8(i).or(Err(Err::Error(LdapError::InvalidDN)))?;
+        let s = parse_utf8(i, "LDAPDN")?;
         let oid = LdapDN(Cow::Borrowed(s));
         Ok((&b""[..], oid))
     })
 }
 
 // DelResponse ::= [APPLICATION 11] LDAPResult
-fn parse_ldap_del_response(bytes: &[u8]) -> Result<LdapResult> {
-    TaggedParser::from_ber_and_then(Class::Application, 11, bytes, parse_ldap_result_content)
+fn parse_ldap_del_response(bytes: &[u8]) -> Result<'_, LdapResult<'_>> {
+    TaggedParser::from_ber_and_then(Class::Application, 11, bytes, parse_ldap_result_op_content)
 }
 
 // ModifyDNRequest ::= [APPLICATION 12] SEQUENCE {
@@ -491,7 +906,7 @@ impl<'a> FromBer<'a, LdapError> for ModDnRequest<'a> {
             let (i, deleteoldrdn) = <bool>::from_ber(i).map_err(Err::convert)?;
             let (i, newsuperior) =
                 OptTaggedParser::new(Class::ContextSpecific, Tag(0)).parse_ber(i, |_, i| {
-                    let s = std::str::from_utf8(i).or(Err(Err::Error(LdapError::InvalidDN)))?;
+                    let s = parse_utf8(i, "LDAPDN")?;
                     let oid = LdapDN(Cow::Borrowed(s));
                     Ok((&b""[..], oid))
                 })?;
@@ -501,14 +916,15 @@ impl<'a> FromBer<'a, LdapError> for ModDnRequest<'a> {
                 deleteoldrdn,
                 newsuperior,
             };
+            check_no_trailing_data(i)?;
             Ok((i, res))
         })
     }
 }
 
 // ModifyDNResponse ::= [APPLICATION 13] LDAPResult
-fn parse_ldap_moddn_response(bytes: &[u8]) -> Result<LdapResult> {
-    TaggedParser::from_ber_and_then(Class::Application, 13, bytes, parse_ldap_result_content)
+fn parse_ldap_moddn_response(bytes: &[u8]) -> Result<'_, LdapResult<'_>> {
+    TaggedParser::from_ber_and_then(Class::Application, 13, bytes, parse_ldap_result_op_content)
 }
 
 // CompareRequest ::= [APPLICATION 14] SEQUENCE {
@@ -520,26 +936,29 @@ impl<'a> FromBer<'a, LdapError> for CompareRequest<'a> {
             let (i, entry) = LdapDN::from_ber(i)?;
             let (i, ava) = AttributeValueAssertion::from_ber(i)?;
             let res = CompareRequest { entry, ava };
+            check_no_trailing_data(i)?;
             Ok((i, res))
         })
     }
 }
 
 // CompareResponse ::= [APPLICATION 15] LDAPResult
-fn parse_ldap_compare_response(bytes: &[u8]) -> Result<LdapResult> {
-    TaggedParser::from_ber_and_then(Class::Application, 15, bytes, parse_ldap_result_content)
+fn parse_ldap_compare_response(bytes: &[u8]) -> Result<'_, LdapResult<'_>> {
+    TaggedParser::from_ber_and_then(Class::Application, 15, bytes, parse_ldap_result_op_content)
 }
 
 // AbandonRequest ::= [APPLICATION 16] MessageID
-fn parse_ldap_abandon_request(bytes: &[u8]) -> Result<MessageID> {
+fn parse_ldap_abandon_request(bytes: &[u8]) -> Result<'_, MessageID> {
     let (rem, id) = TaggedValue::<u32, _, Implicit, { Class::APPLICATION }, 16>::from_ber(bytes)
         .map_err(Err::convert)?;
-    Ok((rem, MessageID(id.into_inner())))
+    let id = id.into_inner();
+    check_message_id(id)?;
+    Ok((rem, MessageID(id)))
 }
 
 // SearchResultReference ::= [APPLICATION 19] SEQUENCE
 //                                   SIZE (1..MAX) OF uri URI
-fn parse_ldap_search_result_ref(bytes: &[u8]) -> Result<Vec<LdapString>> {
+fn parse_ldap_search_result_ref(bytes: &[u8]) -> Result<'_, Vec<LdapString<'_>>> {
     TaggedParser::from_ber_and_then(
         Class::Application,
         19,
@@ -556,7 +975,7 @@ impl<'a> FromBer<'a, LdapError> for ExtendedRequest<'a> {
         TaggedParser::from_ber_and_then(Class::Application, 23, bytes, |i| {
             let (i, request_name) =
                 TaggedParser::from_ber_and_then(Class::ContextSpecific, 0, i, |i| {
-                    let s = std::str::from_utf8(i).or(Err(Err::Error(LdapError::InvalidDN)))?;
+                    let s = parse_utf8(i, "LDAPOID")?;
                     let oid = LdapOID(Cow::Borrowed(s));
                     Ok((&b""[..], oid))
                 })?;
@@ -566,6 +985,7 @@ impl<'a> FromBer<'a, LdapError> for ExtendedRequest<'a> {
                 request_name,
                 request_value,
             };
+            check_no_trailing_data(i)?;
             Ok((i, req))
         })
     }
@@ -581,7 +1001,7 @@ impl<'a> FromBer<'a, LdapError> for ExtendedResponse<'a> {
             let (i, result) = parse_ldap_result_content(i)?;
             let (i, response_name) = OptTaggedParser::new(Class::ContextSpecific, Tag(10))
                 .parse_ber(i, |_, i| {
-                    let s = std::str::from_utf8(i).or(Err(Err::Error(LdapError::InvalidDN)))?;
+                    let s = parse_utf8(i, "LDAPOID")?;
                     let oid = LdapOID(Cow::Borrowed(s));
                     Ok((&b""[..], oid))
                 })?;
@@ -592,6 +1012,7 @@ impl<'a> FromBer<'a, LdapError> for ExtendedResponse<'a> {
                 response_name,
                 response_value,
             };
+            check_no_trailing_data(i)?;
             Ok((i, resp))
         })
     }
@@ -605,7 +1026,7 @@ impl<'a> FromBer<'a, LdapError> for IntermediateResponse<'a> {
         TaggedParser::from_ber_and_then(Class::Application, 25, bytes, |i| {
             let (i, response_name) = OptTaggedParser::new(Class::ContextSpecific, Tag(0))
                 .parse_ber(i, |_, i| {
-                    let s = std::str::from_utf8(i).or(Err(Err::Error(LdapError::InvalidDN)))?;
+                    let s = parse_utf8(i, "LDAPOID")?;
                     let oid = LdapOID(Cow::Borrowed(s));
                     Ok((&b""[..], oid))
                 })?;
@@ -615,6 +1036,7 @@ impl<'a> FromBer<'a, LdapError> for IntermediateResponse<'a> {
                 response_name,
                 response_value,
             };
+            check_no_trailing_data(i)?;
             Ok((i, resp))
         })
     }
@@ -661,7 +1083,7 @@ impl<'a> FromBer<'a, LdapError> for AuthenticationChoice<'a> {
 // SaslCredentials ::= SEQUENCE {
 //      mechanism               LDAPString,
 //      credentials             OCTET STRING OPTIONAL }
-fn parse_sasl_credentials(i: &[u8]) -> Result<SaslCredentials> {
+fn parse_sasl_credentials(i: &[u8]) -> Result<'_, SaslCredentials<'_>> {
     let (i, mechanism) = LdapString::from_ber(i)?;
     let (i, credentials) = opt(complete(map(
         parse_ldap_octet_string_as_slice,
@@ -677,18 +1099,27 @@ fn parse_sasl_credentials(i: &[u8]) -> Result<SaslCredentials> {
 // AttributeSelection ::= SEQUENCE OF selector LDAPString
 //      -- The LDAPString is constrained to
 //      -- <attributeSelector> in Section 4.5.1.8
-fn parse_attribute_selection(bytes: &[u8]) -> Result<Vec<LdapString>> {
+fn parse_attribute_selection(bytes: &[u8]) -> Result<'_, Vec<LdapString<'_>>> {
     Sequence::from_ber_and_then(bytes, many0(complete(LdapString::from_ber)))
 }
 
 // PartialAttributeList ::= SEQUENCE OF partialAttribute PartialAttribute
-fn parse_partial_attribute_list(bytes: &[u8]) -> Result<Vec<PartialAttribute>> {
-    Sequence::from_ber_and_then(bytes, many0(complete(PartialAttribute::from_ber)))
+fn parse_partial_attribute_list(bytes: &[u8]) -> Result<'_, Vec<PartialAttribute<'_>>> {
+    Sequence::from_ber_and_then(bytes, many0_presized(PartialAttribute::from_ber))
 }
 
 // AttributeList ::= SEQUENCE OF attribute Attribute
-fn parse_attribute_list(bytes: &[u8]) -> Result<Vec<Attribute>> {
-    Sequence::from_ber_and_then(bytes, many0(complete(Attribute::from_ber)))
+fn parse_attribute_list(bytes: &[u8]) -> Result<'_, Vec<Attribute<'_>>> {
+    Sequence::from_ber_and_then(bytes, |mut i| {
+        // parse explicitly to report errors in attributes
+        let mut attributes = Vec::new();
+        while !i.is_empty() {
+            let (rem, attribute) = Attribute::from_ber(i)?;
+            attributes.push(attribute);
+            i = rem;
+        }
+        Ok((i, attributes))
+    })
 }
 
 // change SEQUENCE {
@@ -698,10 +1129,15 @@ fn parse_attribute_list(bytes: &[u8]) -> Result<Vec<Attribute>> {
 //               replace (2),
 //               ...  },
 //          modification    PartialAttribute }
+//
+// increment (3) is defined in RFC4525
 impl<'a> FromBer<'a, LdapError> for Change<'a> {
     fn from_ber(bytes: &'a [u8]) -> ParseResult<'a, Self, LdapError> {
         Sequence::from_ber_and_then(bytes, |i| {
             let (i, operation) = map(parse_ldap_enum_as_u32, Operation)(i)?;
+            if !operation.is_valid() {
+                return Err(Err::Error(LdapError::InvalidModifyOperation));
+            }
             let (i, modification) = PartialAttribute::from_ber(i)?;
             let change = Change {
                 operation,
@@ -737,6 +1173,82 @@ impl<'a> FromBer<'a, LdapError> for Control<'a> {
     }
 }
 
+impl<'a> Control<'a> {
+    /// Parse a control, rejecting ambiguous encodings
+    ///
+    /// Unlike [`Control::from_ber`], the element following the control type must be either a
+    /// BOOLEAN (the criticality) or an OCTET STRING (the value), and no other element is
+    /// allowed in the control. Unexpected elements are rejected with
+    /// [`LdapError::InvalidControl`].
+    pub fn from_ber_strict(bytes: &'a [u8]) -> ParseResult<'a, Self, LdapError> {
+        Sequence::from_ber_and_then(bytes, |i| {
+            let (i, control_type) = LdapOID::from_ber(i)?;
+            let (i, criticality) = if next_universal_tag(i)? == Some(Tag::Boolean) {
+                <bool>::from_ber(i).map_err(Err::convert)?
+            } else {
+                (i, false)
+            };
+            let (i, control_value) = match next_universal_tag(i)? {
+                None if i.is_empty() => (i, None),
+                Some(Tag::OctetString) => {
+                    let (i, value) = parse_ldap_octet_string_as_slice(i)?;
+                    (i, Some(Cow::Borrowed(value)))
+                }
+                _ => return Err(Err::Error(LdapError::InvalidControl)),
+            };
+            if !i.is_empty() {
+                return Err(Err::Error(LdapError::InvalidControl));
+            }
+            let control = Control {
+                control_type,
+                criticality,
+                control_value,
+            };
+            Ok((i, control))
+        })
+    }
+}
+
+// Controls ::= SEQUENCE OF control Control
+/// Parse a list of controls
+///
+/// The input is either a `SEQUENCE OF Control`, or the `controls [0]` element of a LDAP message.
+/// An element with another tag is rejected with [`LdapError::InvalidControl`].
+pub fn parse_controls(i: &[u8]) -> Result<'_, Vec<Control<'_>>> {
+    let (rem, any) = Any::from_ber(i).map_err(Err::convert)?;
+    let is_sequence = any.class() == Class::Universal && any.tag() == Tag::Sequence;
+    let is_tagged = any.class() == Class::ContextSpecific && any.tag() == Tag(0);
+    if !(is_sequence || is_tagged) || !any.header.is_constructed() {
+        return Err(Err::Error(LdapError::InvalidControl));
+    }
+    let (_, controls) = parse_control_list(any.data)?;
+    Ok((rem, controls))
+}
+
+// Controls ::= SEQUENCE OF control Control
+fn parse_control_list(mut i: &[u8]) -> Result<'_, Vec<Control<'_>>> {
+    let mut controls = Vec::new();
+    while !i.is_empty() {
+        let (next, control) = Control::from_ber(i)?;
+        controls.push(control);
+        i = next;
+    }
+    Ok((i, controls))
+}
+
+/// Get the tag of the next element, if it is a primitive universal element
+fn next_universal_tag(i: &[u8]) -> std::result::Result<Option<Tag>, LdapError> {
+    if i.is_empty() {
+        return Ok(None);
+    }
+    let (_, header) = Header::from_ber(i).map_err(|e| LdapError::from(Err::convert(e)))?;
+    if header.class() == Class::Universal && header.is_primitive() {
+        Ok(Some(header.tag()))
+    } else {
+        Ok(None)
+    }
+}
+
 //
 //
 //
@@ -770,6 +1282,8 @@ mod tests {
             req.authentication,
             AuthenticationChoice::Simple(Cow::Borrowed(b"passwor8d1"))
         );
+        let op: ProtocolOp = req.clone().into();
+        assert_eq!(op, ProtocolOp::BindRequest(req));
     }
 
     #[test]
@@ -784,11 +1298,68 @@ mod tests {
         assert_eq!(&req.name.0, "");
         if let AuthenticationChoice::Sasl(sasl_credentials) = &req.authentication {
             assert_eq!(&sasl_credentials.mechanism.0, "GSS-SPNEGO");
+            assert!(sasl_credentials.credentials_bytes().is_some());
+            assert!(sasl_credentials.external_authz_id().is_none());
         } else {
             panic!("wrong authentication type");
         }
     }
 
+    #[test]
+    fn test_sasl_external_authz_id() {
+        fn authz_id(data: &[u8]) -> Option<String> {
+            let (_, auth) = AuthenticationChoice::from_ber(data).expect("parsing failed");
+            match auth {
+                AuthenticationChoice::Sasl(sasl) => sasl.external_authz_id().map(String::from),
+                _ => panic!("wrong authentication type"),
+            }
+        }
+        // EXTERNAL, without credentials
+        assert_eq!(authz_id(&hex!("a3 0a 04 08 45 58 54 45 52 4e 41 4c")), None);
+        // EXTERNAL, with empty credentials
+        assert_eq!(
+            authz_id(&hex!("a3 0c 04 08 45 58 54 45 52 4e 41 4c 04 00")),
+            Some(String::new())
+        );
+        // EXTERNAL, with authzId "u:x"
+        assert_eq!(
+            authz_id(&hex!("a3 0f 04 08 45 58 54 45 52 4e 41 4c 04 03 75 3a 78")),
+            Some(String::from("u:x"))
+        );
+        // EXTERNAL, with invalid UTF-8
+        assert_eq!(
+            authz_id(&hex!("a3 0d 04 08 45 58 54 45 52 4e 41 4c 04 01 ff")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_sasl_credentials_boundaries() {
+        // The crate has no BER encoder: check instead that the credentials are borrowed from the
+        // exact content of the OCTET STRING, so the original encoding can be recovered.
+        const DATA: &[u8] = include_bytes!("../assets/bind_request_sasl.bin");
+        let (_, req) = BindRequest::from_ber(DATA).expect("parsing failed");
+        let sasl = match &req.authentication {
+            AuthenticationChoice::Sasl(sasl) => sasl,
+            _ => panic!("wrong authentication type"),
+        };
+        let creds = sasl.credentials_bytes().expect("no credentials");
+        let creds_start = creds.as_ptr() as usize - DATA.as_ptr() as usize;
+        // the bytes before the credentials are the header of the OCTET STRING (here, with a
+        // 2-bytes length)
+        let (rem, header) = Header::from_ber(&DATA[creds_start - 4..]).expect("invalid header");
+        assert_eq!(header.tag(), Tag::OctetString);
+        assert_eq!(header.length().definite(), Ok(creds.len()));
+        assert_eq!(rem.as_ptr(), creds.as_ptr());
+        assert_eq!(
+            &DATA[creds_start - 16..creds_start - 4],
+            b"\x04\x0aGSS-SPNEGO"
+        );
+        // the credentials are the last element of the request
+        assert_eq!(creds_start + creds.len(), DATA.len());
+        assert_eq!(&DATA[creds_start..], creds);
+    }
+
     #[test]
     fn test_parse_bind_response_minimal() {
         const DATA: &[u8] = &hex!("61 84 00 00 00 07 0a 01 00 04 00 04 00");
@@ -798,6 +1369,24 @@ mod tests {
         //
         assert!(rem.is_empty());
         assert_eq!(resp.result.result_code, ResultCode::Success);
+        assert!(!resp.has_sasl_creds());
+        assert!(resp.sasl_creds().is_none());
+    }
+
+    #[test]
+    fn test_result_code_display() {
+        assert_eq!(ResultCode::Success.to_string(), "success");
+        assert_eq!(ResultCode::NoSuchObject.to_string(), "noSuchObject");
+        assert_eq!(ResultCode(49).to_string(), "invalidCredentials");
+        assert_eq!(ResultCode(9).to_string(), "unknown(9)");
+        // Debug is not changed
+        assert_eq!(format!("{:?}", ResultCode::Success), "Success");
+        assert_eq!(format!("{:?}", ResultCode(34)), "InvalidDNSyntax");
+        assert_eq!(format!("{:?}", ResultCode(71)), "AffectsMultipleDSAs");
+        assert_eq!(format!("{:?}", ResultCode(90)), "ResultCode(90 / 0x5a)");
+        assert!(ResultCode::Success.is_success());
+        assert!(!ResultCode::Referral.is_success());
+        assert!(ResultCode(10).is_referral());
     }
 
     #[test]
@@ -810,6 +1399,8 @@ mod tests {
         assert!(rem.is_empty());
         assert_eq!(resp.result.result_code, ResultCode::Success);
         assert!(resp.server_sasl_creds.is_some());
+        assert!(resp.has_sasl_creds());
+        assert_eq!(resp.sasl_creds(), resp.server_sasl_creds.as_deref());
     }
 
     #[test]
@@ -835,6 +1426,15 @@ mod tests {
         assert_eq!(&resp.base_object.0, "DC=xx,DC=xxx,DC=xxxxx,DC=net");
         assert_eq!(resp.scope, SearchScope::WholeSubtree);
         assert_eq!(resp.attributes.len(), 1);
+        assert!(!resp.is_types_only());
+        assert_eq!(resp.validate(), Ok(()));
+        let mut req = resp;
+        req.scope = SearchScope(7);
+        assert!(!req.scope.is_valid());
+        assert_eq!(req.validate(), Err(LdapError::InvalidSearchScope));
+        req.scope = SearchScope::SingleLevel;
+        req.deref_aliases = DerefAliases(4);
+        assert_eq!(req.validate(), Err(LdapError::InvalidDerefAliases));
     }
 
     #[test]
@@ -846,6 +1446,18 @@ mod tests {
         //
         assert!(rem.is_empty());
         assert_eq!(resp.attributes.len(), 1);
+        let attr = resp
+            .attribute("samaccountname")
+            .expect("attribute not found");
+        assert_eq!(attr.attr_vals[0].as_str(), Some("xxxxxxxx"));
+        assert!(resp.attribute("cn").is_none());
+        let map = resp.attributes_map();
+        assert_eq!(map.len(), 1);
+        assert_eq!(map["sAMAccountName"], attr.attr_vals.as_slice());
+        assert!(!resp.is_types_only_shaped());
+        let mut entry = resp.clone();
+        entry.attributes[0].attr_vals.clear();
+        assert!(entry.is_types_only_shaped());
     }
 
     #[test]
@@ -857,6 +1469,9 @@ mod tests {
         //
         assert!(rem.is_empty());
         assert_eq!(resp.result_code, ResultCode::Success);
+        let op = ProtocolOp::SearchResultDone(resp);
+        assert!(op.is_response());
+        assert!(!op.is_request());
     }
 
     #[test]
@@ -887,6 +1502,12 @@ mod tests {
             oid!(1.3.6 .1 .4 .1 .1466 .20037).to_string()
         );
         assert!(req.request_value.is_none());
+        let msg = LdapMessage {
+            message_id: MessageID(1),
+            protocol_op: req.clone().into(),
+            controls: None,
+        };
+        assert_eq!(msg.protocol_op, ProtocolOp::ExtendedRequest(req));
     }
 
     #[test]
@@ -913,6 +1534,46 @@ mod tests {
         assert_eq!(req.changes[0].modification.attr_type.0, "description");
     }
 
+    #[test]
+    fn test_parse_modify_request_empty_changes() {
+        const DATA: &[u8] = &hex!("30 09 02 01 01 66 04 04 00 30 00");
+        let (rem, msg) = LdapMessage::from_ber(DATA).expect("parsing failed");
+        assert!(rem.is_empty());
+        assert!(matches!(
+            msg.protocol_op,
+            ProtocolOp::ModifyRequest(ref req) if req.changes.is_empty()
+        ));
+        let (messages, rem) = parse_ldap_messages_streaming(DATA).expect("parsing failed");
+        assert_eq!(messages.len(), 1);
+        assert!(rem.is_empty());
+    }
+
+    #[test]
+    fn test_parse_modify_request_operations() {
+        // replace and increment changes
+        const DATA: &[u8] = &hex!(
+            "66 1c 04 00 30 18
+             30 0a 0a 01 02 30 05 04 01 78 31 00
+             30 0a 0a 01 03 30 05 04 01 78 31 00"
+        );
+        let (_, req) = ModifyRequest::from_ber(DATA).expect("parsing failed");
+        assert_eq!(req.changes[0].operation, Operation::Replace);
+        assert_eq!(req.changes[1].operation, Operation::Increment);
+        let increments: Vec<_> = req.changes_with(Operation::Increment).collect();
+        assert_eq!(increments, vec![&req.changes[1]]);
+        assert_eq!(req.changes_with(Operation::Add).count(), 0);
+        // unknown operation in the second change
+        const INVALID: &[u8] = &hex!(
+            "66 1c 04 00 30 18
+             30 0a 0a 01 02 30 05 04 01 78 31 00
+             30 0a 0a 01 05 30 05 04 01 78 31 00"
+        );
+        assert_eq!(
+            ModifyRequest::from_ber(INVALID),
+            Err(Err::Error(LdapError::InvalidModifyOperation))
+        );
+    }
+
     #[test]
     fn test_parse_modify_response() {
         const DATA: &[u8] = include_bytes!("../assets/modify-response.bin");
@@ -934,6 +1595,12 @@ mod tests {
         assert!(rem.is_empty());
         assert_eq!(&req.entry.0, "cn=username1,ou=users,dc=xxx,dc=internet");
         assert_eq!(req.attributes.len(), 4);
+        let view = EntryView::from(&req);
+        assert_eq!(view.dn(), &req.entry);
+        let attributes: Vec<_> = view.attributes().collect();
+        assert_eq!(attributes.len(), 4);
+        assert_eq!(attributes[0].0, req.attributes[0].attr_type.0);
+        assert_eq!(attributes[0].1, req.attributes[0].attr_vals.as_slice());
     }
 
     #[test]
@@ -1027,6 +1694,753 @@ mod tests {
         assert!(matches!(
             msg.protocol_op,
             ProtocolOp::AbandonRequest(MessageID(5))
-        ))
+        ));
+        assert!(msg.protocol_op.is_request());
+        assert!(!msg.protocol_op.is_response());
+    }
+
+    #[test]
+    fn test_parse_message_id_range() {
+        const MAX: &[u8] = &hex!("30 08 02 04 7f ff ff ff 42 00");
+        let (_, msg) = LdapMessage::from_ber(MAX).expect("parsing failed");
+        assert_eq!(msg.message_id, MessageID(2_147_483_647));
+        const TOO_LARGE: &[u8] = &hex!("30 09 02 05 00 ff ff ff ff 42 00");
+        assert_eq!(
+            LdapMessage::from_ber(TOO_LARGE),
+            Err(Err::Error(LdapError::InvalidMessageId))
+        );
+        // abandon request
+        const ABANDON: &[u8] = &hex!("30 0a 02 01 01 50 05 00 80 00 00 00");
+        assert_eq!(
+            LdapMessage::from_ber(ABANDON),
+            Err(Err::Error(LdapError::OperationParse {
+                tag: ProtocolOpTag::AbandonRequest,
+                source: Box::new(LdapError::InvalidMessageId)
+            }))
+        );
+    }
+
+    #[test]
+    fn test_message_id_as_u32() {
+        let id = MessageID(42);
+        assert_eq!(id.as_u32(), 42);
+        assert_eq!(u32::from(id), 42);
+    }
+
+    #[test]
+    fn test_parse_unknown_op() {
+        const DATA: &[u8] = &hex!("30 06 02 01 07 5e 01 ff");
+        let (rem, msg) = LdapMessage::from_ber(DATA).expect("parsing failed");
+        assert!(rem.is_empty());
+        assert_eq!(msg.message_id, MessageID(7));
+        assert_eq!(msg.protocol_op.tag(), ProtocolOpTag(30));
+        assert_eq!(
+            msg.protocol_op,
+            ProtocolOp::Unknown {
+                tag: 30,
+                data: Cow::Borrowed(&[0xff])
+            }
+        );
+        assert!(!msg.protocol_op.is_request());
+        assert!(!msg.protocol_op.is_response());
+        // not an application tag
+        const DATA2: &[u8] = &hex!("30 06 02 01 07 9e 01 ff");
+        LdapMessage::from_ber(DATA2).expect_err("expected error");
+    }
+
+    #[test]
+    fn test_ldap_message_iterator() {
+        const DATA: &[u8] = &hex!(
+            "30 06 02 01 06 50 01 05
+             30 05 02 01 07 42 00
+             30 ff"
+        );
+        let mut iter = LdapMessageIterator::new(DATA);
+        let msg = iter.next().expect("no message").expect("parsing failed");
+        assert_eq!(msg.message_id, MessageID(6));
+        let msg = iter.next().expect("no message").expect("parsing failed");
+        assert_eq!(msg.protocol_op, ProtocolOp::UnbindRequest);
+        assert_eq!(iter.remaining(), &hex!("30 ff"));
+        assert_eq!(iter.offset(), 15);
+        let err = iter
+            .next()
+            .expect("no message")
+            .expect_err("expected error");
+        assert_eq!(err.offset(), Some(15));
+        assert!(iter.next().is_none());
+        // offset of the failing element (invalid message ID)
+        const DATA2: &[u8] = &hex!(
+            "30 05 02 01 07 42 00
+             30 09 02 05 00 ff ff ff ff 42 00"
+        );
+        let mut iter = LdapMessageIterator::new(DATA2);
+        iter.next().expect("no message").expect("parsing failed");
+        let err = iter
+            .next()
+            .expect("no message")
+            .expect_err("expected error");
+        assert_eq!(err.offset(), Some(9));
+        assert_eq!(err.into_inner(), LdapError::InvalidMessageId);
+        // parse error inside protocolOp
+        const DATA3: &[u8] = &hex!("30 0c 02 01 01 60 07 02 01 03 04 00 85 00");
+        let err = LdapMessageIterator::new(DATA3)
+            .next()
+            .expect("no message")
+            .expect_err("expected error");
+        assert_eq!(err.offset(), Some(5));
+        // empty input
+        assert!(LdapMessageIterator::new(&[]).next().is_none());
+    }
+
+    #[test]
+    fn test_parse_message_with_raw() {
+        const DATA: &[u8] = &hex!("30 05 02 01 07 42 00 30 06 02 01 08 50 01 07");
+        let (rem, (msg, raw)) = LdapMessage::from_ber_with_raw(DATA).expect("parsing failed");
+        assert_eq!(msg.protocol_op, ProtocolOp::UnbindRequest);
+        assert_eq!(raw, &DATA[..7]);
+        let (rem, (msg, raw)) = LdapMessage::from_ber_with_raw(rem).expect("parsing failed");
+        assert_eq!(msg.message_id, MessageID(8));
+        assert_eq!(raw, &DATA[7..]);
+        assert!(rem.is_empty());
+    }
+
+    #[test]
+    fn test_parse_owned() {
+        fn parse(data: Vec<u8>) -> std::result::Result<LdapMessage<'static>, LdapError> {
+            LdapMessage::parse_owned(&data)
+        }
+        let msg = parse(hex!("30 06 02 01 08 50 01 07").to_vec()).expect("parsing failed");
+        assert_eq!(msg.message_id, MessageID(8));
+        assert_eq!(
+            parse(hex!("30 05 02 01 07 42 00 00").to_vec()),
+            Err(LdapError::TrailingData)
+        );
+        assert!(parse(hex!("30 05 02 01").to_vec()).is_err());
+    }
+
+    #[test]
+    fn test_collect_referrals() {
+        // SearchResultReference with 2 URIs, and SearchResultDone with a referral
+        const DATA: &[u8] = &hex!(
+            "30 1b 02 01 02 73 16 04 09 6c 64 61 70 3a 2f 2f 61 2f 04 09 6c 64 61 70 3a 2f 2f 62 2f
+             30 19 02 01 02 65 14 0a 01 0a 04 00 04 00 a3 0b 04 09 6c 64 61 70 3a 2f 2f 61 2f"
+        );
+        let (_, messages) = parse_ldap_messages(DATA).expect("parsing failed");
+        let uris: Vec<&str> = collect_referrals(&messages)
+            .into_iter()
+            .map(|s| s.as_ref())
+            .collect();
+        assert_eq!(uris, vec!["ldap://a/", "ldap://b/", "ldap://a/"]);
+        assert!(collect_referrals(&messages[..0]).is_empty());
+        // the result code of the SearchResultDone is referral
+        assert!(messages[0].referral().is_none());
+        let referral = messages[1].referral().expect("no referral");
+        assert_eq!(referral, &[LdapString(Cow::Borrowed("ldap://a/"))]);
+    }
+
+    #[test]
+    fn test_referral_urls() {
+        // referral, with a valid and an invalid URI
+        const DATA: &[u8] = &hex!(
+            "65 28 0a 01 0a 04 00 04 00 a3 1f
+             04 16 6c 64 61 70 3a 2f 2f 68 6f 73 74 3a 33 38 39 2f 64 63 3d 63 6f 6d
+             04 05 68 74 74 70 3a"
+        );
+        let (_, result) = parse_ldap_search_result_done(DATA).expect("parsing failed");
+        assert_eq!(result.result_code, ResultCode::Referral);
+        let urls = result.referral_urls().expect("no referral");
+        assert_eq!(urls.len(), 2);
+        let url = urls[0].as_ref().expect("invalid URL");
+        assert_eq!(url.host.as_deref(), Some("host"));
+        assert_eq!(url.port, Some(389));
+        assert_eq!(url.dn, "dc=com");
+        assert_eq!(urls[1], Err(LdapError::InvalidUrl));
+    }
+
+    #[test]
+    fn test_parse_trailing_data() {
+        // ModifyResponse with an extra NULL
+        const DATA: &[u8] = &hex!("67 09 0a 01 00 04 00 04 00 05 00");
+        let res = parse_ldap_modify_response(DATA);
+        assert_eq!(res, Err(Err::Error(LdapError::TrailingData)));
+        // SearchRequest (base "", filter (a=*)) with an extra NULL after the attributes
+        const SEARCH: &[u8] =
+            &hex!("63 18 04 00 0a 01 00 0a 01 00 02 01 00 02 01 00 01 01 00 87 01 61 30 00 05 00");
+        assert_eq!(
+            SearchRequest::from_ber(SEARCH),
+            Err(Err::Error(LdapError::TrailingData))
+        );
+        let search = [&hex!("63 16")[..], &SEARCH[2..24]].concat();
+        assert!(SearchRequest::from_ber(&search).is_ok());
+        // SearchResultEntry with an extra NULL after the attributes
+        const ENTRY: &[u8] = &hex!("64 06 04 00 30 00 05 00");
+        assert_eq!(
+            SearchResultEntry::from_ber(ENTRY),
+            Err(Err::Error(LdapError::TrailingData))
+        );
+        assert!(SearchResultEntry::from_ber(&hex!("64 04 04 00 30 00")).is_ok());
+        // AddRequest with an extra NULL after the attributes
+        const ADD: &[u8] = &hex!("68 06 04 00 30 00 05 00");
+        assert_eq!(
+            AddRequest::from_ber(ADD),
+            Err(Err::Error(LdapError::TrailingData))
+        );
+        assert!(AddRequest::from_ber(&hex!("68 04 04 00 30 00")).is_ok());
+        // referral is allowed
+        const REFERRAL: &[u8] =
+            &hex!("67 14 0a 01 0a 04 00 04 00 a3 0b 04 09 6c 64 61 70 3a 2f 2f 68 2f");
+        let (rem, resp) = parse_ldap_modify_response(REFERRAL).expect("parsing failed");
+        assert!(rem.is_empty());
+        assert_eq!(
+            resp.result.referral,
+            Some(vec![LdapString(Cow::Borrowed("ldap://h/"))])
+        );
+    }
+
+    #[test]
+    fn test_message_controls() {
+        // SearchResultDone with a paged results control
+        const DATA: &[u8] = &hex!(
+            "30 2c 02 01 02 65 07 0a 01 00 04 00 04 00 a0 1e
+             30 1c 04 16 31 2e 32 2e 38 34 30 2e 31 31 33 35
+             35 36 2e 31 2e 34 2e 33 31 39 04 02 30 00"
+        );
+        let (_, msg) = LdapMessage::from_ber(DATA).expect("parsing failed");
+        assert_eq!(msg.controls_iter().count(), 1);
+        assert!(msg.control(crate::oids::PAGED_RESULTS).is_some());
+        assert!(msg.control(crate::oids::SYNC_DONE).is_none());
+        let (_, msg) =
+            LdapMessage::from_ber(&hex!("30 05 02 01 07 42 00")).expect("parsing failed");
+        assert_eq!(msg.controls_iter().count(), 0);
+        assert!(msg.control(crate::oids::PAGED_RESULTS).is_none());
+    }
+
+    #[test]
+    fn test_absent_vs_empty_values() {
+        // ExtendedRequest: requestValue absent, or present and empty
+        let (_, req) =
+            ExtendedRequest::from_ber(&hex!("77 05 80 03 31 2e 32")).expect("parsing failed");
+        assert_eq!(req.request_value, None);
+        let (_, req) =
+            ExtendedRequest::from_ber(&hex!("77 07 80 03 31 2e 32 81 00")).expect("parsing failed");
+        assert_eq!(req.request_value.as_deref(), Some(&b""[..]));
+        // ExtendedResponse: responseValue
+        let (_, resp) = ExtendedResponse::from_ber(&hex!("78 07 0a 01 00 04 00 04 00"))
+            .expect("parsing failed");
+        assert_eq!(resp.response_value, None);
+        let (_, resp) = ExtendedResponse::from_ber(&hex!("78 09 0a 01 00 04 00 04 00 8b 00"))
+            .expect("parsing failed");
+        assert_eq!(resp.response_value.as_deref(), Some(&b""[..]));
+        // IntermediateResponse: responseName and responseValue
+        let (_, resp) = IntermediateResponse::from_ber(&hex!("79 00")).expect("parsing failed");
+        assert_eq!((resp.response_name, resp.response_value), (None, None));
+        let (_, resp) =
+            IntermediateResponse::from_ber(&hex!("79 04 80 00 81 00")).expect("parsing failed");
+        assert_eq!(resp.response_name, Some(LdapOID(Cow::Borrowed(""))));
+        assert_eq!(resp.response_value.as_deref(), Some(&b""[..]));
+        // Control: controlValue
+        let (_, c) = Control::from_ber(&hex!("30 05 04 03 31 2e 32")).expect("parsing failed");
+        assert_eq!(c.control_value, None);
+        let (_, c) =
+            Control::from_ber(&hex!("30 07 04 03 31 2e 32 04 00")).expect("parsing failed");
+        assert_eq!(c.control_value.as_deref(), Some(&b""[..]));
+        let (_, c) =
+            Control::from_ber_strict(&hex!("30 07 04 03 31 2e 32 04 00")).expect("parsing failed");
+        assert_eq!(c.control_value.as_deref(), Some(&b""[..]));
+        // BindResponse: serverSaslCreds
+        let (_, resp) =
+            BindResponse::from_ber(&hex!("61 07 0a 01 00 04 00 04 00")).expect("parsing failed");
+        assert_eq!(resp.server_sasl_creds, None);
+        let (_, resp) = BindResponse::from_ber(&hex!("61 09 0a 01 00 04 00 04 00 87 00"))
+            .expect("parsing failed");
+        assert_eq!(resp.sasl_creds(), Some(&b""[..]));
+        // SaslCredentials: credentials
+        let (_, auth) =
+            AuthenticationChoice::from_ber(&hex!("a3 03 04 01 58")).expect("parsing failed");
+        assert!(matches!(auth, AuthenticationChoice::Sasl(ref s) if s.credentials.is_none()));
+        let (_, auth) =
+            AuthenticationChoice::from_ber(&hex!("a3 05 04 01 58 04 00")).expect("parsing failed");
+        assert!(matches!(
+            auth,
+            AuthenticationChoice::Sasl(ref s) if s.credentials_bytes() == Some(&b""[..])
+        ));
+    }
+
+    #[test]
+    fn test_parse_controls() {
+        // two controls: OID "1.2" (critical), and OID "1.3" with an empty value
+        const DATA: &[u8] = &hex!("30 13 30 08 04 03 31 2e 32 01 01 ff 30 07 04 03 31 2e 33 04 00");
+        const TAGGED: &[u8] =
+            &hex!("a0 13 30 08 04 03 31 2e 32 01 01 ff 30 07 04 03 31 2e 33 04 00");
+        let (rem, controls) = parse_controls(DATA).expect("parsing failed");
+        assert!(rem.is_empty());
+        assert_eq!(controls.len(), 2);
+        assert!(controls[0].criticality);
+        assert_eq!(controls[1].control_type.0, "1.3");
+        // [0] tagged, as in LdapMessage
+        let (_, tagged) = parse_controls(TAGGED).expect("parsing failed");
+        assert_eq!(tagged, controls);
+        // empty list
+        let (_, controls) = parse_controls(&hex!("30 00")).expect("parsing failed");
+        assert!(controls.is_empty());
+        assert_eq!(
+            parse_controls(&hex!("31 00")),
+            Err(Err::Error(LdapError::InvalidControl))
+        );
+        assert!(parse_controls(&hex!("30 03 04 01 00")).is_err());
+    }
+
+    #[test]
+    fn test_control_from_ber_strict() {
+        // OID "1.2", critical, with a value
+        const CONTROL: &[u8] = &hex!("30 0b 04 03 31 2e 32 01 01 ff 04 01 00");
+        let (_, c) = Control::from_ber_strict(CONTROL).expect("parsing failed");
+        assert!(c.criticality);
+        assert_eq!(c.control_value.as_deref(), Some(&[0u8][..]));
+        assert_eq!(Control::from_ber(CONTROL).expect("parsing failed").1, c);
+        let (_, c) =
+            Control::from_ber_strict(&hex!("30 05 04 03 31 2e 32")).expect("parsing failed");
+        assert!(!c.criticality && c.control_value.is_none());
+        // INTEGER where the criticality is expected
+        const BAD: &[u8] = &hex!("30 0b 04 03 31 2e 32 02 01 01 04 01 00");
+        assert_eq!(
+            Control::from_ber_strict(BAD),
+            Err(Err::Error(LdapError::InvalidControl))
+        );
+        // trailing element after the value
+        const TRAILING: &[u8] = &hex!("30 0b 04 03 31 2e 32 04 01 00 04 01 00");
+        assert_eq!(
+            Control::from_ber_strict(TRAILING),
+            Err(Err::Error(LdapError::InvalidControl))
+        );
+        assert!(Control::from_ber(TRAILING).is_ok());
+    }
+
+    #[test]
+    fn test_peek_protocol_op_tag() {
+        const DATA: &[u8] = include_bytes!("../assets/message-search-request-01.bin");
+        let (rem, tag) = peek_protocol_op_tag(DATA).expect("parsing failed");
+        assert_eq!(tag, ProtocolOpTag::SearchRequest);
+        assert_eq!(rem, DATA);
+        let (_, tag) = peek_protocol_op_tag(&hex!("30 05 02 01 07 42 00")).expect("parsing failed");
+        assert_eq!(tag, ProtocolOpTag::UnbindRequest);
+        // not a SEQUENCE, and not an application tag
+        assert!(peek_protocol_op_tag(&hex!("31 05 02 01 07 42 00")).is_err());
+        assert_eq!(
+            peek_protocol_op_tag(&hex!("30 05 02 01 07 04 00")),
+            Err(Err::Error(LdapError::InvalidMessageType))
+        );
+        assert!(matches!(
+            peek_protocol_op_tag(&hex!("30 05 02 01")),
+            Err(Err::Incomplete(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_ldap_messages_streaming() {
+        const DATA: &[u8] = &hex!("30 05 02 01 07 42 00 30 05 02 01 08 42 00 30 06 02 01");
+        let (messages, rem) = parse_ldap_messages_streaming(DATA).expect("parsing failed");
+        assert_eq!(messages.len(), 2);
+        assert_eq!(rem, &DATA[14..]);
+        // only a partial message, or no data
+        let (messages, rem) = parse_ldap_messages_streaming(rem).expect("parsing failed");
+        assert!(messages.is_empty());
+        assert_eq!(rem, &DATA[14..]);
+        let (messages, rem) = parse_ldap_messages_streaming(&[]).expect("parsing failed");
+        assert!(messages.is_empty() && rem.is_empty());
+        // a complete message, with an invalid length inside
+        assert!(parse_ldap_messages_streaming(&hex!("30 05 02 01 07 42 05")).is_err());
+        // invalid message after a valid one
+        assert!(parse_ldap_messages_streaming(&hex!("30 05 02 01 07 42 00 31 00")).is_err());
+    }
+
+    #[test]
+    fn test_parse_ldap_messages_limited() {
+        const DATA: &[u8] = &hex!("30 05 02 01 07 42 00 30 05 02 01 08 42 00 ff");
+        let (rem, messages) = parse_ldap_messages_limited(DATA, 2).expect("parsing failed");
+        assert_eq!(messages.len(), 2);
+        assert_eq!(rem, &[0xff]);
+        assert_eq!(
+            parse_ldap_messages_limited(DATA, 1),
+            Err(Err::Error(LdapError::TooManyMessages))
+        );
+        // the next message (a truncated BindRequest) is detected from its header
+        const TRUNCATED: &[u8] = &hex!("30 05 02 01 07 42 00 30 0c 02 01 01 60 07");
+        assert_eq!(
+            parse_ldap_messages_limited(TRUNCATED, 1),
+            Err(Err::Error(LdapError::TooManyMessages))
+        );
+    }
+
+    #[test]
+    fn test_parse_cldap_message() {
+        const DATA: &[u8] = &hex!("30 05 02 01 07 42 00 00 00 00");
+        let (rem, msg) = parse_cldap_message(DATA).expect("parsing failed");
+        assert_eq!(msg.protocol_op, ProtocolOp::UnbindRequest);
+        assert_eq!(rem, &[0, 0, 0]);
+        assert!(matches!(
+            parse_cldap_message(&DATA[..4]),
+            Err(Err::Error(LdapError::Ber(asn1_rs::Error::Incomplete(_))))
+        ));
+    }
+
+    #[test]
+    fn test_string_newtypes_display() {
+        let dn = LdapDN(Cow::Borrowed("dc=example,dc=com"));
+        assert_eq!(format!("base: {}", dn), "base: dc=example,dc=com");
+        let oid = LdapOID(Cow::Borrowed("1.3.6.1.1.8"));
+        assert_eq!(oid.to_string(), "1.3.6.1.1.8");
+        assert_eq!(LdapString(Cow::Borrowed("cn")).as_ref(), "cn");
+    }
+
+    #[test]
+    fn test_ldap_result_helpers() {
+        let mut result = LdapResult {
+            result_code: ResultCode::Success,
+            matched_dn: LdapDN(Cow::Borrowed("")),
+            diagnostic_message: LdapString(Cow::Borrowed("")),
+            referral: None,
+        };
+        assert!(result.is_success());
+        assert!(!result.has_diagnostic() && !result.has_matched_dn());
+        assert_eq!(result.diagnostic(), None);
+        result.result_code = ResultCode::NoSuchObject;
+        result.matched_dn = LdapDN(Cow::Borrowed("dc=example,dc=com"));
+        result.diagnostic_message = LdapString(Cow::Borrowed("entry not found"));
+        assert!(!result.is_success());
+        assert!(result.has_diagnostic() && result.has_matched_dn());
+        assert_eq!(result.diagnostic(), Some("entry not found"));
+    }
+
+    #[test]
+    fn test_parse_filter_recursion_limit() {
+        // build (!(!(...(cn=*))))
+        fn nested_not(depth: usize) -> Vec<u8> {
+            let mut v = hex!("87 02 63 6e").to_vec();
+            for _ in 0..depth {
+                let mut outer = vec![0xa2, v.len() as u8];
+                outer.extend_from_slice(&v);
+                v = outer;
+            }
+            v
+        }
+        let data = nested_not(10);
+        let (rem, _) = Filter::from_ber(&data).expect("parsing failed");
+        assert!(rem.is_empty());
+        Filter::from_ber_with_limit(&data, 11).expect("parsing failed");
+        assert_eq!(
+            Filter::from_ber_with_limit(&data, 10),
+            Err(Err::Error(LdapError::RecursionLimitExceeded))
+        );
+        let data = nested_not(60);
+        assert_eq!(
+            Filter::from_ber(&data),
+            Err(Err::Error(LdapError::RecursionLimitExceeded))
+        );
+        // a too deep sub-filter of and/or is reported, whatever its position
+        let deep = nested_not(10);
+        let present = hex!("87 02 63 6e");
+        for (first, second) in [(&present[..], &deep[..]), (&deep[..], &present[..])] {
+            for tag in [0xa0, 0xa1] {
+                let mut data = vec![tag, (first.len() + second.len()) as u8];
+                data.extend_from_slice(first);
+                data.extend_from_slice(second);
+                Filter::from_ber(&data).expect("parsing failed");
+                assert_eq!(
+                    Filter::from_ber_with_limit(&data, 5),
+                    Err(Err::Error(LdapError::RecursionLimitExceeded))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_filter_set_invalid_element() {
+        // (&(cn=*)<invalid>): the invalid element is not dropped
+        const SECOND: &[u8] = &hex!("a0 07 87 02 63 6e 87 01 ff");
+        assert!(matches!(
+            Filter::from_ber(SECOND),
+            Err(Err::Error(LdapError::InvalidUtf8 { .. }))
+        ));
+        const FIRST: &[u8] = &hex!("a1 07 87 01 ff 87 02 63 6e");
+        assert!(matches!(
+            Filter::from_ber(FIRST),
+            Err(Err::Error(LdapError::InvalidUtf8 { .. }))
+        ));
+        // truncated element
+        const TRUNCATED: &[u8] = &hex!("a0 06 87 02 63 6e 87 05");
+        assert!(Filter::from_ber(TRUNCATED).is_err());
+        // empty set
+        assert!(Filter::from_ber(&hex!("a0 00")).is_err());
+    }
+
+    #[test]
+    fn test_parse_filter_unknown() {
+        // (&(cn=*)[10]{01 02})
+        const DATA: &[u8] = &hex!("a0 08 87 02 63 6e 8a 02 01 02");
+        let (rem, filter) = Filter::from_ber(DATA).expect("parsing failed");
+        assert!(rem.is_empty());
+        let filters = match filter {
+            Filter::And(filters) => filters,
+            _ => panic!("wrong filter type"),
+        };
+        assert_eq!(
+            filters[1],
+            Filter::Unknown {
+                tag: 10,
+                data: Cow::Borrowed(&[1, 2])
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_filter_substrings_order() {
+        const VALID: &[u8] = &hex!("a4 0f 04 02 63 6e 30 09 80 01 61 81 01 62 82 01 63");
+        let (_, filter) = Filter::from_ber(VALID).expect("parsing failed");
+        assert!(matches!(filter, Filter::Substrings(ref f) if f.substrings.len() == 3));
+        // initial twice
+        const TWO_INITIAL: &[u8] = &hex!("a4 0c 04 02 63 6e 30 06 80 01 61 80 01 62");
+        assert_eq!(
+            Filter::from_ber(TWO_INITIAL),
+            Err(Err::Error(LdapError::InvalidSubstring))
+        );
+        // final before any
+        const FINAL_FIRST: &[u8] = &hex!("a4 0c 04 02 63 6e 30 06 82 01 61 81 01 62");
+        assert_eq!(
+            Filter::from_ber(FINAL_FIRST),
+            Err(Err::Error(LdapError::InvalidSubstring))
+        );
+    }
+
+    #[test]
+    fn test_parse_filter_extensible_match() {
+        // (cn:dn:=John)
+        const VALID: &[u8] = &hex!("a9 0d 82 02 63 6e 83 04 4a 6f 68 6e 84 01 ff");
+        let (_, filter) = Filter::from_ber(VALID).expect("parsing failed");
+        assert!(matches!(filter, Filter::ExtensibleMatch(ref mra) if mra.dn_attributes_value()));
+        // neither matchingRule nor type
+        const NO_RULE_NO_TYPE: &[u8] = &hex!("a9 06 83 04 4a 6f 68 6e");
+        assert_eq!(
+            Filter::from_ber(NO_RULE_NO_TYPE),
+            Err(Err::Error(LdapError::InvalidFilterType))
+        );
+    }
+
+    #[test]
+    fn test_parse_filter_unexpected_class() {
+        // SEQUENCE instead of a context-specific filter
+        const SEQUENCE: &[u8] = &hex!("30 04 04 02 63 6e");
+        assert_eq!(
+            Filter::from_ber(SEQUENCE),
+            Err(Err::Error(LdapError::UnexpectedFilterClass {
+                expected: Class::ContextSpecific,
+                found: Class::Universal,
+            }))
+        );
+        // present filter with an APPLICATION tag
+        const APPLICATION: &[u8] = &hex!("47 02 63 6e");
+        assert!(matches!(
+            Filter::from_ber(APPLICATION),
+            Err(Err::Error(LdapError::UnexpectedFilterClass {
+                found: Class::Application,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn test_parse_truncated_incomplete() {
+        const DATA: &[u8] = include_bytes!("../assets/message-search-request-01.bin");
+        for len in 0..DATA.len() {
+            assert!(
+                matches!(LdapMessage::from_ber(&DATA[..len]), Err(Err::Incomplete(_))),
+                "truncated to {} bytes",
+                len
+            );
+        }
+        const REQ: &[u8] = include_bytes!("../assets/search_request.bin");
+        for len in 0..REQ.len() {
+            assert!(
+                matches!(
+                    SearchRequest::from_ber(&REQ[..len]),
+                    Err(Err::Incomplete(_))
+                ),
+                "truncated to {} bytes",
+                len
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_message_operation_error() {
+        // search request with an invalid UTF-8 base object
+        const DATA: &[u8] = &hex!(
+            "30 1d 02 01 02 63 18 04 01 ff 0a 01 00 0a 01 00 02 01 00 02 01 00 01 01 00"
+            "87 02 63 6e 30 00"
+        );
+        let err = LdapError::from(LdapMessage::from_ber(DATA).expect_err("parsing succeeded"));
+        assert_eq!(err.operation_tag(), Some(ProtocolOpTag::SearchRequest));
+        match err {
+            LdapError::OperationParse { source, .. } => {
+                assert_eq!(
+                    *source,
+                    LdapError::InvalidUtf8 {
+                        field: "LDAPDN",
+                        valid_up_to: 0
+                    }
+                )
+            }
+            e => panic!("unexpected error {:?}", e),
+        }
+        // errors are annotated by the iterator with the offset of the operation
+        let err = LdapMessageIterator::new(DATA)
+            .next()
+            .expect("no message")
+            .expect_err("parsing succeeded");
+        assert_eq!(err.offset(), Some(5));
+        assert_eq!(err.operation_tag(), Some(ProtocolOpTag::SearchRequest));
+        // truncated operations are still reported as incomplete
+        assert!(matches!(
+            LdapMessage::from_ber(&DATA[..20]),
+            Err(Err::Incomplete(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_add_request_empty_values() {
+        // attribute "cn" with an empty SET of values
+        const DATA: &[u8] = &hex!("68 0c 04 00 30 08 30 06 04 02 63 6e 31 00");
+        assert_eq!(
+            AddRequest::from_ber(DATA),
+            Err(Err::Error(LdapError::EmptyAttributeValues))
+        );
+        // empty values are allowed in partial attributes
+        let (_, attr) = PartialAttribute::from_ber(&DATA[6..]).expect("parsing failed");
+        assert!(attr.attr_vals.is_empty());
+    }
+
+    #[test]
+    fn test_many0_presized() {
+        // two OCTET STRING, followed by an element truncated
+        const DATA: &[u8] = &hex!("04 01 61 04 00 04 05 00");
+        assert_eq!(count_elements(DATA), 2);
+        assert_eq!(count_elements(&[]), 0);
+        let res = many0_presized(parse_ldap_octet_string_as_slice)(DATA);
+        assert_eq!(res, many0(complete(parse_ldap_octet_string_as_slice))(DATA));
+        let (rem, v) = res.expect("parsing failed");
+        assert_eq!(v, vec![&b"a"[..], &b""[..]]);
+        assert_eq!(v.capacity(), 2);
+        assert_eq!(rem, &DATA[5..]);
+    }
+
+    #[test]
+    fn test_parse_ldap_message_framed() {
+        const DATA: &[u8] = &hex!("00 00 00 07 30 05 02 01 01 42 00 00 00 00 07");
+        let (rem, msg) = parse_ldap_message_framed(DATA).expect("parsing failed");
+        assert_eq!(rem, &DATA[11..]);
+        assert_eq!(msg.protocol_op, ProtocolOp::UnbindRequest);
+        // next frame is truncated
+        assert!(matches!(
+            parse_ldap_message_framed(rem),
+            Err(Err::Incomplete(_))
+        ));
+        assert!(matches!(
+            parse_ldap_message_framed(&DATA[..2]),
+            Err(Err::Incomplete(_))
+        ));
+        // frame longer than the message
+        const LONG: &[u8] = &hex!("00 00 00 08 30 05 02 01 01 42 00 00");
+        assert_eq!(
+            parse_ldap_message_framed(LONG),
+            Err(Err::Error(LdapError::InvalidFrameLength))
+        );
+        // frame shorter than the message
+        const SHORT: &[u8] = &hex!("00 00 00 06 30 05 02 01 01 42 00");
+        assert_eq!(
+            parse_ldap_message_framed(SHORT),
+            Err(Err::Error(LdapError::InvalidFrameLength))
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_utf8() {
+        // latin-1 encoded "café"
+        const DATA: &[u8] = &hex!("04 04 63 61 66 e9");
+        assert_eq!(
+            LdapString::from_ber(DATA),
+            Err(Err::Error(LdapError::InvalidUtf8 {
+                field: "LDAPString",
+                valid_up_to: 3
+            }))
+        );
+        assert_eq!(
+            LdapOID::from_ber(DATA),
+            Err(Err::Error(LdapError::InvalidUtf8 {
+                field: "LDAPOID",
+                valid_up_to: 3
+            }))
+        );
+        let err = LdapError::InvalidUtf8 {
+            field: "LDAPDN",
+            valid_up_to: 3,
+        };
+        assert_eq!(
+            err.to_string(),
+            "Invalid UTF-8 in LDAPDN (valid up to byte 3)"
+        );
+    }
+
+    #[test]
+    fn test_parse_ldap_int_constrained() {
+        const MAX: &[u8] = &hex!("02 04 7f ff ff ff");
+        assert_eq!(
+            parse_ldap_int_constrained(MAX),
+            Ok((&b""[..], LDAP_MAX_INT))
+        );
+        const TOO_LARGE: &[u8] = &hex!("02 05 00 80 00 00 00");
+        assert_eq!(
+            parse_ldap_int_constrained(TOO_LARGE),
+            Err(Err::Error(LdapError::IntegerOutOfRange))
+        );
+        const NEGATIVE: &[u8] = &hex!("02 01 ff");
+        assert_eq!(
+            parse_ldap_int_constrained(NEGATIVE),
+            Err(Err::Error(LdapError::IntegerOutOfRange))
+        );
+        // search request with a size limit of 2147483648
+        const SEARCH: &[u8] = &hex!(
+            "63 1b 04 00 0a 01 00 0a 01 00 02 05 00 80 00 00 00 02 01 00 01 01 00
+             87 02 63 6e 30 00"
+        );
+        assert_eq!(
+            SearchRequest::from_ber(SEARCH),
+            Err(Err::Error(LdapError::IntegerOutOfRange))
+        );
+    }
+
+    #[test]
+    fn test_parse_message_invalid_controls() {
+        // unbind request, with an OCTET STRING instead of a control
+        const DATA: &[u8] = &hex!("30 0a 02 01 01 42 00 a0 03 04 01 61");
+        assert!(LdapMessage::from_ber(DATA).is_err());
+        let (rem, (msg, warning)) = LdapMessage::from_ber_lenient(DATA).expect("parsing failed");
+        assert!(rem.is_empty());
+        assert_eq!(msg.protocol_op, ProtocolOp::UnbindRequest);
+        assert!(msg.controls.is_none());
+        assert!(warning.is_some());
+        // truncated control
+        const TRUNCATED: &[u8] = &hex!("30 0b 02 01 01 42 00 a0 04 30 07 04 01");
+        assert!(LdapMessage::from_ber(TRUNCATED).is_err());
+        let (_, (msg, warning)) = LdapMessage::from_ber_lenient(TRUNCATED).expect("parsing failed");
+        assert!(msg.controls.is_none());
+        assert!(warning.is_some());
+        // valid controls are not affected
+        const VALID: &[u8] = &hex!("30 11 02 01 01 42 00 a0 0a 30 08 04 03 31 2e 32 01 01 ff");
+        let (_, (msg, warning)) = LdapMessage::from_ber_lenient(VALID).expect("parsing failed");
+        assert_eq!(msg.controls.map(|c| c.len()), Some(1));
+        assert!(warning.is_none());
+        // errors in the operation are not ignored
+        const INVALID_OP: &[u8] = &hex!("30 0a 02 01 01 50 05 00 80 00 00 00");
+        assert!(LdapMessage::from_ber_lenient(INVALID_OP).is_err());
     }
 }
@@ -0,0 +1,159 @@
+//! Helpers for SASL mechanisms (requires the `sasl` feature)
+//!
+//! SASL credentials are parsed as opaque values in [`SaslCredentials`](crate::ldap::SaslCredentials)
+//! and [`BindResponse`](crate::ldap::BindResponse). The helpers in this module decode the
+//! credentials of some mechanisms.
+
+use crate::error::LdapError;
+
+/// Parse the directives of a DIGEST-MD5 challenge or response ([RFC2831])
+///
+/// The directives are returned in order, as `(name, value)` pairs. Quoted values are unquoted
+/// and unescaped. Names are not converted to lowercase, and duplicate directives are returned
+/// as-is.
+///
+/// ```rust
+/// use ldap_parser::sasl::parse_sasl_digest_md5;
+///
+/// let challenge = br#"realm="example.com",nonce="OA6MG9tEQGm2hh",qop="auth",algorithm=md5-sess"#;
+/// let directives = parse_sasl_digest_md5(challenge).expect("invalid challenge");
+/// assert_eq!(directives[0], ("realm".to_string(), "example.com".to_string()));
+/// assert_eq!(directives[1].1, "OA6MG9tEQGm2hh");
+/// assert_eq!(directives.len(), 4);
+/// ```
+///
+/// [RFC2831]: https://tools.ietf.org/html/rfc2831
+pub fn parse_sasl_digest_md5(bytes: &[u8]) -> Result<Vec<(String, String)>, LdapError> {
+    let s = std::str::from_utf8(bytes).map_err(|e| LdapError::InvalidUtf8 {
+        field: "digest-challenge",
+        valid_up_to: e.valid_up_to(),
+    })?;
+    let mut directives = Vec::new();
+    let mut rest = s;
+    loop {
+        // directives are separated by commas, and empty elements are allowed
+        rest = rest.trim_start_matches(|c: char| c == ',' || is_lws(c));
+        if rest.is_empty() {
+            break;
+        }
+        let eq = rest.find('=').ok_or(LdapError::InvalidSaslDirective)?;
+        let name = rest[..eq].trim_end_matches(is_lws);
+        if name.is_empty() || !name.chars().all(is_token_char) {
+            return Err(LdapError::InvalidSaslDirective);
+        }
+        rest = rest[eq + 1..].trim_start_matches(is_lws);
+        let value = if let Some(quoted) = rest.strip_prefix('"') {
+            let (value, len) = parse_quoted_string(quoted)?;
+            rest = &quoted[len..];
+            value
+        } else {
+            let end = rest
+                .find(|c: char| c == ',' || is_lws(c))
+                .unwrap_or(rest.len());
+            let value = &rest[..end];
+            if value.is_empty() || !value.chars().all(is_token_char) {
+                return Err(LdapError::InvalidSaslDirective);
+            }
+            rest = &rest[end..];
+            value.to_string()
+        };
+        rest = rest.trim_start_matches(is_lws);
+        if !rest.is_empty() && !rest.starts_with(',') {
+            return Err(LdapError::InvalidSaslDirective);
+        }
+        directives.push((name.to_string(), value));
+    }
+    Ok(directives)
+}
+
+// quoted-string = ( <"> qdstr-val <"> )
+// qdstr-val     = *( qdtext | quoted-pair )
+// quoted-pair   = "\" CHAR
+//
+// Returns the unescaped value, and the length of the input including the closing quote
+fn parse_quoted_string(s: &str) -> Result<(String, usize), LdapError> {
+    let mut value = String::new();
+    let mut chars = s.char_indices();
+    while let Some((idx, c)) = chars.next() {
+        match c {
+            '"' => return Ok((value, idx + 1)),
+            '\\' => {
+                let (_, c) = chars.next().ok_or(LdapError::InvalidSaslDirective)?;
+                value.push(c);
+            }
+            c => value.push(c),
+        }
+    }
+    Err(LdapError::InvalidSaslDirective)
+}
+
+// LWS = [CRLF] 1*( SP | HT )
+fn is_lws(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\r' | '\n')
+}
+
+// token = 1*<any CHAR except CTLs or separators>
+fn is_token_char(c: char) -> bool {
+    c.is_ascii()
+        && !c.is_ascii_control()
+        && !matches!(
+            c,
+            '(' | ')'
+                | '<'
+                | '>'
+                | '@'
+                | ','
+                | ';'
+                | ':'
+                | '\\'
+                | '"'
+                | '/'
+                | '['
+                | ']'
+                | '?'
+                | '='
+                | '{'
+                | '}'
+                | ' '
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sasl_digest_md5() {
+        let directives = parse_sasl_digest_md5(
+            b"realm=\"elwood.innosoft.com\", nonce=\"OA6MG9tEQGm2hh\",qop=\"auth\",\r\n algorithm=md5-sess,,charset=utf-8",
+        )
+        .expect("parsing failed");
+        let names: Vec<&str> = directives.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(names, vec!["realm", "nonce", "qop", "algorithm", "charset"]);
+        assert_eq!(directives[0].1, "elwood.innosoft.com");
+        assert_eq!(directives[3].1, "md5-sess");
+
+        let directives = parse_sasl_digest_md5(br#"realm="a \"b\" \\ c""#).expect("parsing failed");
+        assert_eq!(directives[0].1, r#"a "b" \ c"#);
+        assert_eq!(parse_sasl_digest_md5(b""), Ok(Vec::new()));
+
+        for invalid in [
+            &b"realm"[..],
+            b"=x",
+            b"realm=\"unterminated",
+            b"realm=\"a\"b",
+            b"realm=a b",
+            b"realm=",
+            b"\xff=a",
+        ] {
+            assert!(parse_sasl_digest_md5(invalid).is_err(), "{:?}", invalid);
+        }
+        assert_eq!(
+            parse_sasl_digest_md5(b"realm=a,\xff"),
+            Err(LdapError::InvalidUtf8 {
+                field: "digest-challenge",
+                valid_up_to: 8
+            })
+        );
+    }
+}
@@ -0,0 +1,237 @@
+//! Assembly of search responses
+//!
+//! The response to a search request is a sequence of `SearchResultEntry` and
+//! `SearchResultReference` messages, terminated by a `SearchResultDone` message, all with the
+//! message ID of the request ([RFC4511] section 4.5.2). Responses of concurrent searches can be
+//! interleaved. [`SearchResponseCollector`] groups these messages by message ID.
+//!
+//! ```rust
+//! use ldap_parser::ldap::{MessageID, ResultCode};
+//! use ldap_parser::parse_ldap_messages;
+//! use ldap_parser::search::{Collected, SearchResponseCollector};
+//!
+//! // SearchResultEntry (c=a) and SearchResultDone (success), with message ID 2
+//! static DATA: &[u8] = &[
+//!     0x30, 0x0c, 0x02, 0x01, 0x02, 0x64, 0x07, 0x04, 0x03, 0x63, 0x3d, 0x61, 0x30, 0x00,
+//!     0x30, 0x0c, 0x02, 0x01, 0x02, 0x65, 0x07, 0x0a, 0x01, 0x00, 0x04, 0x00, 0x04, 0x00,
+//! ];
+//!
+//! let (_, messages) = parse_ldap_messages(DATA).expect("parsing failed");
+//! let mut collector = SearchResponseCollector::new();
+//! let mut responses = Vec::new();
+//! for msg in messages {
+//!     match collector.push(msg) {
+//!         Collected::Complete(response) => responses.push(response),
+//!         Collected::Partial => (),
+//!         Collected::Other(msg) => println!("not a search response: {:?}", msg.protocol_op.tag()),
+//!     }
+//! }
+//! assert_eq!(responses.len(), 1);
+//! assert_eq!(responses[0].message_id, MessageID(2));
+//! assert_eq!(responses[0].entries[0].object_name.0, "c=a");
+//! assert_eq!(responses[0].result_code(), Some(ResultCode::Success));
+//! ```
+//!
+//! [RFC4511]: https://tools.ietf.org/html/rfc4511
+
+use crate::ldap::*;
+use std::collections::BTreeMap;
+
+/// The messages of the response to a search request
+///
+/// See [`SearchResponseCollector`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SearchResponse<'a> {
+    /// Message ID of the search request
+    pub message_id: MessageID,
+    /// Entries, in the order of the messages
+    pub entries: Vec<SearchResultEntry<'a>>,
+    /// URIs of each search result reference, in the order of the messages
+    pub references: Vec<Vec<LdapString<'a>>>,
+    /// Result of the search, or `None` if the `SearchResultDone` message was not received
+    pub done: Option<LdapResult<'a>>,
+    /// Controls of the `SearchResultDone` message (for ex. paged results)
+    pub done_controls: Option<Vec<Control<'a>>>,
+}
+
+impl<'a> SearchResponse<'a> {
+    fn new(message_id: MessageID) -> Self {
+        SearchResponse {
+            message_id,
+            entries: Vec::new(),
+            references: Vec::new(),
+            done: None,
+            done_controls: None,
+        }
+    }
+
+    /// Return `true` if the `SearchResultDone` message was received
+    pub fn is_complete(&self) -> bool {
+        self.done.is_some()
+    }
+
+    /// Get the result code of the search, if the `SearchResultDone` message was received
+    pub fn result_code(&self) -> Option<ResultCode> {
+        self.done.as_ref().map(|r| r.result_code)
+    }
+}
+
+/// Result of adding a message to a [`SearchResponseCollector`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Collected<'a> {
+    /// The message was added to the partial response of its search
+    Partial,
+    /// The message completed the response of its search
+    Complete(SearchResponse<'a>),
+    /// The message is not a search response, and is returned unchanged
+    Other(LdapMessage<'a>),
+}
+
+/// Collect the messages of search responses, grouped by message ID
+///
+/// Messages are added using [`push`](Self::push), which returns the response once the
+/// `SearchResultDone` message of a search is received. Responses of searches that did not
+/// complete (for ex. if the stream ended early) can be inspected with
+/// [`pending`](Self::pending), or taken with [`into_pending`](Self::into_pending).
+#[derive(Clone, Debug, Default)]
+pub struct SearchResponseCollector<'a> {
+    pending: BTreeMap<MessageID, SearchResponse<'a>>,
+}
+
+impl<'a> SearchResponseCollector<'a> {
+    /// Create a new, empty collector
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a message to the response of its search
+    ///
+    /// Returns the complete response if `msg` is a `SearchResultDone` message. Messages that
+    /// are not search responses are returned unchanged, so they can be handled by the caller.
+    pub fn push(&mut self, msg: LdapMessage<'a>) -> Collected<'a> {
+        let id = msg.message_id;
+        match msg.protocol_op {
+            ProtocolOp::SearchResultEntry(entry) => {
+                self.response_mut(id).entries.push(entry);
+                Collected::Partial
+            }
+            ProtocolOp::SearchResultReference(uris) => {
+                self.response_mut(id).references.push(uris);
+                Collected::Partial
+            }
+            ProtocolOp::SearchResultDone(result) => {
+                let mut response = self
+                    .pending
+                    .remove(&id)
+                    .unwrap_or_else(|| SearchResponse::new(id));
+                response.done = Some(result);
+                response.done_controls = msg.controls;
+                Collected::Complete(response)
+            }
+            _ => Collected::Other(msg),
+        }
+    }
+
+    fn response_mut(&mut self, id: MessageID) -> &mut SearchResponse<'a> {
+        self.pending
+            .entry(id)
+            .or_insert_with(|| SearchResponse::new(id))
+    }
+
+    /// Get the partial response of the search with message ID `id`, if any message was received
+    pub fn pending(&self, id: MessageID) -> Option<&SearchResponse<'a>> {
+        self.pending.get(&id)
+    }
+
+    /// Return `true` if no search has a partial response
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Take the partial responses of all searches that did not complete, ordered by message ID
+    pub fn into_pending(self) -> Vec<SearchResponse<'a>> {
+        self.pending.into_values().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::borrow::Cow;
+
+    fn message(id: u32, protocol_op: ProtocolOp<'static>) -> LdapMessage<'static> {
+        LdapMessage {
+            message_id: MessageID(id),
+            protocol_op,
+            controls: None,
+        }
+    }
+
+    fn entry(dn: &'static str) -> ProtocolOp<'static> {
+        ProtocolOp::SearchResultEntry(SearchResultEntry {
+            object_name: LdapDN(Cow::Borrowed(dn)),
+            attributes: Vec::new(),
+        })
+    }
+
+    fn done() -> ProtocolOp<'static> {
+        ProtocolOp::SearchResultDone(LdapResult {
+            result_code: ResultCode::Success,
+            matched_dn: LdapDN(Cow::Borrowed("")),
+            diagnostic_message: LdapString(Cow::Borrowed("")),
+            referral: None,
+        })
+    }
+
+    #[test]
+    fn test_search_response_collector() {
+        let mut collector = SearchResponseCollector::new();
+        assert_eq!(
+            collector.push(message(1, entry("cn=a"))),
+            Collected::Partial
+        );
+        assert_eq!(
+            collector.push(message(2, entry("cn=b"))),
+            Collected::Partial
+        );
+        let uris = vec![LdapString(Cow::Borrowed("ldap://example.com/"))];
+        let reference = ProtocolOp::SearchResultReference(uris.clone());
+        assert_eq!(collector.push(message(1, reference)), Collected::Partial);
+        // not a search response
+        let unbind = message(3, ProtocolOp::UnbindRequest);
+        assert_eq!(collector.push(unbind.clone()), Collected::Other(unbind));
+
+        let mut done_msg = message(1, done());
+        done_msg.controls = Some(Vec::new());
+        let response = match collector.push(done_msg) {
+            Collected::Complete(response) => response,
+            _ => panic!("response not complete"),
+        };
+        assert!(response.is_complete());
+        assert_eq!(response.message_id, MessageID(1));
+        assert_eq!(response.entries.len(), 1);
+        assert_eq!(response.entries[0].object_name.0, "cn=a");
+        assert_eq!(response.references, vec![uris]);
+        assert_eq!(response.result_code(), Some(ResultCode::Success));
+        assert_eq!(response.done_controls, Some(Vec::new()));
+        assert!(collector.pending(MessageID(1)).is_none());
+
+        // search 2 did not complete
+        let pending = collector
+            .pending(MessageID(2))
+            .expect("no pending response");
+        assert!(!pending.is_complete());
+        assert_eq!(pending.entries[0].object_name.0, "cn=b");
+        let pending = collector.into_pending();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].message_id, MessageID(2));
+
+        // a search without entries
+        let mut collector = SearchResponseCollector::new();
+        assert!(matches!(
+            collector.push(message(4, done())),
+            Collected::Complete(ref r) if r.entries.is_empty() && r.is_complete()
+        ));
+        assert!(collector.is_empty());
+    }
+}
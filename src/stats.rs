@@ -0,0 +1,179 @@
+//! Metrics about parsed messages
+//!
+//! [`ParseStats`] records the size and nesting of a message, for example to detect abusive
+//! search filters. The metrics are computed from the parsed message, so the plain `from_ber`
+//! parser has no overhead.
+//!
+//! ```rust
+//! use ldap_parser::ldap::LdapMessage;
+//!
+//! static DATA: &[u8] = include_bytes!("../assets/message-search-request-01.bin");
+//!
+//! let (_, (msg, stats)) = LdapMessage::from_ber_with_stats(DATA).expect("parsing failed");
+//! assert_eq!(stats.filter_depth, 4);
+//! assert_eq!(stats.attributes, 22);
+//! assert_eq!(stats.controls, 1);
+//! ```
+
+use crate::error::Result;
+use crate::filter::{Filter, PartialAttribute};
+use crate::ldap::*;
+use asn1_rs::FromBer;
+
+/// Metrics about a parsed message
+///
+/// See [`LdapMessage::from_ber_with_stats`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ParseStats {
+    /// Maximum nesting depth of the filter (1 for a filter without `and`, `or` or `not`, and 0
+    /// if the message has no filter)
+    pub filter_depth: usize,
+    /// Number of items of the filter, including `and`, `or` and `not`
+    pub filter_items: usize,
+    /// Number of attributes (requested attributes, attributes of an entry, or modifications)
+    pub attributes: usize,
+    /// Number of attribute values
+    pub attribute_values: usize,
+    /// Number of controls
+    pub controls: usize,
+    /// Total number of elements of all `SEQUENCE OF` and `SET OF` in the message
+    pub sequence_elements: usize,
+}
+
+impl ParseStats {
+    /// Compute the metrics of a parsed message
+    pub fn from_message(msg: &LdapMessage) -> Self {
+        let mut stats = ParseStats::default();
+        stats.add_op(&msg.protocol_op);
+        if let Some(controls) = &msg.controls {
+            stats.controls = controls.len();
+            stats.sequence_elements += controls.len();
+        }
+        stats
+    }
+
+    fn add_op(&mut self, op: &ProtocolOp) {
+        if let Some(result) = op.result() {
+            self.add_result(result);
+        }
+        match op {
+            ProtocolOp::SearchRequest(req) => {
+                self.filter_depth = self.add_filter(&req.filter);
+                self.add_attribute_selection(req.attributes.len());
+            }
+            ProtocolOp::SearchResultEntry(entry) => self.add_attributes(&entry.attributes),
+            ProtocolOp::SearchResultReference(uris) => self.sequence_elements += uris.len(),
+            ProtocolOp::ModifyRequest(req) => {
+                self.sequence_elements += req.changes.len();
+                for change in &req.changes {
+                    self.add_attribute(&change.modification);
+                }
+            }
+            ProtocolOp::AddRequest(req) => {
+                self.sequence_elements += req.attributes.len();
+                for attr in &req.attributes {
+                    self.attributes += 1;
+                    self.add_values(attr.attr_vals.len());
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn add_result(&mut self, result: &LdapResult) {
+        if let Some(referral) = &result.referral {
+            self.sequence_elements += referral.len();
+        }
+    }
+
+    // Returns the depth of the filter
+    fn add_filter(&mut self, filter: &Filter) -> usize {
+        self.filter_items += 1;
+        match filter {
+            Filter::And(filters) | Filter::Or(filters) => {
+                self.sequence_elements += filters.len();
+                1 + filters
+                    .iter()
+                    .map(|f| self.add_filter(f))
+                    .max()
+                    .unwrap_or(0)
+            }
+            Filter::Not(filter) => 1 + self.add_filter(filter),
+            Filter::Substrings(substrings) => {
+                self.sequence_elements += substrings.substrings.len();
+                1
+            }
+            _ => 1,
+        }
+    }
+
+    fn add_attributes(&mut self, attributes: &[PartialAttribute]) {
+        self.sequence_elements += attributes.len();
+        for attr in attributes {
+            self.add_attribute(attr);
+        }
+    }
+
+    fn add_attribute(&mut self, attr: &PartialAttribute) {
+        self.attributes += 1;
+        self.add_values(attr.attr_vals.len());
+    }
+
+    fn add_attribute_selection(&mut self, n: usize) {
+        self.attributes += n;
+        self.sequence_elements += n;
+    }
+
+    fn add_values(&mut self, n: usize) {
+        self.attribute_values += n;
+        self.sequence_elements += n;
+    }
+}
+
+impl<'a> LdapMessage<'a> {
+    /// Parse a single LDAP message, and compute its metrics
+    ///
+    /// On success, returns the remaining input and a tuple of the message and its metrics (see
+    /// [`ParseStats`]).
+    pub fn from_ber_with_stats(i: &'a [u8]) -> Result<'a, (LdapMessage<'a>, ParseStats)> {
+        let (rem, msg) = Self::from_ber(i)?;
+        let stats = ParseStats::from_message(&msg);
+        Ok((rem, (msg, stats)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::SearchRequestBuilder;
+    use crate::parse_filter_str;
+
+    #[test]
+    fn test_parse_stats() {
+        let filter = parse_filter_str("(&(objectClass=person)(|(cn=a*b*c)(!(uid=x))))")
+            .expect("invalid filter");
+        let req = SearchRequestBuilder::new()
+            .filter(&filter)
+            .attributes(["cn", "mail"])
+            .build();
+        let msg = LdapMessage {
+            message_id: MessageID(1),
+            protocol_op: ProtocolOp::SearchRequest(req),
+            controls: Some(Vec::new()),
+        };
+        let stats = ParseStats::from_message(&msg);
+        assert_eq!(stats.filter_depth, 4);
+        assert_eq!(stats.filter_items, 6);
+        assert_eq!(stats.attributes, 2);
+        assert_eq!(stats.controls, 0);
+        // and (2) + or (2) + substrings (3) + attributes (2)
+        assert_eq!(stats.sequence_elements, 9);
+
+        let msg = LdapMessage {
+            message_id: MessageID(1),
+            protocol_op: ProtocolOp::UnbindRequest,
+            controls: None,
+        };
+        assert_eq!(ParseStats::from_message(&msg), ParseStats::default());
+    }
+}
@@ -0,0 +1,417 @@
+//! Implementations of [`ToStatic`], to detach parsed objects from the input buffer
+
+use crate::filter::*;
+use crate::ldap::*;
+use asn1_rs::ToStatic;
+use std::borrow::Cow;
+
+#[inline]
+fn cow_to_static<B>(b: &B) -> Cow<'static, B>
+where
+    B: ToOwned + ?Sized + 'static,
+{
+    Cow::Owned(b.to_owned())
+}
+
+#[inline]
+fn opt_cow_to_static<B>(c: &Option<Cow<'_, B>>) -> Option<Cow<'static, B>>
+where
+    B: ToOwned + ?Sized + 'static,
+{
+    c.as_deref().map(cow_to_static)
+}
+
+#[inline]
+fn opt_to_static<T: ToStatic>(o: &Option<T>) -> Option<T::Owned> {
+    o.as_ref().map(ToStatic::to_static)
+}
+
+macro_rules! impl_to_static_newtype {
+    ($($name:ident),* $(,)?) => {
+        $(
+            impl ToStatic for $name<'_> {
+                type Owned = $name<'static>;
+
+                fn to_static(&self) -> Self::Owned {
+                    $name(cow_to_static(&*self.0))
+                }
+            }
+        )*
+    };
+}
+
+impl_to_static_newtype!(
+    LdapString,
+    LdapDN,
+    RelativeLdapDN,
+    LdapOID,
+    AttributeDescription,
+    MatchingRuleId,
+    AssertionValue,
+    AttributeValue,
+    SyncCookie,
+);
+
+impl ToStatic for LdapResult<'_> {
+    type Owned = LdapResult<'static>;
+
+    fn to_static(&self) -> Self::Owned {
+        LdapResult {
+            result_code: self.result_code,
+            matched_dn: self.matched_dn.to_static(),
+            diagnostic_message: self.diagnostic_message.to_static(),
+            referral: opt_to_static(&self.referral),
+        }
+    }
+}
+
+impl ToStatic for BindRequest<'_> {
+    type Owned = BindRequest<'static>;
+
+    fn to_static(&self) -> Self::Owned {
+        BindRequest {
+            version: self.version,
+            name: self.name.to_static(),
+            authentication: self.authentication.to_static(),
+        }
+    }
+}
+
+impl ToStatic for SaslCredentials<'_> {
+    type Owned = SaslCredentials<'static>;
+
+    fn to_static(&self) -> Self::Owned {
+        SaslCredentials {
+            mechanism: self.mechanism.to_static(),
+            credentials: opt_cow_to_static(&self.credentials),
+        }
+    }
+}
+
+impl ToStatic for AuthenticationChoice<'_> {
+    type Owned = AuthenticationChoice<'static>;
+
+    fn to_static(&self) -> Self::Owned {
+        match self {
+            AuthenticationChoice::Simple(b) => AuthenticationChoice::Simple(cow_to_static(&**b)),
+            AuthenticationChoice::Sasl(sasl) => AuthenticationChoice::Sasl(sasl.to_static()),
+        }
+    }
+}
+
+impl ToStatic for BindResponse<'_> {
+    type Owned = BindResponse<'static>;
+
+    fn to_static(&self) -> Self::Owned {
+        BindResponse {
+            result: self.result.to_static(),
+            server_sasl_creds: opt_cow_to_static(&self.server_sasl_creds),
+        }
+    }
+}
+
+impl ToStatic for SearchRequest<'_> {
+    type Owned = SearchRequest<'static>;
+
+    fn to_static(&self) -> Self::Owned {
+        SearchRequest {
+            base_object: self.base_object.to_static(),
+            scope: self.scope,
+            deref_aliases: self.deref_aliases,
+            size_limit: self.size_limit,
+            time_limit: self.time_limit,
+            types_only: self.types_only,
+            filter: self.filter.to_static(),
+            attributes: self.attributes.to_static(),
+        }
+    }
+}
+
+impl ToStatic for SearchResultEntry<'_> {
+    type Owned = SearchResultEntry<'static>;
+
+    fn to_static(&self) -> Self::Owned {
+        SearchResultEntry {
+            object_name: self.object_name.to_static(),
+            attributes: self.attributes.to_static(),
+        }
+    }
+}
+
+impl ToStatic for ModifyRequest<'_> {
+    type Owned = ModifyRequest<'static>;
+
+    fn to_static(&self) -> Self::Owned {
+        ModifyRequest {
+            object: self.object.to_static(),
+            changes: self.changes.to_static(),
+        }
+    }
+}
+
+impl ToStatic for ModifyResponse<'_> {
+    type Owned = ModifyResponse<'static>;
+
+    fn to_static(&self) -> Self::Owned {
+        ModifyResponse {
+            result: self.result.to_static(),
+        }
+    }
+}
+
+impl ToStatic for Change<'_> {
+    type Owned = Change<'static>;
+
+    fn to_static(&self) -> Self::Owned {
+        Change {
+            operation: self.operation,
+            modification: self.modification.to_static(),
+        }
+    }
+}
+
+impl ToStatic for AddRequest<'_> {
+    type Owned = AddRequest<'static>;
+
+    fn to_static(&self) -> Self::Owned {
+        AddRequest {
+            entry: self.entry.to_static(),
+            attributes: self.attributes.to_static(),
+        }
+    }
+}
+
+impl ToStatic for ModDnRequest<'_> {
+    type Owned = ModDnRequest<'static>;
+
+    fn to_static(&self) -> Self::Owned {
+        ModDnRequest {
+            entry: self.entry.to_static(),
+            newrdn: self.newrdn.to_static(),
+            deleteoldrdn: self.deleteoldrdn,
+            newsuperior: opt_to_static(&self.newsuperior),
+        }
+    }
+}
+
+impl ToStatic for CompareRequest<'_> {
+    type Owned = CompareRequest<'static>;
+
+    fn to_static(&self) -> Self::Owned {
+        CompareRequest {
+            entry: self.entry.to_static(),
+            ava: self.ava.to_static(),
+        }
+    }
+}
+
+impl ToStatic for ExtendedRequest<'_> {
+    type Owned = ExtendedRequest<'static>;
+
+    fn to_static(&self) -> Self::Owned {
+        ExtendedRequest {
+            request_name: self.request_name.to_static(),
+            request_value: opt_cow_to_static(&self.request_value),
+        }
+    }
+}
+
+impl ToStatic for ExtendedResponse<'_> {
+    type Owned = ExtendedResponse<'static>;
+
+    fn to_static(&self) -> Self::Owned {
+        ExtendedResponse {
+            result: self.result.to_static(),
+            response_name: opt_to_static(&self.response_name),
+            response_value: opt_cow_to_static(&self.response_value),
+        }
+    }
+}
+
+impl ToStatic for IntermediateResponse<'_> {
+    type Owned = IntermediateResponse<'static>;
+
+    fn to_static(&self) -> Self::Owned {
+        IntermediateResponse {
+            response_name: opt_to_static(&self.response_name),
+            response_value: opt_cow_to_static(&self.response_value),
+        }
+    }
+}
+
+impl ToStatic for ProtocolOp<'_> {
+    type Owned = ProtocolOp<'static>;
+
+    fn to_static(&self) -> Self::Owned {
+        match self {
+            ProtocolOp::BindRequest(r) => ProtocolOp::BindRequest(r.to_static()),
+            ProtocolOp::BindResponse(r) => ProtocolOp::BindResponse(r.to_static()),
+            ProtocolOp::UnbindRequest => ProtocolOp::UnbindRequest,
+            ProtocolOp::SearchRequest(r) => ProtocolOp::SearchRequest(r.to_static()),
+            ProtocolOp::SearchResultEntry(r) => ProtocolOp::SearchResultEntry(r.to_static()),
+            ProtocolOp::SearchResultDone(r) => ProtocolOp::SearchResultDone(r.to_static()),
+            ProtocolOp::SearchResultReference(v) => {
+                ProtocolOp::SearchResultReference(v.to_static())
+            }
+            ProtocolOp::ModifyRequest(r) => ProtocolOp::ModifyRequest(r.to_static()),
+            ProtocolOp::ModifyResponse(r) => ProtocolOp::ModifyResponse(r.to_static()),
+            ProtocolOp::AddRequest(r) => ProtocolOp::AddRequest(r.to_static()),
+            ProtocolOp::AddResponse(r) => ProtocolOp::AddResponse(r.to_static()),
+            ProtocolOp::DelRequest(r) => ProtocolOp::DelRequest(r.to_static()),
+            ProtocolOp::DelResponse(r) => ProtocolOp::DelResponse(r.to_static()),
+            ProtocolOp::ModDnRequest(r) => ProtocolOp::ModDnRequest(r.to_static()),
+            ProtocolOp::ModDnResponse(r) => ProtocolOp::ModDnResponse(r.to_static()),
+            ProtocolOp::CompareRequest(r) => ProtocolOp::CompareRequest(r.to_static()),
+            ProtocolOp::CompareResponse(r) => ProtocolOp::CompareResponse(r.to_static()),
+            ProtocolOp::AbandonRequest(id) => ProtocolOp::AbandonRequest(*id),
+            ProtocolOp::ExtendedRequest(r) => ProtocolOp::ExtendedRequest(r.to_static()),
+            ProtocolOp::ExtendedResponse(r) => ProtocolOp::ExtendedResponse(r.to_static()),
+            ProtocolOp::IntermediateResponse(r) => ProtocolOp::IntermediateResponse(r.to_static()),
+            ProtocolOp::Unknown { tag, data } => ProtocolOp::Unknown {
+                tag: *tag,
+                data: cow_to_static(&**data),
+            },
+        }
+    }
+}
+
+impl ToStatic for Control<'_> {
+    type Owned = Control<'static>;
+
+    fn to_static(&self) -> Self::Owned {
+        Control {
+            control_type: self.control_type.to_static(),
+            criticality: self.criticality,
+            control_value: opt_cow_to_static(&self.control_value),
+        }
+    }
+}
+
+impl ToStatic for LdapMessage<'_> {
+    type Owned = LdapMessage<'static>;
+
+    fn to_static(&self) -> Self::Owned {
+        LdapMessage {
+            message_id: self.message_id,
+            protocol_op: self.protocol_op.to_static(),
+            controls: opt_to_static(&self.controls),
+        }
+    }
+}
+
+impl ToStatic for Filter<'_> {
+    type Owned = Filter<'static>;
+
+    fn to_static(&self) -> Self::Owned {
+        match self {
+            Filter::And(v) => Filter::And(v.to_static()),
+            Filter::Or(v) => Filter::Or(v.to_static()),
+            Filter::Not(f) => Filter::Not(Box::new(f.as_ref().to_static())),
+            Filter::EqualityMatch(ava) => Filter::EqualityMatch(ava.to_static()),
+            Filter::Substrings(f) => Filter::Substrings(f.to_static()),
+            Filter::GreaterOrEqual(ava) => Filter::GreaterOrEqual(ava.to_static()),
+            Filter::LessOrEqual(ava) => Filter::LessOrEqual(ava.to_static()),
+            Filter::Present(s) => Filter::Present(s.to_static()),
+            Filter::ApproxMatch(ava) => Filter::ApproxMatch(ava.to_static()),
+            Filter::ExtensibleMatch(mra) => Filter::ExtensibleMatch(mra.to_static()),
+            Filter::Unknown { tag, data } => Filter::Unknown {
+                tag: *tag,
+                data: cow_to_static(&**data),
+            },
+        }
+    }
+}
+
+impl ToStatic for PartialAttribute<'_> {
+    type Owned = PartialAttribute<'static>;
+
+    fn to_static(&self) -> Self::Owned {
+        PartialAttribute {
+            attr_type: self.attr_type.to_static(),
+            attr_vals: self.attr_vals.to_static(),
+        }
+    }
+}
+
+impl ToStatic for Attribute<'_> {
+    type Owned = Attribute<'static>;
+
+    fn to_static(&self) -> Self::Owned {
+        Attribute {
+            attr_type: self.attr_type.to_static(),
+            attr_vals: self.attr_vals.to_static(),
+        }
+    }
+}
+
+impl ToStatic for AttributeValueAssertion<'_> {
+    type Owned = AttributeValueAssertion<'static>;
+
+    fn to_static(&self) -> Self::Owned {
+        AttributeValueAssertion {
+            attribute_desc: self.attribute_desc.to_static(),
+            assertion_value: cow_to_static(&*self.assertion_value),
+        }
+    }
+}
+
+impl ToStatic for MatchingRuleAssertion<'_> {
+    type Owned = MatchingRuleAssertion<'static>;
+
+    fn to_static(&self) -> Self::Owned {
+        MatchingRuleAssertion {
+            matching_rule: opt_to_static(&self.matching_rule),
+            rule_type: opt_to_static(&self.rule_type),
+            assertion_value: self.assertion_value.to_static(),
+            dn_attributes: self.dn_attributes,
+        }
+    }
+}
+
+impl ToStatic for SubstringFilter<'_> {
+    type Owned = SubstringFilter<'static>;
+
+    fn to_static(&self) -> Self::Owned {
+        SubstringFilter {
+            filter_type: self.filter_type.to_static(),
+            substrings: self.substrings.to_static(),
+        }
+    }
+}
+
+impl ToStatic for Substring<'_> {
+    type Owned = Substring<'static>;
+
+    fn to_static(&self) -> Self::Owned {
+        match self {
+            Substring::Initial(b) => Substring::Initial(b.to_static()),
+            Substring::Any(b) => Substring::Any(b.to_static()),
+            Substring::Final(b) => Substring::Final(b.to_static()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use asn1_rs::FromBer;
+
+    fn parse_static(data: &[u8]) -> LdapMessage<'static> {
+        // copy to a temporary buffer, dropped after parsing
+        let buffer = data.to_vec();
+        let (_, msg) = LdapMessage::from_ber(&buffer).expect("parsing failed");
+        msg.to_static()
+    }
+
+    #[test]
+    fn test_message_to_static() {
+        const DATA: &[u8] = include_bytes!("../assets/message-search-request-01.bin");
+        let msg = parse_static(DATA);
+        let (_, expected) = LdapMessage::from_ber(DATA).expect("parsing failed");
+        assert_eq!(msg, expected);
+        assert!(matches!(
+            msg.protocol_op,
+            ProtocolOp::SearchRequest(ref req) if matches!(req.base_object.0, Cow::Owned(_))
+        ));
+    }
+}
@@ -0,0 +1,269 @@
+//! Parser for LDAP URLs ([RFC4516])
+//!
+//! LDAP URLs are used in referrals and search result references.
+//!
+//! [RFC4516]: https://tools.ietf.org/html/rfc4516
+
+use crate::error::LdapError;
+use crate::filter::Filter;
+use crate::filter_string::{hex_value, parse_filter_str};
+use crate::ldap::SearchScope;
+use std::str::FromStr;
+
+/// An extension of a LDAP URL
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LdapUrlExtension {
+    /// `true` if the extension is marked as critical (prefixed by `!`)
+    pub critical: bool,
+    pub extype: String,
+    pub exvalue: Option<String>,
+}
+
+/// A parsed LDAP URL ([RFC4516])
+///
+/// Absent optional fields are `None`. The default values defined in RFC4516 are a scope of
+/// `base` and a filter of `(objectClass=*)`, and an absent host is left to the client
+/// configuration.
+///
+/// [RFC4516]: https://tools.ietf.org/html/rfc4516
+#[derive(Clone, Debug, PartialEq)]
+pub struct LdapUrl {
+    /// URL scheme (`ldap`, `ldaps` or `ldapi`), in lowercase
+    pub scheme: String,
+    pub host: Option<String>,
+    pub port: Option<u16>,
+    /// Base DN (percent-decoded)
+    pub dn: String,
+    pub attributes: Vec<String>,
+    pub scope: Option<SearchScope>,
+    pub filter: Option<Filter<'static>>,
+    pub extensions: Vec<LdapUrlExtension>,
+}
+
+/// Parse a LDAP URL ([RFC4516])
+///
+/// All components are percent-decoded. The filter is parsed using [`parse_filter_str`].
+///
+/// ```rust
+/// use ldap_parser::ldap::SearchScope;
+/// use ldap_parser::parse_ldap_url;
+///
+/// let url = parse_ldap_url("ldap://ldap.example.com:389/o=University%20of%20Michigan,c=US?cn,mail?sub?(cn=Babs)")
+///     .expect("invalid URL");
+/// assert_eq!(url.host.as_deref(), Some("ldap.example.com"));
+/// assert_eq!(url.port, Some(389));
+/// assert_eq!(url.dn, "o=University of Michigan,c=US");
+/// assert_eq!(url.attributes, vec!["cn", "mail"]);
+/// assert_eq!(url.scope, Some(SearchScope::WholeSubtree));
+/// assert!(url.filter.is_some());
+/// ```
+///
+/// [RFC4516]: https://tools.ietf.org/html/rfc4516
+pub fn parse_ldap_url(s: &str) -> Result<LdapUrl, LdapError> {
+    // ldapurl = scheme COLON SLASH SLASH [host [COLON port]]
+    //     [SLASH dn [QUESTION [attributes]
+    //     [QUESTION [scope] [QUESTION [filter]
+    //     [QUESTION extensions]]]]]
+    let sep = s.find("://").ok_or(LdapError::InvalidUrl)?;
+    let scheme = s[..sep].to_ascii_lowercase();
+    if !matches!(scheme.as_str(), "ldap" | "ldaps" | "ldapi") {
+        return Err(LdapError::InvalidUrl);
+    }
+    let rest = &s[sep + 3..];
+    let (hostport, rest) = match rest.find('/') {
+        Some(idx) => (&rest[..idx], Some(&rest[idx + 1..])),
+        None => (rest, None),
+    };
+    let (host, port) = parse_hostport(hostport)?;
+    let mut url = LdapUrl {
+        scheme,
+        host,
+        port,
+        dn: String::new(),
+        attributes: Vec::new(),
+        scope: None,
+        filter: None,
+        extensions: Vec::new(),
+    };
+    let rest = match rest {
+        Some(rest) => rest,
+        None => return Ok(url),
+    };
+    let mut parts = rest.split('?');
+    if let Some(dn) = parts.next() {
+        url.dn = percent_decode(dn)?;
+    }
+    if let Some(attributes) = parts.next() {
+        url.attributes = attributes
+            .split(',')
+            .filter(|a| !a.is_empty())
+            .map(percent_decode)
+            .collect::<Result<_, _>>()?;
+    }
+    if let Some(scope) = parts.next() {
+        url.scope = match scope.to_ascii_lowercase().as_str() {
+            "" => None,
+            "base" => Some(SearchScope::BaseObject),
+            "one" => Some(SearchScope::SingleLevel),
+            "sub" => Some(SearchScope::WholeSubtree),
+            _ => return Err(LdapError::InvalidUrl),
+        };
+    }
+    if let Some(filter) = parts.next() {
+        if !filter.is_empty() {
+            url.filter = Some(parse_filter_str(&percent_decode(filter)?)?);
+        }
+    }
+    if let Some(extensions) = parts.next() {
+        url.extensions = extensions
+            .split(',')
+            .map(parse_extension)
+            .collect::<Result<_, _>>()?;
+    }
+    if parts.next().is_some() {
+        return Err(LdapError::InvalidUrl);
+    }
+    Ok(url)
+}
+
+impl FromStr for LdapUrl {
+    type Err = LdapError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_ldap_url(s)
+    }
+}
+
+// host and port are defined in RFC3986, IPv6 addresses are enclosed in brackets
+fn parse_hostport(s: &str) -> Result<(Option<String>, Option<u16>), LdapError> {
+    if s.is_empty() {
+        return Ok((None, None));
+    }
+    let (host, port) = if s.starts_with('[') {
+        let end = s.find(']').ok_or(LdapError::InvalidUrl)?;
+        match &s[end + 1..] {
+            "" => (&s[..=end], None),
+            p if p.starts_with(':') => (&s[..=end], Some(&p[1..])),
+            _ => return Err(LdapError::InvalidUrl),
+        }
+    } else {
+        match s.rfind(':') {
+            Some(idx) => (&s[..idx], Some(&s[idx + 1..])),
+            None => (s, None),
+        }
+    };
+    let port = match port {
+        Some("") | None => None,
+        Some(p) => Some(p.parse::<u16>().or(Err(LdapError::InvalidUrl))?),
+    };
+    let host = if host.is_empty() {
+        None
+    } else {
+        Some(percent_decode(host)?)
+    };
+    Ok((host, port))
+}
+
+// extension = [EXCLAMATION] extype [EQUALS exvalue]
+fn parse_extension(s: &str) -> Result<LdapUrlExtension, LdapError> {
+    let (critical, s) = match s.strip_prefix('!') {
+        Some(s) => (true, s),
+        None => (false, s),
+    };
+    let (extype, exvalue) = match s.find('=') {
+        Some(idx) => (&s[..idx], Some(percent_decode(&s[idx + 1..])?)),
+        None => (s, None),
+    };
+    if extype.is_empty() {
+        return Err(LdapError::InvalidUrl);
+    }
+    Ok(LdapUrlExtension {
+        critical,
+        extype: percent_decode(extype)?,
+        exvalue,
+    })
+}
+
+// pct-encoded = "%" HEXDIG HEXDIG
+fn percent_decode(s: &str) -> Result<String, LdapError> {
+    let b = s.as_bytes();
+    let mut v = Vec::with_capacity(b.len());
+    let mut idx = 0;
+    while idx < b.len() {
+        if b[idx] == b'%' {
+            let hi = b.get(idx + 1).and_then(|&c| hex_value(c));
+            let lo = b.get(idx + 2).and_then(|&c| hex_value(c));
+            match (hi, lo) {
+                (Some(hi), Some(lo)) => v.push((hi << 4) | lo),
+                _ => return Err(LdapError::InvalidUrl),
+            }
+            idx += 3;
+        } else {
+            v.push(b[idx]);
+            idx += 1;
+        }
+    }
+    String::from_utf8(v).or(Err(LdapError::InvalidUrl))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ldap_url() {
+        let url = parse_ldap_url("ldap:///").expect("invalid URL");
+        assert_eq!(url.scheme, "ldap");
+        assert!(url.host.is_none() && url.port.is_none());
+        assert!(url.dn.is_empty() && url.attributes.is_empty());
+        assert!(url.scope.is_none() && url.filter.is_none());
+
+        let url = parse_ldap_url("LDAPS://host.com").expect("invalid URL");
+        assert_eq!(url.scheme, "ldaps");
+        assert_eq!(url.host.as_deref(), Some("host.com"));
+
+        let url =
+            parse_ldap_url("ldap://[2001:db8::7]:636/c=GB?objectClass?one").expect("invalid URL");
+        assert_eq!(url.host.as_deref(), Some("[2001:db8::7]"));
+        assert_eq!(url.port, Some(636));
+        assert_eq!(url.attributes, vec!["objectClass"]);
+        assert_eq!(url.scope, Some(SearchScope::SingleLevel));
+
+        let url = parse_ldap_url("ldap://ldap.example.com/o=An%20Example%5C2C%20Inc.,c=US")
+            .expect("invalid URL");
+        assert_eq!(url.dn, r"o=An Example\2C Inc.,c=US");
+
+        let url = parse_ldap_url(
+            "ldap:///??sub??!bindname=cn=Manager%2co=Foo,e-bindname=cn=Manager%2co=Foo",
+        )
+        .expect("invalid URL");
+        assert_eq!(url.extensions.len(), 2);
+        assert!(url.extensions[0].critical);
+        assert_eq!(url.extensions[0].extype, "bindname");
+        assert_eq!(
+            url.extensions[0].exvalue.as_deref(),
+            Some("cn=Manager,o=Foo")
+        );
+        assert!(!url.extensions[1].critical);
+    }
+
+    #[test]
+    fn test_parse_ldap_url_errors() {
+        assert_eq!(parse_ldap_url("http://host"), Err(LdapError::InvalidUrl));
+        assert_eq!(parse_ldap_url("ldap:host"), Err(LdapError::InvalidUrl));
+        assert_eq!(
+            parse_ldap_url("ldap://host:99999/"),
+            Err(LdapError::InvalidUrl)
+        );
+        assert_eq!(
+            parse_ldap_url("ldap://host/dc=x??nope"),
+            Err(LdapError::InvalidUrl)
+        );
+        assert_eq!(parse_ldap_url("ldap:///a%2"), Err(LdapError::InvalidUrl));
+        assert_eq!(
+            parse_ldap_url("ldap:///??sub?(cn=a"),
+            Err(LdapError::UnbalancedParentheses)
+        );
+        assert_eq!(parse_ldap_url("ldap:///?????"), Err(LdapError::InvalidUrl));
+    }
+}
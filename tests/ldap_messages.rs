@@ -1,3 +1,4 @@
+use ldap_parser::filter::Filter;
 use ldap_parser::ldap::{LdapMessage, ProtocolOp, SearchScope};
 use ldap_parser::FromBer;
 
@@ -23,3 +24,41 @@ fn test_parse_msg_search_request_01() {
         assert_eq!(req.attributes.len(), 22);
     }
 }
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_msg_search_request_01() {
+    const DATA: &[u8] = include_bytes!("../assets/message-search-request-01.bin");
+    let (_, msg) = LdapMessage::from_ber(DATA).expect("parsing failed");
+    let json = serde_json::to_string(&msg).expect("serialization failed");
+    let msg2: LdapMessage = serde_json::from_str(&json).expect("deserialization failed");
+    assert_eq!(msg, msg2);
+    // strings are borrowed from the input
+    match msg2.protocol_op {
+        ProtocolOp::SearchRequest(req) => {
+            assert!(matches!(req.base_object.0, std::borrow::Cow::Borrowed(_)));
+            assert!(matches!(req.attributes[0].0, std::borrow::Cow::Borrowed(_)));
+        }
+        _ => panic!("not a search request"),
+    }
+}
+
+#[test]
+fn test_parse_filter_from_search_request_01() {
+    const DATA: &[u8] = include_bytes!("../assets/message-search-request-01.bin");
+    let (_, msg) = LdapMessage::from_ber(DATA).expect("parsing failed");
+    let req = match msg.protocol_op {
+        ProtocolOp::SearchRequest(req) => req,
+        _ => panic!("not a search request"),
+    };
+    // The filter starts at offset 0x2b, after the message ID, the search request header, and
+    // the fields before the filter. It has a 4-bytes header (a0 82 01 f8) and 504 bytes of content.
+    let filter_bytes = &DATA[0x2b..0x2b + 4 + 0x1f8];
+    assert_eq!(filter_bytes[0], 0xa0);
+    let (rem, filter) = Filter::from_ber(filter_bytes).expect("parsing failed");
+    assert!(rem.is_empty());
+    assert_eq!(filter, req.filter);
+    // data following the filter is returned as remaining input
+    let (rem, _) = Filter::from_ber(&DATA[0x2b..]).expect("parsing failed");
+    assert_eq!(rem, &DATA[0x2b + 4 + 0x1f8..]);
+}